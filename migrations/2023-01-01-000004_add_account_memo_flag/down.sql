@@ -0,0 +1 @@
+ALTER TABLE accounts DROP COLUMN is_memo;
\ No newline at end of file
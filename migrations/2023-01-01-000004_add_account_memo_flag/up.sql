@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN is_memo BOOLEAN NOT NULL DEFAULT 0;
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_transactions_ledger_id;
+DROP INDEX IF EXISTS idx_accounts_ledger_id;
+ALTER TABLE transactions DROP COLUMN ledger_id;
+ALTER TABLE accounts DROP COLUMN ledger_id;
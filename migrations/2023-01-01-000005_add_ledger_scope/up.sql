@@ -0,0 +1,5 @@
+ALTER TABLE accounts ADD COLUMN ledger_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE transactions ADD COLUMN ledger_id TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX idx_accounts_ledger_id ON accounts (ledger_id);
+CREATE INDEX idx_transactions_ledger_id ON transactions (ledger_id);
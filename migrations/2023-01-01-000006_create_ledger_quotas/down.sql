@@ -0,0 +1 @@
+DROP TABLE ledger_quotas;
\ No newline at end of file
@@ -0,0 +1,6 @@
+CREATE TABLE ledger_quotas (
+    ledger_id TEXT PRIMARY KEY,
+    max_accounts BIGINT,
+    max_transactions BIGINT,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE entries DROP COLUMN amount_flagged;
\ No newline at end of file
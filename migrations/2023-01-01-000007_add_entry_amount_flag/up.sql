@@ -0,0 +1 @@
+ALTER TABLE entries ADD COLUMN amount_flagged BOOLEAN NOT NULL DEFAULT 0;
\ No newline at end of file
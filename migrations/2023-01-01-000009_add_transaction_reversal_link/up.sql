@@ -0,0 +1,3 @@
+ALTER TABLE transactions ADD COLUMN reverses_transaction_id TEXT REFERENCES transactions (id);
+
+CREATE INDEX idx_transactions_reverses ON transactions (reverses_transaction_id);
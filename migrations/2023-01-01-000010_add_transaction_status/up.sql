@@ -0,0 +1,3 @@
+ALTER TABLE transactions ADD COLUMN status TEXT NOT NULL DEFAULT 'posted' CHECK (status IN ('draft', 'posted'));
+
+CREATE INDEX idx_transactions_status ON transactions (status);
@@ -0,0 +1,2 @@
+ALTER TABLE accounts ADD COLUMN opening_balance TEXT NOT NULL DEFAULT '0';
+ALTER TABLE accounts ADD COLUMN opening_date TEXT;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_periods_dates;
+DROP TABLE periods;
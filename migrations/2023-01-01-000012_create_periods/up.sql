@@ -0,0 +1,10 @@
+CREATE TABLE periods (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    closed_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_periods_dates ON periods (start_date, end_date);
@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN voided BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE entries ADD COLUMN reconciled BOOLEAN NOT NULL DEFAULT 0;
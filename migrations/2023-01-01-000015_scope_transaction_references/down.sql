@@ -0,0 +1,30 @@
+CREATE TABLE transactions_old (
+    id TEXT PRIMARY KEY,
+    reference TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL,
+    transaction_date TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    ledger_id TEXT NOT NULL DEFAULT 'default',
+    reverses_transaction_id TEXT REFERENCES transactions (id),
+    status TEXT NOT NULL DEFAULT 'posted' CHECK (status IN ('draft', 'posted')),
+    voided BOOLEAN NOT NULL DEFAULT 0
+);
+
+INSERT INTO transactions_old (
+    id, reference, description, transaction_date, created_at, updated_at,
+    ledger_id, reverses_transaction_id, status, voided
+)
+SELECT
+    id, reference, description, transaction_date, created_at, updated_at,
+    ledger_id, reverses_transaction_id, status, voided
+FROM transactions;
+
+DROP TABLE transactions;
+ALTER TABLE transactions_old RENAME TO transactions;
+
+CREATE INDEX idx_transactions_reference ON transactions (reference);
+CREATE INDEX idx_transactions_date ON transactions (transaction_date);
+CREATE INDEX idx_transactions_ledger_id ON transactions (ledger_id);
+CREATE INDEX idx_transactions_reverses ON transactions (reverses_transaction_id);
+CREATE INDEX idx_transactions_status ON transactions (status);
@@ -0,0 +1,38 @@
+-- SQLite cannot drop a column constraint, so the table is rebuilt without the
+-- column-level UNIQUE on reference. Uniqueness is enforced by the partial
+-- indexes below: globally when period_id is NULL, otherwise per period.
+CREATE TABLE transactions_new (
+    id TEXT PRIMARY KEY,
+    reference TEXT NOT NULL,
+    description TEXT NOT NULL,
+    transaction_date TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    ledger_id TEXT NOT NULL DEFAULT 'default',
+    reverses_transaction_id TEXT REFERENCES transactions (id),
+    status TEXT NOT NULL DEFAULT 'posted' CHECK (status IN ('draft', 'posted')),
+    voided BOOLEAN NOT NULL DEFAULT 0,
+    period_id TEXT REFERENCES periods (id)
+);
+
+INSERT INTO transactions_new (
+    id, reference, description, transaction_date, created_at, updated_at,
+    ledger_id, reverses_transaction_id, status, voided
+)
+SELECT
+    id, reference, description, transaction_date, created_at, updated_at,
+    ledger_id, reverses_transaction_id, status, voided
+FROM transactions;
+
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
+
+CREATE INDEX idx_transactions_reference ON transactions (reference);
+CREATE INDEX idx_transactions_date ON transactions (transaction_date);
+CREATE INDEX idx_transactions_ledger_id ON transactions (ledger_id);
+CREATE INDEX idx_transactions_reverses ON transactions (reverses_transaction_id);
+CREATE INDEX idx_transactions_status ON transactions (status);
+CREATE UNIQUE INDEX idx_transactions_reference_global ON transactions (reference)
+    WHERE period_id IS NULL;
+CREATE UNIQUE INDEX idx_transactions_reference_period ON transactions (period_id, reference)
+    WHERE period_id IS NOT NULL;
@@ -0,0 +1,31 @@
+CREATE TABLE accounts_old (
+    id TEXT PRIMARY KEY,
+    code TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    account_type TEXT NOT NULL CHECK (account_type IN ('asset', 'liability', 'equity', 'revenue', 'expense')),
+    parent_id TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    is_memo BOOLEAN NOT NULL DEFAULT 0,
+    ledger_id TEXT NOT NULL DEFAULT 'default',
+    opening_balance TEXT NOT NULL DEFAULT '0',
+    opening_date TEXT,
+    FOREIGN KEY (parent_id) REFERENCES accounts (id)
+);
+
+INSERT INTO accounts_old (
+    id, code, name, account_type, parent_id, is_active, created_at, updated_at,
+    is_memo, ledger_id, opening_balance, opening_date
+)
+SELECT
+    id, code, name, account_type, parent_id, is_active, created_at, updated_at,
+    is_memo, ledger_id, opening_balance, opening_date
+FROM accounts;
+
+DROP TABLE accounts;
+ALTER TABLE accounts_old RENAME TO accounts;
+
+CREATE INDEX idx_accounts_code ON accounts (code);
+CREATE INDEX idx_accounts_type ON accounts (account_type);
+CREATE INDEX idx_accounts_ledger_id ON accounts (ledger_id);
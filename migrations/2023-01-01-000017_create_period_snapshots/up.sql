@@ -0,0 +1,9 @@
+CREATE TABLE period_snapshots (
+    period_id TEXT NOT NULL REFERENCES periods (id),
+    account_id TEXT NOT NULL,
+    account_code TEXT NOT NULL,
+    account_name TEXT NOT NULL,
+    balance TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (period_id, account_id)
+);
@@ -0,0 +1,4 @@
+CREATE TABLE sequences (
+    name TEXT PRIMARY KEY,
+    value BIGINT NOT NULL
+);
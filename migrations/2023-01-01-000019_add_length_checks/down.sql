@@ -0,0 +1,69 @@
+CREATE TABLE accounts_new (
+    id TEXT PRIMARY KEY,
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    account_type TEXT NOT NULL CHECK (account_type IN ('asset', 'liability', 'equity', 'revenue', 'expense')),
+    parent_id TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    is_memo BOOLEAN NOT NULL DEFAULT 0,
+    ledger_id TEXT NOT NULL DEFAULT 'default',
+    opening_balance TEXT NOT NULL DEFAULT '0',
+    opening_date TEXT,
+    FOREIGN KEY (parent_id) REFERENCES accounts (id)
+);
+
+INSERT INTO accounts_new (
+    id, code, name, account_type, parent_id, is_active, created_at, updated_at,
+    is_memo, ledger_id, opening_balance, opening_date
+)
+SELECT
+    id, code, name, account_type, parent_id, is_active, created_at, updated_at,
+    is_memo, ledger_id, opening_balance, opening_date
+FROM accounts;
+
+DROP TABLE accounts;
+ALTER TABLE accounts_new RENAME TO accounts;
+
+CREATE INDEX idx_accounts_code ON accounts (code);
+CREATE INDEX idx_accounts_type ON accounts (account_type);
+CREATE INDEX idx_accounts_ledger_id ON accounts (ledger_id);
+CREATE UNIQUE INDEX idx_accounts_code_active ON accounts (ledger_id, code)
+    WHERE is_active = 1;
+
+CREATE TABLE transactions_new (
+    id TEXT PRIMARY KEY,
+    reference TEXT NOT NULL,
+    description TEXT NOT NULL,
+    transaction_date TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    ledger_id TEXT NOT NULL DEFAULT 'default',
+    reverses_transaction_id TEXT REFERENCES transactions (id),
+    status TEXT NOT NULL DEFAULT 'posted' CHECK (status IN ('draft', 'posted')),
+    voided BOOLEAN NOT NULL DEFAULT 0,
+    period_id TEXT REFERENCES periods (id)
+);
+
+INSERT INTO transactions_new (
+    id, reference, description, transaction_date, created_at, updated_at,
+    ledger_id, reverses_transaction_id, status, voided, period_id
+)
+SELECT
+    id, reference, description, transaction_date, created_at, updated_at,
+    ledger_id, reverses_transaction_id, status, voided, period_id
+FROM transactions;
+
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
+
+CREATE INDEX idx_transactions_reference ON transactions (reference);
+CREATE INDEX idx_transactions_date ON transactions (transaction_date);
+CREATE INDEX idx_transactions_ledger_id ON transactions (ledger_id);
+CREATE INDEX idx_transactions_reverses ON transactions (reverses_transaction_id);
+CREATE INDEX idx_transactions_status ON transactions (status);
+CREATE UNIQUE INDEX idx_transactions_reference_global ON transactions (reference)
+    WHERE period_id IS NULL;
+CREATE UNIQUE INDEX idx_transactions_reference_period ON transactions (period_id, reference)
+    WHERE period_id IS NOT NULL;
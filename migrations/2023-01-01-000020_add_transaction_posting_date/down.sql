@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_transactions_posting_date;
+ALTER TABLE transactions DROP COLUMN posting_date;
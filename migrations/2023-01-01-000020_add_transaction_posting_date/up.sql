@@ -0,0 +1,6 @@
+-- The date a transaction counts from in balances, reports and period locks.
+-- Existing transactions post on their document date.
+ALTER TABLE transactions ADD COLUMN posting_date TEXT NOT NULL DEFAULT '';
+UPDATE transactions SET posting_date = transaction_date;
+
+CREATE INDEX idx_transactions_posting_date ON transactions (posting_date);
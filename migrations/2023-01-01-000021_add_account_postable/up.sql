@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN postable BOOLEAN NOT NULL DEFAULT 1;
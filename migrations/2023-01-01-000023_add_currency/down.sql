@@ -0,0 +1,2 @@
+ALTER TABLE entries DROP COLUMN currency;
+ALTER TABLE accounts DROP COLUMN currency;
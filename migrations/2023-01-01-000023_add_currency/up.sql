@@ -0,0 +1,4 @@
+-- Existing rows are left blank here and given LEDGER_CURRENCY at startup by
+-- seed::backfill_currency, since a migration cannot read the configuration
+ALTER TABLE accounts ADD COLUMN currency TEXT NOT NULL DEFAULT '';
+ALTER TABLE entries ADD COLUMN currency TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,9 @@
+CREATE TABLE idempotency_keys (
+    ledger_id TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (ledger_id, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys (created_at);
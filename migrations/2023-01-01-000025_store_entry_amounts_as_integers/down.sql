@@ -0,0 +1,41 @@
+CREATE TABLE entries_old (
+    id TEXT PRIMARY KEY,
+    transaction_id TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    debit_amount TEXT NOT NULL DEFAULT '0',
+    credit_amount TEXT NOT NULL DEFAULT '0',
+    description TEXT,
+    created_at TEXT NOT NULL,
+    amount_flagged BOOLEAN NOT NULL DEFAULT 0,
+    reconciled BOOLEAN NOT NULL DEFAULT 0,
+    currency TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY (transaction_id) REFERENCES transactions (id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts (id)
+);
+
+INSERT INTO entries_old (
+    id, transaction_id, account_id, debit_amount, credit_amount,
+    description, created_at, amount_flagged, reconciled, currency
+)
+SELECT
+    id, transaction_id, account_id,
+    COALESCE(
+        (SELECT raw_value FROM unreadable_amounts u WHERE u.entry_id = entries.id AND u.field = 'debit_amount'),
+        (debit_amount / 10000) || '.' || substr('0000' || (debit_amount % 10000), -4, 4)
+    ),
+    COALESCE(
+        (SELECT raw_value FROM unreadable_amounts u WHERE u.entry_id = entries.id AND u.field = 'credit_amount'),
+        (credit_amount / 10000) || '.' || substr('0000' || (credit_amount % 10000), -4, 4)
+    ),
+    description, created_at, amount_flagged, reconciled, currency
+FROM entries;
+
+DROP INDEX IF EXISTS idx_entries_account_id;
+DROP INDEX IF EXISTS idx_entries_transaction_id;
+DROP TABLE entries;
+ALTER TABLE entries_old RENAME TO entries;
+
+CREATE INDEX idx_entries_transaction_id ON entries (transaction_id);
+CREATE INDEX idx_entries_account_id ON entries (account_id);
+
+DROP TABLE unreadable_amounts;
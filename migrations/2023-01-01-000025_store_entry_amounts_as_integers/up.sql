@@ -0,0 +1,71 @@
+-- Amounts that cannot be read as a non-negative decimal with at most four
+-- places are stored as 0 and their entry flagged; the original text is kept
+-- here for review through GET /admin/corrupt-amounts
+CREATE TABLE unreadable_amounts (
+    entry_id TEXT NOT NULL,
+    field TEXT NOT NULL,
+    raw_value TEXT NOT NULL,
+    PRIMARY KEY (entry_id, field)
+);
+
+INSERT INTO unreadable_amounts (entry_id, field, raw_value)
+SELECT id, 'debit_amount', debit_amount FROM entries
+WHERE NOT (debit_amount GLOB '[0-9]*'
+    AND debit_amount NOT GLOB '*[^0-9.]*'
+    AND debit_amount NOT GLOB '*.*.*'
+    AND (instr(debit_amount, '.') = 0 OR length(debit_amount) - instr(debit_amount, '.') BETWEEN 1 AND 4)
+    AND (CASE instr(debit_amount, '.') WHEN 0 THEN length(debit_amount) ELSE instr(debit_amount, '.') - 1 END) <= 14);
+
+INSERT INTO unreadable_amounts (entry_id, field, raw_value)
+SELECT id, 'credit_amount', credit_amount FROM entries
+WHERE NOT (credit_amount GLOB '[0-9]*'
+    AND credit_amount NOT GLOB '*[^0-9.]*'
+    AND credit_amount NOT GLOB '*.*.*'
+    AND (instr(credit_amount, '.') = 0 OR length(credit_amount) - instr(credit_amount, '.') BETWEEN 1 AND 4)
+    AND (CASE instr(credit_amount, '.') WHEN 0 THEN length(credit_amount) ELSE instr(credit_amount, '.') - 1 END) <= 14);
+
+CREATE TABLE entries_new (
+    id TEXT PRIMARY KEY,
+    transaction_id TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    debit_amount BIGINT NOT NULL DEFAULT 0,
+    credit_amount BIGINT NOT NULL DEFAULT 0,
+    description TEXT,
+    created_at TEXT NOT NULL,
+    amount_flagged BOOLEAN NOT NULL DEFAULT 0,
+    reconciled BOOLEAN NOT NULL DEFAULT 0,
+    currency TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY (transaction_id) REFERENCES transactions (id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts (id),
+    CONSTRAINT debit_amount_is_integer CHECK (typeof(debit_amount) = 'integer'),
+    CONSTRAINT credit_amount_is_integer CHECK (typeof(credit_amount) = 'integer')
+);
+
+INSERT INTO entries_new (
+    id, transaction_id, account_id, debit_amount, credit_amount,
+    description, created_at, amount_flagged, reconciled, currency
+)
+SELECT
+    id, transaction_id, account_id,
+    CASE
+        WHEN id IN (SELECT entry_id FROM unreadable_amounts WHERE field = 'debit_amount') THEN 0
+        ELSE CAST(CASE instr(debit_amount, '.') WHEN 0 THEN debit_amount ELSE substr(debit_amount, 1, instr(debit_amount, '.') - 1) END AS INTEGER) * 10000
+            + CAST(substr(CASE instr(debit_amount, '.') WHEN 0 THEN '' ELSE substr(debit_amount, instr(debit_amount, '.') + 1) END || '0000', 1, 4) AS INTEGER)
+    END,
+    CASE
+        WHEN id IN (SELECT entry_id FROM unreadable_amounts WHERE field = 'credit_amount') THEN 0
+        ELSE CAST(CASE instr(credit_amount, '.') WHEN 0 THEN credit_amount ELSE substr(credit_amount, 1, instr(credit_amount, '.') - 1) END AS INTEGER) * 10000
+            + CAST(substr(CASE instr(credit_amount, '.') WHEN 0 THEN '' ELSE substr(credit_amount, instr(credit_amount, '.') + 1) END || '0000', 1, 4) AS INTEGER)
+    END,
+    description, created_at,
+    amount_flagged OR id IN (SELECT entry_id FROM unreadable_amounts),
+    reconciled, currency
+FROM entries;
+
+DROP INDEX IF EXISTS idx_entries_account_id;
+DROP INDEX IF EXISTS idx_entries_transaction_id;
+DROP TABLE entries;
+ALTER TABLE entries_new RENAME TO entries;
+
+CREATE INDEX idx_entries_transaction_id ON entries (transaction_id);
+CREATE INDEX idx_entries_account_id ON entries (account_id);
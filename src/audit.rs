@@ -0,0 +1,45 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::clock::Clock;
+use crate::errors::AppError;
+use crate::models::AuditLogEntry;
+use crate::schema::audit_log;
+
+pub const ENTITY_ACCOUNT: &str = "account";
+pub const ENTITY_TRANSACTION: &str = "transaction";
+pub const ENTITY_ENTRY: &str = "entry";
+
+pub const ACTION_CREATED: &str = "created";
+pub const ACTION_UPDATED: &str = "updated";
+pub const ACTION_DELETED: &str = "deleted";
+pub const ACTION_POSTED: &str = "posted";
+pub const ACTION_VOIDED: &str = "voided";
+pub const ACTION_REVERSED: &str = "reversed";
+pub const ACTION_RECONCILED: &str = "reconciled";
+
+/// Appends a row to the audit log. Call it on the same connection (and database
+/// transaction) as the change it describes so both commit or roll back together.
+pub fn record(
+    conn: &mut SqliteConnection,
+    clock: &dyn Clock,
+    entity_type: &str,
+    entity_id: &str,
+    action: &str,
+    details: Option<serde_json::Value>,
+) -> Result<(), AppError> {
+    let entry = AuditLogEntry {
+        id: Uuid::new_v4().to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id: entity_id.to_string(),
+        action: action.to_string(),
+        details: details.map(|d| d.to_string()),
+        created_at: clock.now_rfc3339(),
+    };
+
+    diesel::insert_into(audit_log::table)
+        .values(&entry)
+        .execute(conn)?;
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Normalized bank feed transactions, as produced by aggregators such as
+//! Nordigen or Plaid, and the rules that pick a contra account for each.
+//!
+//! `amount` is signed from the account holder's view: positive for money into
+//! the bank account, negative for money going out.
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct NormalizedBankTxn {
+    pub date: String,
+    pub amount: Decimal,
+    pub description: String,
+    pub counterparty: Option<String>,
+}
+
+/// Sends every transaction whose description contains `pattern`
+/// (case-insensitively) to `contra_account_id`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct CategorizationRule {
+    pub pattern: String,
+    pub contra_account_id: String,
+}
+
+/// Contra accounts picked by the direction of a transaction when no rule or
+/// explicit default applies: money in goes to `income`, money out to `expense`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignContras {
+    pub income: Option<String>,
+    pub expense: Option<String>,
+}
+
+impl SignContras {
+    pub fn for_amount(&self, amount: Decimal) -> Option<&str> {
+        if amount.is_sign_positive() {
+            self.income.as_deref()
+        } else {
+            self.expense.as_deref()
+        }
+    }
+}
+
+/// The first rule matching `description`, in the order given.
+pub fn categorize<'a>(
+    rules: &'a [CategorizationRule],
+    description: &str,
+) -> Option<&'a CategorizationRule> {
+    let description = description.to_lowercase();
+    rules
+        .iter()
+        .find(|rule| description.contains(&rule.pattern.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, contra_account_id: &str) -> CategorizationRule {
+        CategorizationRule {
+            pattern: pattern.to_string(),
+            contra_account_id: contra_account_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = vec![rule("coffee", "meals"), rule("shop", "supplies")];
+
+        let matched = categorize(&rules, "Coffee Shop Downtown").unwrap();
+
+        assert_eq!(matched.contra_account_id, "meals");
+    }
+
+    #[test]
+    fn test_sign_picks_income_or_expense_contra() {
+        let contras = SignContras {
+            income: Some("sales".to_string()),
+            expense: None,
+        };
+
+        assert_eq!(contras.for_amount(Decimal::new(1000, 2)), Some("sales"));
+        assert_eq!(contras.for_amount(Decimal::new(-450, 2)), None);
+    }
+
+    #[test]
+    fn test_match_ignores_case() {
+        let rules = vec![rule("PAYROLL", "salaries")];
+
+        assert!(categorize(&rules, "Monthly payroll run").is_some());
+        assert!(categorize(&rules, "Rent").is_none());
+    }
+}
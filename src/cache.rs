@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+use crate::models::{AccountTreeNode, TrialBalance};
+
+/// Most trial balances kept at once; the least recently read is dropped first.
+const TRIAL_BALANCE_CACHE_CAPACITY: usize = 64;
+
+/// In-memory trial balances keyed by their date range and whether drafts are included.
+///
+/// Anything that can move a balance (posting, deleting, account changes) must call
+/// `invalidate` so the next read recomputes from the database. Readers take
+/// `generation` before computing and pass it to `insert`, which drops the result
+/// if an invalidation happened in between.
+pub struct TrialBalanceCache {
+    capacity: usize,
+    state: Mutex<TrialBalanceCacheState>,
+}
+
+#[derive(Default)]
+struct TrialBalanceCacheState {
+    /// Each trial balance with the tick it was last read or stored at.
+    entries: HashMap<String, (TrialBalance, u64)>,
+    tick: u64,
+    generation: u64,
+}
+
+impl Default for TrialBalanceCache {
+    fn default() -> Self {
+        Self::with_capacity(TRIAL_BALANCE_CACHE_CAPACITY)
+    }
+}
+
+impl TrialBalanceCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(TrialBalanceCacheState::default()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<TrialBalance> {
+        let mut state = self.state.lock().ok()?;
+        state.tick += 1;
+        let tick = state.tick;
+        state
+            .entries
+            .get_mut(key)
+            .map(|(trial_balance, last_used)| {
+                *last_used = tick;
+                trial_balance.clone()
+            })
+    }
+
+    /// Changes on every `invalidate`.
+    pub fn generation(&self) -> u64 {
+        self.state.lock().map_or(0, |state| state.generation)
+    }
+
+    /// Stores a trial balance computed after `generation` was read, unless the
+    /// cache has been invalidated since.
+    pub fn insert(&self, key: String, trial_balance: TrialBalance, generation: u64) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if state.generation != generation || self.capacity == 0 {
+            return;
+        }
+
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(key, (trial_balance, tick));
+    }
+
+    pub fn invalidate(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.entries.clear();
+            state.generation += 1;
+        }
+    }
+}
+
+/// The account hierarchy served by `GET /accounts/tree`, built on the first read.
+///
+/// Every handler that creates, updates or deletes accounts must call
+/// `invalidate` so the next read rebuilds it.
+#[derive(Default)]
+pub struct AccountTreeCache {
+    tree: RwLock<Option<Vec<AccountTreeNode>>>,
+}
+
+impl AccountTreeCache {
+    pub fn get(&self) -> Option<Vec<AccountTreeNode>> {
+        self.tree.read().ok().and_then(|tree| tree.clone())
+    }
+
+    pub fn insert(&self, tree: Vec<AccountTreeNode>) {
+        if let Ok(mut cached) = self.tree.write() {
+            *cached = Some(tree);
+        }
+    }
+
+    pub fn invalidate(&self) {
+        if let Ok(mut cached) = self.tree.write() {
+            *cached = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trial_balance() -> TrialBalance {
+        TrialBalance {
+            accounts: Vec::new(),
+            total_debits: None,
+            total_credits: None,
+            totals: Vec::new(),
+            is_balanced: true,
+        }
+    }
+
+    #[test]
+    fn test_least_recently_read_is_evicted() {
+        let cache = TrialBalanceCache::with_capacity(2);
+        let generation = cache.generation();
+
+        cache.insert("a".to_string(), trial_balance(), generation);
+        cache.insert("b".to_string(), trial_balance(), generation);
+        assert!(cache.get("a").is_some());
+        cache.insert("c".to_string(), trial_balance(), generation);
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn test_result_computed_before_invalidation_is_not_stored() {
+        let cache = TrialBalanceCache::default();
+        let generation = cache.generation();
+
+        cache.invalidate();
+        cache.insert("a".to_string(), trial_balance(), generation);
+        assert!(cache.get("a").is_none());
+
+        cache.insert("a".to_string(), trial_balance(), cache.generation());
+        assert!(cache.get("a").is_some());
+    }
+}
@@ -0,0 +1,227 @@
+//! Minimal reader for ISO 20022 CAMT.053 bank-to-customer statements.
+//!
+//! Only the handful of `Ntry` elements needed to post a transaction are read;
+//! namespaces, attributes other than `Ccy` and everything outside `Ntry` are ignored.
+
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementEntry {
+    pub amount: Decimal,
+    pub currency: Option<String>,
+    /// `true` for `CRDT` (money into the account), `false` for `DBIT`.
+    pub is_credit: bool,
+    pub booking_date: Option<String>,
+    pub reference: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Extracts every `Ntry` of the statement in document order.
+pub fn parse_statement(xml: &str) -> Result<Vec<StatementEntry>, String> {
+    let mut statement_entries = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    // Values of the entry being read, keyed by their path below `Ntry`
+    let mut current: Option<HashMap<String, String>> = None;
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        if let Some(fields) = current.as_mut() {
+            let text = rest[..start].trim();
+            if !text.is_empty() {
+                let path = entry_path(&stack);
+                fields.entry(path).or_insert_with(|| unescape(text));
+            }
+        }
+
+        rest = &rest[start..];
+
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").ok_or("unterminated comment")?;
+            rest = &rest[end + 3..];
+            continue;
+        }
+
+        let end = rest.find('>').ok_or("unterminated tag")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        if let Some(closing) = tag.strip_prefix('/') {
+            let name = local_name(closing);
+            match stack.pop() {
+                Some(open) if open == name => {}
+                _ => return Err(format!("unexpected closing tag </{}>", name)),
+            }
+            if name == "Ntry" {
+                if let Some(fields) = current.take() {
+                    statement_entries.push(build_entry(fields, statement_entries.len())?);
+                }
+            }
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name = local_name(tag.split_whitespace().next().unwrap_or_default());
+
+        if name == "Ntry" && current.is_none() {
+            current = Some(HashMap::new());
+        }
+
+        if self_closing {
+            continue;
+        }
+
+        stack.push(name.to_string());
+
+        if let Some(fields) = current.as_mut() {
+            if let Some(currency) = attribute(tag, "Ccy") {
+                fields
+                    .entry(format!("{}@Ccy", entry_path(&stack)))
+                    .or_insert(currency);
+            }
+        }
+    }
+
+    if current.is_some() || !stack.is_empty() {
+        return Err("unexpected end of document".to_string());
+    }
+
+    Ok(statement_entries)
+}
+
+fn build_entry(fields: HashMap<String, String>, index: usize) -> Result<StatementEntry, String> {
+    let amount = fields
+        .get("Amt")
+        .ok_or_else(|| format!("entry {}: missing Amt", index))?
+        .parse::<Decimal>()
+        .map_err(|_| format!("entry {}: invalid Amt", index))?;
+
+    let is_credit = match fields.get("CdtDbtInd").map(String::as_str) {
+        Some("CRDT") => true,
+        Some("DBIT") => false,
+        _ => return Err(format!("entry {}: CdtDbtInd must be CRDT or DBIT", index)),
+    };
+
+    let booking_date = fields
+        .get("BookgDt/DtTm")
+        .or_else(|| fields.get("BookgDt/Dt"))
+        .or_else(|| fields.get("ValDt/Dt"))
+        .cloned();
+
+    let reference = fields
+        .get("NtryRef")
+        .or_else(|| fields.get("AcctSvcrRef"))
+        .or_else(|| fields.get("NtryDtls/TxDtls/Refs/EndToEndId"))
+        .cloned();
+
+    let description = fields
+        .get("AddtlNtryInf")
+        .or_else(|| fields.get("NtryDtls/TxDtls/RmtInf/Ustrd"))
+        .cloned();
+
+    Ok(StatementEntry {
+        amount,
+        currency: fields.get("Amt@Ccy").cloned(),
+        is_credit,
+        booking_date,
+        reference,
+        description,
+    })
+}
+
+/// Path of the innermost element relative to the enclosing `Ntry`.
+fn entry_path(stack: &[String]) -> String {
+    let start = stack
+        .iter()
+        .rposition(|name| name == "Ntry")
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    stack[start..].join("/")
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let needle = format!("{}=", name);
+    let start = tag.find(&needle)? + needle.len();
+    let quote = tag[start..].chars().next()?;
+    let value = &tag[start + 1..];
+    let end = value.find(quote)?;
+    Some(unescape(&value[..end]))
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Id>STMT-2023-12</Id>
+      <Ntry>
+        <NtryRef>REF-1</NtryRef>
+        <Amt Ccy="EUR">150.25</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <BookgDt><Dt>2023-12-01</Dt></BookgDt>
+        <AddtlNtryInf>Customer payment &amp; fees</AddtlNtryInf>
+      </Ntry>
+      <!-- debit with details only -->
+      <Ntry>
+        <Amt Ccy="EUR">20.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <BookgDt><DtTm>2023-12-02T09:30:00Z</DtTm></BookgDt>
+        <AcctSvcrRef>BANK-77</AcctSvcrRef>
+        <NtryDtls><TxDtls><RmtInf><Ustrd>Card fee</Ustrd></RmtInf></TxDtls></NtryDtls>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn test_parse_statement_entries() {
+        let entries = parse_statement(SAMPLE).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].amount, Decimal::new(15025, 2));
+        assert_eq!(entries[0].currency.as_deref(), Some("EUR"));
+        assert!(entries[0].is_credit);
+        assert_eq!(entries[0].booking_date.as_deref(), Some("2023-12-01"));
+        assert_eq!(entries[0].reference.as_deref(), Some("REF-1"));
+        assert_eq!(
+            entries[0].description.as_deref(),
+            Some("Customer payment & fees")
+        );
+
+        assert_eq!(entries[1].amount, Decimal::new(2000, 2));
+        assert!(!entries[1].is_credit);
+        assert_eq!(
+            entries[1].booking_date.as_deref(),
+            Some("2023-12-02T09:30:00Z")
+        );
+        assert_eq!(entries[1].reference.as_deref(), Some("BANK-77"));
+        assert_eq!(entries[1].description.as_deref(), Some("Card fee"));
+    }
+
+    #[test]
+    fn test_parse_statement_rejects_bad_indicator() {
+        let xml =
+            "<Document><Ntry><Amt Ccy=\"EUR\">1</Amt><CdtDbtInd>X</CdtDbtInd></Ntry></Document>";
+        assert!(parse_statement(xml).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! Source of the current time for every timestamp the ledger writes.
+//!
+//! Handlers take the clock from app data as `web::Data<dyn Clock>` so tests can
+//! swap in a [`FixedClock`] and assert exact `created_at` values.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// The current time in the RFC 3339 form stored in timestamp columns.
+    fn now_rfc3339(&self) -> String {
+        self.now().to_rfc3339()
+    }
+}
+
+/// Reads the system clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Returns the same instant until moved with `set` or `advance`.
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_fixed_clock_only_moves_when_told() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap();
+        let clock = FixedClock::new(start);
+
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now_rfc3339(), "2024-01-31T12:00:00+00:00");
+
+        clock.advance(Duration::days(1));
+        assert_eq!(clock.now_rfc3339(), "2024-02-01T12:00:00+00:00");
+    }
+}
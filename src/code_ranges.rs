@@ -0,0 +1,111 @@
+//! Allowed account code ranges per account type.
+//!
+//! Written as comma-separated `type=spec` pairs, e.g.
+//! `asset=1,liability=2,expense=5000-6999`. A spec is either a prefix the code
+//! must start with or an inclusive numeric range `low-high`; several specs for
+//! one type are separated by `|`. Types without an entry accept any code.
+
+use std::collections::HashMap;
+use std::fmt;
+
+const ACCOUNT_TYPES: [&str; 5] = ["asset", "liability", "equity", "revenue", "expense"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodeRange {
+    Prefix(String),
+    Between(u64, u64),
+}
+
+impl CodeRange {
+    pub fn allows(&self, code: &str) -> bool {
+        match self {
+            CodeRange::Prefix(prefix) => code.starts_with(prefix.as_str()),
+            CodeRange::Between(low, high) => code
+                .parse::<u64>()
+                .is_ok_and(|value| (*low..=*high).contains(&value)),
+        }
+    }
+
+    fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once('-') {
+            Some((low, high)) => {
+                let low: u64 = low
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a numeric range", spec))?;
+                let high: u64 = high
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a numeric range", spec))?;
+                if low > high {
+                    return Err(format!("range '{}' is empty", spec));
+                }
+                Ok(CodeRange::Between(low, high))
+            }
+            None if !spec.is_empty() => Ok(CodeRange::Prefix(spec.to_string())),
+            None => Err("empty code range".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for CodeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeRange::Prefix(prefix) => write!(f, "{}*", prefix),
+            CodeRange::Between(low, high) => write!(f, "{}-{}", low, high),
+        }
+    }
+}
+
+/// Allowed ranges keyed by account type.
+pub type CodeRanges = HashMap<String, Vec<CodeRange>>;
+
+/// Parses the configured ranges, keyed by account type.
+pub fn parse(config: &str) -> Result<CodeRanges, String> {
+    let mut ranges = CodeRanges::new();
+
+    for pair in config.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (account_type, specs) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' must be written as type=range", pair))?;
+        let account_type = account_type.trim();
+        if !ACCOUNT_TYPES.contains(&account_type) {
+            return Err(format!("unknown account type '{}'", account_type));
+        }
+
+        for spec in specs.split('|') {
+            ranges
+                .entry(account_type.to_string())
+                .or_default()
+                .push(CodeRange::parse(spec.trim())?);
+        }
+    }
+
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_and_numeric_ranges() {
+        let ranges = parse("asset=1, expense=5000-5999|6").unwrap();
+
+        assert!(ranges["asset"][0].allows("1100"));
+        assert!(!ranges["asset"][0].allows("2100"));
+        assert!(ranges["expense"][0].allows("5400"));
+        assert!(!ranges["expense"][0].allows("50000"));
+        assert!(!ranges["expense"][0].allows("5A00"));
+        assert!(ranges["expense"][1].allows("6100"));
+        assert!(!ranges.contains_key("revenue"));
+    }
+
+    #[test]
+    fn test_malformed_config_rejected() {
+        for config in ["asset", "assets=1", "asset=", "asset=9-1", "asset=a-b"] {
+            assert!(parse(config).is_err(), "{} accepted", config);
+        }
+        assert!(parse("").unwrap().is_empty());
+    }
+}
@@ -1,19 +1,653 @@
 use std::env;
+use std::fmt;
+
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use crate::bank_feed::SignContras;
+use crate::code_ranges::{self, CodeRanges};
+use crate::money;
+
+pub const DEFAULT_API_BASE_PATH: &str = "/api/v1";
+/// Letters, digits and `.`, `_` or `-` separators, e.g. `1000` or `1000.01`.
+pub const DEFAULT_ACCOUNT_CODE_PATTERN: &str = "^[A-Za-z0-9]+([._-][A-Za-z0-9]+)*$";
+pub const REFERENCE_SCOPE_GLOBAL: &str = "global";
+pub const REFERENCE_SCOPE_PERIOD: &str = "period";
+pub const ID_FORMAT_UUID: &str = "uuid";
+pub const ID_FORMAT_SEQUENTIAL: &str = "sequential";
 
 #[derive(Clone)]
 pub struct AppConfig {
     pub database_url: String,
     pub bind_address: String,
     pub log_level: String,
+    pub pool_size: u32,
+    /// Prefix every API route is mounted under.
+    pub api_base_path: String,
+    /// Enables development-only endpoints such as demo seeding.
+    pub dev_endpoints: bool,
+    /// Rejects any change to an existing account's code.
+    pub immutable_codes: bool,
+    /// Only accounts without children may receive entries.
+    pub post_leaf_only: bool,
+    /// Creates the opening balances and suspense accounts on startup.
+    pub system_accounts: bool,
+    /// Queues write requests one at a time; see `middleware::SerializeWrites`.
+    pub serialize_writes: bool,
+    /// Rejects JSON endpoint bodies whose `Content-Type` is missing or not JSON.
+    /// When off, every body is parsed as JSON whatever its header says.
+    pub require_json_content_type: bool,
+    /// Most rows an unpaged list endpoint returns before truncating.
+    pub max_list_results: i64,
+    /// `global` keeps transaction references unique forever; `period` only
+    /// within the accounting period covering the transaction date.
+    pub reference_scope: String,
+    /// ISO 4217 code whose minor units set the scale of rendered amounts.
+    pub currency: String,
+    /// Requests taking longer than this many milliseconds are logged as slow.
+    pub slow_request_ms: u64,
+    /// Seconds to wait at startup while another instance holds the migration lock.
+    pub migration_lock_timeout_secs: u64,
+    /// Most requests handled at once; the rest get `503`. `None` disables the
+    /// limit. See `middleware::ConcurrencyLimit`.
+    pub max_concurrent_requests: Option<usize>,
+    /// Seconds an `Idempotency-Key` keeps returning the transaction it created.
+    pub idempotency_key_ttl_secs: u64,
+    /// Keys accepted as `Authorization: Bearer <key>`; empty leaves the API open.
+    pub api_keys: Vec<String>,
+    /// Route prefixes, relative to `api_base_path`, exempt from the request timeout.
+    pub timeout_exempt_paths: Vec<String>,
+    /// Allowed code ranges per account type, parsed once from `code_ranges`
+    /// syntax; empty disables the check.
+    pub account_code_ranges: CodeRanges,
+    /// Regular expression every account code must match, compiled once.
+    pub account_code_pattern: Regex,
+    /// `uuid` or `sequential`; the latter gives new accounts and transactions
+    /// readable ids such as `acc_000123`.
+    pub id_format: String,
+    /// Transactions whose debits sum past this need an approval header; `None`
+    /// disables the cap.
+    pub max_transaction_amount: Option<Decimal>,
+    /// Contra account for incoming bank import lines no rule or default covers.
+    pub import_income_account_id: Option<String>,
+    /// Contra account for outgoing bank import lines no rule or default covers.
+    pub import_expense_account_id: Option<String>,
 }
 
-impl AppConfig {
-    pub fn from_env() -> Self {
+impl Default for AppConfig {
+    fn default() -> Self {
         Self {
-            database_url: env::var("DATABASE_URL")
-                .unwrap_or_else(|_| "sqlite:ledger.db".to_string()),
-            bind_address: env::var("BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1:8080".to_string()),
-            log_level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
+            database_url: "sqlite:ledger.db".to_string(),
+            bind_address: "127.0.0.1:8080".to_string(),
+            log_level: "info".to_string(),
+            pool_size: 15,
+            api_base_path: DEFAULT_API_BASE_PATH.to_string(),
+            dev_endpoints: false,
+            immutable_codes: false,
+            post_leaf_only: false,
+            system_accounts: true,
+            serialize_writes: false,
+            require_json_content_type: true,
+            max_list_results: 1000,
+            reference_scope: REFERENCE_SCOPE_GLOBAL.to_string(),
+            currency: money::DEFAULT_CURRENCY.to_string(),
+            slow_request_ms: 1000,
+            migration_lock_timeout_secs: 30,
+            max_concurrent_requests: None,
+            idempotency_key_ttl_secs: 86_400,
+            api_keys: Vec::new(),
+            timeout_exempt_paths: vec!["/reports".to_string(), "/balance/export.csv".to_string()],
+            account_code_ranges: CodeRanges::new(),
+            account_code_pattern: Regex::new(DEFAULT_ACCOUNT_CODE_PATTERN)
+                .expect("default account code pattern compiles"),
+            id_format: ID_FORMAT_UUID.to_string(),
+            max_transaction_amount: None,
+            import_income_account_id: None,
+            import_expense_account_id: None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Reads every setting from the environment, compiling the account code
+    /// pattern and ranges once, then validates the result. All problems,
+    /// unparsable or out of range, are reported together.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let mut problems = Vec::new();
+
+        let account_code_ranges = match env::var("LEDGER_ACCOUNT_CODE_RANGES") {
+            Ok(value) => parse_code_ranges(&value).unwrap_or_else(|e| {
+                problems.push(e);
+                defaults.account_code_ranges.clone()
+            }),
+            Err(_) => defaults.account_code_ranges.clone(),
+        };
+        let account_code_pattern = match env::var("LEDGER_ACCOUNT_CODE_PATTERN") {
+            Ok(value) => parse_code_pattern(&value).unwrap_or_else(|e| {
+                problems.push(e);
+                defaults.account_code_pattern.clone()
+            }),
+            Err(_) => defaults.account_code_pattern.clone(),
+        };
+
+        let config = Self {
+            database_url: env::var("DATABASE_URL").unwrap_or(defaults.database_url),
+            bind_address: env::var("BIND_ADDRESS").unwrap_or(defaults.bind_address),
+            log_level: env::var("LOG_LEVEL").unwrap_or(defaults.log_level),
+            // Unparsable sizes become 0 so that validate() reports them
+            pool_size: env::var("DATABASE_POOL_SIZE")
+                .map(|v| v.trim().parse().unwrap_or(0))
+                .unwrap_or(defaults.pool_size),
+            api_base_path: env::var("API_BASE_PATH").unwrap_or(defaults.api_base_path),
+            dev_endpoints: env_flag("LEDGER_DEV_ENDPOINTS").unwrap_or(defaults.dev_endpoints),
+            immutable_codes: env_flag("LEDGER_IMMUTABLE_CODES").unwrap_or(defaults.immutable_codes),
+            post_leaf_only: env_flag("LEDGER_POST_LEAF_ONLY").unwrap_or(defaults.post_leaf_only),
+            system_accounts: env_flag("LEDGER_SYSTEM_ACCOUNTS").unwrap_or(defaults.system_accounts),
+            serialize_writes: env_flag("LEDGER_SERIALIZE_WRITES")
+                .unwrap_or(defaults.serialize_writes),
+            require_json_content_type: env_flag("LEDGER_REQUIRE_JSON_CONTENT_TYPE")
+                .unwrap_or(defaults.require_json_content_type),
+            max_list_results: env::var("LEDGER_MAX_LIST_RESULTS")
+                .map(|v| v.trim().parse().unwrap_or(0))
+                .unwrap_or(defaults.max_list_results),
+            reference_scope: env::var("LEDGER_REFERENCE_SCOPE").unwrap_or(defaults.reference_scope),
+            // Stored on accounts and compared as-is, so one spelling only
+            currency: env::var("LEDGER_CURRENCY")
+                .map(|v| v.to_ascii_uppercase())
+                .unwrap_or(defaults.currency),
+            slow_request_ms: env::var("SLOW_REQUEST_MS")
+                .map(|v| v.trim().parse().unwrap_or(0))
+                .unwrap_or(defaults.slow_request_ms),
+            migration_lock_timeout_secs: env::var("LEDGER_MIGRATION_LOCK_TIMEOUT_SECS")
+                .map(|v| v.trim().parse().unwrap_or(0))
+                .unwrap_or(defaults.migration_lock_timeout_secs),
+            // Unparsable limits become 0 so that validate() reports them
+            max_concurrent_requests: env::var("LEDGER_MAX_CONCURRENT_REQUESTS")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| v.trim().parse().unwrap_or(0))
+                .or(defaults.max_concurrent_requests),
+            idempotency_key_ttl_secs: env::var("LEDGER_IDEMPOTENCY_KEY_TTL_SECS")
+                .map(|v| v.trim().parse().unwrap_or(0))
+                .unwrap_or(defaults.idempotency_key_ttl_secs),
+            api_keys: env::var("LEDGER_API_KEYS")
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|k| !k.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or(defaults.api_keys),
+            timeout_exempt_paths: env::var("LEDGER_TIMEOUT_EXEMPT_PATHS")
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|p| !p.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or(defaults.timeout_exempt_paths),
+            account_code_ranges,
+            account_code_pattern,
+            id_format: env::var("LEDGER_ID_FORMAT").unwrap_or(defaults.id_format),
+            // Unparsable amounts become 0 so that validate() reports them
+            max_transaction_amount: env::var("MAX_TRANSACTION_AMOUNT")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| v.trim().parse().unwrap_or(Decimal::ZERO))
+                .or(defaults.max_transaction_amount),
+            import_income_account_id: env::var("LEDGER_IMPORT_INCOME_ACCOUNT_ID")
+                .ok()
+                .or(defaults.import_income_account_id),
+            import_expense_account_id: env::var("LEDGER_IMPORT_EXPENSE_ACCOUNT_ID")
+                .ok()
+                .or(defaults.import_expense_account_id),
+        };
+
+        if let Err(ConfigError(invalid)) = config.validate() {
+            problems.extend(invalid);
+        }
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError(problems))
+        }
+    }
+
+    pub fn references_unique_per_period(&self) -> bool {
+        self.reference_scope == REFERENCE_SCOPE_PERIOD
+    }
+
+    pub fn sequential_ids(&self) -> bool {
+        self.id_format == ID_FORMAT_SEQUENTIAL
+    }
+
+    /// Full paths of the routes the request timeout does not apply to.
+    pub fn timeout_exempt_prefixes(&self) -> Vec<String> {
+        self.timeout_exempt_paths
+            .iter()
+            .map(|path| format!("{}{}", self.api_base_path, path))
+            .collect()
+    }
+
+    /// Sign-based contra accounts for bank imports.
+    pub fn import_sign_contras(&self) -> SignContras {
+        SignContras {
+            income: self.import_income_account_id.clone(),
+            expense: self.import_expense_account_id.clone(),
+        }
+    }
+
+    /// Decimal places of the ledger currency, for amounts not tied to one account.
+    pub fn amount_scale(&self) -> u32 {
+        money::currency_scale(&self.currency)
+    }
+
+    /// Checks every setting up front and reports all problems at once.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        let db_path = self
+            .database_url
+            .strip_prefix("sqlite://")
+            .or_else(|| self.database_url.strip_prefix("sqlite:"))
+            .unwrap_or(&self.database_url);
+        if db_path.trim().is_empty() {
+            problems.push("DATABASE_URL must not be empty".to_string());
+        } else if db_path.contains("://") {
+            problems.push(format!(
+                "DATABASE_URL '{}' is not a SQLite path",
+                self.database_url
+            ));
+        }
+
+        if self.pool_size == 0 {
+            problems.push("DATABASE_POOL_SIZE must be a positive integer".to_string());
+        }
+
+        if !self.api_base_path.starts_with('/') || self.api_base_path.ends_with('/') {
+            problems.push(format!(
+                "API_BASE_PATH '{}' must start with '/' and not end with '/'",
+                self.api_base_path
+            ));
+        }
+
+        if self.max_list_results <= 0 {
+            problems.push("LEDGER_MAX_LIST_RESULTS must be a positive integer".to_string());
+        }
+
+        for path in &self.timeout_exempt_paths {
+            if !path.starts_with('/') {
+                problems.push(format!(
+                    "LEDGER_TIMEOUT_EXEMPT_PATHS entry '{}' must start with '/'",
+                    path
+                ));
+            }
+        }
+
+        if self
+            .max_transaction_amount
+            .is_some_and(|cap| cap <= Decimal::ZERO)
+        {
+            problems.push("MAX_TRANSACTION_AMOUNT must be a positive amount".to_string());
+        }
+
+        if self.slow_request_ms == 0 {
+            problems.push("SLOW_REQUEST_MS must be a positive integer".to_string());
+        }
+
+        if self.migration_lock_timeout_secs == 0 {
+            problems
+                .push("LEDGER_MIGRATION_LOCK_TIMEOUT_SECS must be a positive integer".to_string());
+        }
+
+        if self.max_concurrent_requests == Some(0) {
+            problems.push("LEDGER_MAX_CONCURRENT_REQUESTS must be a positive integer".to_string());
+        }
+
+        if self.idempotency_key_ttl_secs == 0 {
+            problems.push("LEDGER_IDEMPOTENCY_KEY_TTL_SECS must be a positive integer".to_string());
+        }
+
+        // Anything else could never arrive intact in an Authorization header
+        if self
+            .api_keys
+            .iter()
+            .any(|key| key.is_empty() || !key.bytes().all(|b| b.is_ascii_graphic()))
+        {
+            problems
+                .push("LEDGER_API_KEYS entries must be printable ASCII without spaces".to_string());
+        }
+
+        if ![REFERENCE_SCOPE_GLOBAL, REFERENCE_SCOPE_PERIOD]
+            .contains(&self.reference_scope.as_str())
+        {
+            problems.push(format!(
+                "LEDGER_REFERENCE_SCOPE '{}' must be '{}' or '{}'",
+                self.reference_scope, REFERENCE_SCOPE_GLOBAL, REFERENCE_SCOPE_PERIOD
+            ));
+        }
+
+        if ![ID_FORMAT_UUID, ID_FORMAT_SEQUENTIAL].contains(&self.id_format.as_str()) {
+            problems.push(format!(
+                "LEDGER_ID_FORMAT '{}' must be '{}' or '{}'",
+                self.id_format, ID_FORMAT_UUID, ID_FORMAT_SEQUENTIAL
+            ));
+        }
+
+        if !money::is_currency_code(&self.currency)
+            || self.currency != self.currency.to_ascii_uppercase()
+        {
+            problems.push(format!(
+                "LEDGER_CURRENCY '{}' must be an uppercase three-letter ISO 4217 code",
+                self.currency
+            ));
+        }
+
+        let valid_bind = match self.bind_address.rsplit_once(':') {
+            Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok(),
+            None => false,
+        };
+        if !valid_bind {
+            problems.push(format!(
+                "BIND_ADDRESS '{}' must be in host:port form",
+                self.bind_address
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError(problems))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid configuration: {}", self.0.join("; "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn parse_code_ranges(value: &str) -> Result<CodeRanges, String> {
+    code_ranges::parse(value).map_err(|e| format!("LEDGER_ACCOUNT_CODE_RANGES is invalid: {}", e))
+}
+
+fn parse_code_pattern(value: &str) -> Result<Regex, String> {
+    Regex::new(value).map_err(|e| format!("LEDGER_ACCOUNT_CODE_PATTERN is invalid: {}", e))
+}
+
+/// Reads a boolean flag, accepting `true`/`false` and `1`/`0`.
+fn env_flag(name: &str) -> Option<bool> {
+    match env::var(name).ok()?.to_lowercase().as_str() {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(AppConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_empty_database_url_rejected() {
+        let config = AppConfig {
+            database_url: "sqlite:".to_string(),
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0, vec!["DATABASE_URL must not be empty"]);
+    }
+
+    #[test]
+    fn test_non_sqlite_database_url_rejected() {
+        let config = AppConfig {
+            database_url: "postgres://localhost/ledger".to_string(),
+            ..Default::default()
+        };
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_bind_address_rejected() {
+        for bind_address in ["localhost", ":8080", "127.0.0.1:http", "127.0.0.1:70000"] {
+            let config = AppConfig {
+                bind_address: bind_address.to_string(),
+                ..Default::default()
+            };
+
+            assert!(config.validate().is_err(), "{} accepted", bind_address);
+        }
+    }
+
+    #[test]
+    fn test_invalid_api_base_path_rejected() {
+        for api_base_path in ["api/v1", "/api/v1/", "/"] {
+            let config = AppConfig {
+                api_base_path: api_base_path.to_string(),
+                ..Default::default()
+            };
+
+            assert!(config.validate().is_err(), "{} accepted", api_base_path);
+        }
+    }
+
+    #[test]
+    fn test_non_positive_list_cap_rejected() {
+        let config = AppConfig {
+            max_list_results: 0,
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.0,
+            vec!["LEDGER_MAX_LIST_RESULTS must be a positive integer"]
+        );
+    }
+
+    #[test]
+    fn test_zero_slow_request_threshold_rejected() {
+        let config = AppConfig {
+            slow_request_ms: 0,
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0, vec!["SLOW_REQUEST_MS must be a positive integer"]);
+    }
+
+    #[test]
+    fn test_zero_migration_lock_timeout_rejected() {
+        let config = AppConfig {
+            migration_lock_timeout_secs: 0,
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.0,
+            vec!["LEDGER_MIGRATION_LOCK_TIMEOUT_SECS must be a positive integer"]
+        );
+    }
+
+    #[test]
+    fn test_zero_concurrency_limit_rejected() {
+        let config = AppConfig {
+            max_concurrent_requests: Some(0),
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.0,
+            vec!["LEDGER_MAX_CONCURRENT_REQUESTS must be a positive integer"]
+        );
+    }
+
+    #[test]
+    fn test_zero_idempotency_key_ttl_rejected() {
+        let config = AppConfig {
+            idempotency_key_ttl_secs: 0,
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.0,
+            vec!["LEDGER_IDEMPOTENCY_KEY_TTL_SECS must be a positive integer"]
+        );
+    }
+
+    #[test]
+    fn test_unprintable_api_key_rejected() {
+        let config = AppConfig {
+            api_keys: vec!["good-key".to_string(), "bad key".to_string()],
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.0,
+            vec!["LEDGER_API_KEYS entries must be printable ASCII without spaces"]
+        );
+    }
+
+    #[test]
+    fn test_non_positive_transaction_cap_rejected() {
+        let config = AppConfig {
+            max_transaction_amount: Some(Decimal::ZERO),
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.0,
+            vec!["MAX_TRANSACTION_AMOUNT must be a positive amount"]
+        );
+    }
+
+    #[test]
+    fn test_timeout_exemptions_are_under_base_path() {
+        let config = AppConfig {
+            api_base_path: "/ledger".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.timeout_exempt_prefixes(),
+            vec!["/ledger/reports", "/ledger/balance/export.csv"]
+        );
+
+        let config = AppConfig {
+            timeout_exempt_paths: vec!["reports".to_string()],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_malformed_code_ranges_rejected() {
+        assert_eq!(
+            parse_code_ranges("assets=1").unwrap_err(),
+            "LEDGER_ACCOUNT_CODE_RANGES is invalid: unknown account type 'assets'"
+        );
+        assert!(parse_code_ranges("asset=1").unwrap().contains_key("asset"));
+    }
+
+    #[test]
+    fn test_malformed_code_pattern_rejected() {
+        assert!(parse_code_pattern("^[0-9+$")
+            .unwrap_err()
+            .starts_with("LEDGER_ACCOUNT_CODE_PATTERN is invalid"));
+        assert!(parse_code_pattern("^[0-9]+$").unwrap().is_match("1000"));
+    }
+
+    #[test]
+    fn test_unknown_reference_scope_rejected() {
+        let config = AppConfig {
+            reference_scope: "yearly".to_string(),
+            ..Default::default()
+        };
+
+        assert!(config.validate().is_err());
+        assert!(!AppConfig::default().references_unique_per_period());
+    }
+
+    #[test]
+    fn test_unknown_id_format_rejected() {
+        let config = AppConfig {
+            id_format: "ulid".to_string(),
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.0,
+            vec!["LEDGER_ID_FORMAT 'ulid' must be 'uuid' or 'sequential'"]
+        );
+        assert!(!AppConfig::default().sequential_ids());
+    }
+
+    #[test]
+    fn test_currency_sets_amount_scale() {
+        let config = AppConfig {
+            currency: "JPY".to_string(),
+            ..Default::default()
+        };
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.amount_scale(), 0);
+        assert_eq!(AppConfig::default().amount_scale(), 2);
+    }
+
+    #[test]
+    fn test_currency_from_env_is_uppercased() {
+        env::set_var("LEDGER_CURRENCY", "eur");
+        let config = AppConfig::from_env();
+        env::remove_var("LEDGER_CURRENCY");
+
+        assert_eq!(config.unwrap().currency, "EUR");
+    }
+
+    #[test]
+    fn test_invalid_currency_rejected() {
+        for currency in ["", "US", "US$", "EURO", "eur"] {
+            let config = AppConfig {
+                currency: currency.to_string(),
+                ..Default::default()
+            };
+
+            assert!(config.validate().is_err(), "{} accepted", currency);
         }
     }
+
+    #[test]
+    fn test_all_problems_reported_together() {
+        let config = AppConfig {
+            database_url: String::new(),
+            bind_address: "nowhere".to_string(),
+            pool_size: 0,
+            ..Default::default()
+        };
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), 3);
+        assert!(err.to_string().starts_with("Invalid configuration: "));
+    }
 }
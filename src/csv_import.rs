@@ -0,0 +1,185 @@
+//! Reader for the journal CSV import format.
+//!
+//! One row per entry, with a header line naming the columns
+//! `reference,date,description,account,debit,credit`. Rows sharing a reference
+//! form one transaction. `account` holds an account code or id; `date` may be empty.
+
+use rust_decimal::Decimal;
+
+pub const HEADER: [&str; 6] = [
+    "reference",
+    "date",
+    "description",
+    "account",
+    "debit",
+    "credit",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalRow {
+    /// 1-based line number in the document, for error messages.
+    pub line: usize,
+    pub reference: String,
+    pub date: Option<String>,
+    pub description: String,
+    pub account: String,
+    pub debit: Option<Decimal>,
+    pub credit: Option<Decimal>,
+}
+
+/// Parses every data row, collecting one message per malformed line.
+pub fn parse_journal(input: &str) -> Result<Vec<JournalRow>, Vec<String>> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let Some((_, header)) = lines.next() else {
+        return Err(vec!["document is empty".to_string()]);
+    };
+    let header_fields = split_record(header).map_err(|e| vec![format!("line 1: {}", e)])?;
+    if header_fields.iter().map(|f| f.trim()).ne(HEADER) {
+        return Err(vec![format!("header must be {}", HEADER.join(","))]);
+    }
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for (line, record) in lines {
+        match parse_row(line, record) {
+            Ok(row) => rows.push(row),
+            Err(e) => errors.push(format!("line {}: {}", line, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(rows)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_row(line: usize, record: &str) -> Result<JournalRow, String> {
+    let fields = split_record(record)?;
+    if fields.len() != HEADER.len() {
+        return Err(format!(
+            "expected {} fields, found {}",
+            HEADER.len(),
+            fields.len()
+        ));
+    }
+
+    let reference = fields[0].trim().to_string();
+    if reference.is_empty() {
+        return Err("reference is required".to_string());
+    }
+    let account = fields[3].trim().to_string();
+    if account.is_empty() {
+        return Err("account is required".to_string());
+    }
+
+    Ok(JournalRow {
+        line,
+        reference,
+        date: non_empty(&fields[1]),
+        description: fields[2].trim().to_string(),
+        account,
+        debit: parse_amount("debit", &fields[4])?,
+        credit: parse_amount("credit", &fields[5])?,
+    })
+}
+
+fn parse_amount(field: &str, value: &str) -> Result<Option<Decimal>, String> {
+    match non_empty(value) {
+        Some(amount) => amount
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("{} '{}' is not a number", field, amount)),
+        None => Ok(None),
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Splits one line on commas, honouring double-quoted fields with `""` escapes.
+fn split_record(record: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = record.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "reference,date,description,account,debit,credit
+SALE-1,2024-01-05,\"Sale, cash\",1000,100.00,
+SALE-1,2024-01-05,\"Sale, cash\",4000,,100.00
+";
+
+    #[test]
+    fn test_parse_journal_rows() {
+        let rows = parse_journal(SAMPLE).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].description, "Sale, cash");
+        assert_eq!(rows[0].debit, Some(Decimal::new(10000, 2)));
+        assert_eq!(rows[0].credit, None);
+        assert_eq!(rows[1].account, "4000");
+        assert_eq!(rows[1].date.as_deref(), Some("2024-01-05"));
+    }
+
+    #[test]
+    fn test_quoted_quotes_are_unescaped() {
+        let fields = split_record("a,\"say \"\"hi\"\"\",c").unwrap();
+
+        assert_eq!(fields, vec!["a", "say \"hi\"", "c"]);
+    }
+
+    #[test]
+    fn test_bad_rows_are_reported_by_line() {
+        let input = "reference,date,description,account,debit,credit
+X,,desc,1000,abc,
+Y,,desc,1000
+";
+        let errors = parse_journal(input).unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                "line 2: debit 'abc' is not a number",
+                "line 3: expected 6 fields, found 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrong_header_rejected() {
+        assert!(parse_journal("ref,account\nX,1000\n").is_err());
+    }
+}
@@ -1,9 +1,15 @@
 use anyhow::Result;
-use diesel::r2d2::{ConnectionManager, Pool};
-use diesel::sqlite::SqliteConnection;
+use diesel::connection::{Connection, Instrumentation, InstrumentationEvent};
+use diesel::migration::MigrationSource;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use diesel::RunQueryDsl;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use std::cell::Cell;
 use std::error::Error;
 use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 
@@ -20,22 +26,135 @@ impl fmt::Display for DatabaseError {
 
 impl Error for DatabaseError {}
 
-pub fn create_pool(database_url: &str) -> Result<DbPool> {
+pub fn create_pool(database_url: &str, pool_size: u32) -> Result<DbPool> {
     let manager = ConnectionManager::<SqliteConnection>::new(database_url);
-    let pool = Pool::builder().max_size(15).build(manager)?;
+    let pool = Pool::builder()
+        .max_size(pool_size)
+        .connection_customizer(Box::new(TimeQueries))
+        .build(manager)?;
 
     log::info!("Database pool created successfully");
     Ok(pool)
 }
 
+/// How long `run_migrations` waits for another instance's migration to finish.
+pub const DEFAULT_MIGRATION_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub fn run_migrations(pool: &DbPool) -> Result<()> {
+    run_migrations_with_lock_timeout(pool, DEFAULT_MIGRATION_LOCK_TIMEOUT)
+}
+
+/// Migrates inside an immediate transaction, which takes SQLite's write lock
+/// before reading the applied migrations. When several instances start at once
+/// one migrates while the others wait up to `lock_timeout` for the lock, then
+/// find nothing left to apply.
+pub fn run_migrations_with_lock_timeout(pool: &DbPool, lock_timeout: Duration) -> Result<()> {
     let mut connection = pool.get()?;
 
-    log::info!("Running database migrations...");
-    connection
-        .run_pending_migrations(MIGRATIONS)
-        .map_err(|e| DatabaseError(format!("Migration failed: {}", e)))?;
+    diesel::sql_query(format!(
+        "PRAGMA busy_timeout = {}",
+        lock_timeout.as_millis()
+    ))
+    .execute(&mut connection)?;
+    let outcome = connection.immediate_transaction::<_, anyhow::Error, _>(|conn| {
+        check_schema_not_ahead(conn)?;
+
+        log::info!("Running database migrations...");
+        conn.run_pending_migrations(MIGRATIONS)
+            .map_err(|e| DatabaseError(format!("Migration failed: {}", e)))?;
+        Ok(())
+    });
+    // Pooled connections go back to failing fast on a locked database
+    diesel::sql_query("PRAGMA busy_timeout = 0").execute(&mut connection)?;
+    outcome?;
 
     log::info!("Database migrations completed successfully");
     Ok(())
 }
+
+/// Versions of the embedded migrations not yet applied to the database.
+pub fn pending_migrations(connection: &mut SqliteConnection) -> Result<Vec<String>> {
+    let pending = connection
+        .pending_migrations(MIGRATIONS)
+        .map_err(|e| DatabaseError(format!("Could not read pending migrations: {}", e)))?;
+    Ok(pending
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect())
+}
+
+/// Refuses to run against a database migrated by a newer build, whose schema
+/// this binary's queries may no longer match (e.g. after a rolled back deploy).
+fn check_schema_not_ahead(connection: &mut SqliteConnection) -> Result<()> {
+    let known: Vec<String> = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(|e| DatabaseError(format!("Could not read embedded migrations: {}", e)))?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect();
+    let applied = connection
+        .applied_migrations()
+        .map_err(|e| DatabaseError(format!("Could not read applied migrations: {}", e)))?;
+
+    let unknown: Vec<String> = applied
+        .iter()
+        .map(|version| version.to_string())
+        .filter(|version| !known.contains(version))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(DatabaseError(format!(
+            "Database schema is ahead of this build; unknown applied migrations: {}. \
+             Deploy a build that includes them or roll the database back",
+            unknown.join(", ")
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+tokio::task_local! {
+    static DB_TIME: Cell<Duration>;
+}
+
+/// Runs `fut` and returns its output with the time its queries spent in the
+/// database. Queries run synchronously inside the handler's poll, so a task
+/// local sees exactly the queries of this request.
+pub async fn with_db_timer<F: Future>(fut: F) -> (F::Output, Duration) {
+    DB_TIME
+        .scope(Cell::new(Duration::ZERO), async move {
+            let output = fut.await;
+            (output, DB_TIME.with(Cell::get))
+        })
+        .await
+}
+
+#[derive(Debug)]
+struct TimeQueries;
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for TimeQueries {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.set_instrumentation(QueryTimer::default());
+        Ok(())
+    }
+}
+
+/// Adds the duration of every query to the running request's `DB_TIME`.
+#[derive(Default)]
+struct QueryTimer {
+    started: Option<Instant>,
+}
+
+impl Instrumentation for QueryTimer {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => self.started = Some(Instant::now()),
+            InstrumentationEvent::FinishQuery { .. } => {
+                if let Some(started) = self.started.take() {
+                    // Queries outside a timed request (migrations, startup) are not counted
+                    let _ = DB_TIME.try_with(|total| total.set(total.get() + started.elapsed()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
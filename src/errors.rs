@@ -1,6 +1,7 @@
 use actix_web::{HttpResponse, ResponseError};
-use diesel::result::Error as DieselError;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use std::fmt;
+use validator::ValidationErrors;
 
 #[derive(Debug)]
 pub enum AppError {
@@ -8,6 +9,12 @@ pub enum AppError {
     ValidationError(String),
     NotFound(String),
     BadRequest(String),
+    Conflict(String),
+    UnsupportedMediaType(String),
+    /// Missing or unrecognized credentials.
+    Unauthorized(String),
+    /// A temporary condition such as an exhausted connection pool; worth retrying.
+    ServiceUnavailable(String),
     InternalServerError(String),
 }
 
@@ -18,6 +25,10 @@ impl fmt::Display for AppError {
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            AppError::UnsupportedMediaType(msg) => write!(f, "Unsupported media type: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::ServiceUnavailable(msg) => write!(f, "Service unavailable: {}", msg),
             AppError::InternalServerError(msg) => write!(f, "Internal server error: {}", msg),
         }
     }
@@ -35,6 +46,17 @@ impl ResponseError for AppError {
             }
             AppError::BadRequest(msg) => HttpResponse::BadRequest()
                 .json(crate::models::ApiResponse::<()>::error(msg.clone())),
+            AppError::Conflict(msg) => {
+                HttpResponse::Conflict().json(crate::models::ApiResponse::<()>::error(msg.clone()))
+            }
+            AppError::UnsupportedMediaType(msg) => HttpResponse::UnsupportedMediaType()
+                .json(crate::models::ApiResponse::<()>::error(msg.clone())),
+            AppError::Unauthorized(msg) => HttpResponse::Unauthorized()
+                .insert_header(("WWW-Authenticate", "Bearer"))
+                .json(crate::models::ApiResponse::<()>::error(msg.clone())),
+            AppError::ServiceUnavailable(msg) => HttpResponse::ServiceUnavailable()
+                .insert_header(("Retry-After", "1"))
+                .json(crate::models::ApiResponse::<()>::error(msg.clone())),
             AppError::InternalServerError(msg) => HttpResponse::InternalServerError()
                 .json(crate::models::ApiResponse::<()>::error(msg.clone())),
         }
@@ -45,13 +67,161 @@ impl From<DieselError> for AppError {
     fn from(error: DieselError) -> Self {
         match error {
             DieselError::NotFound => AppError::NotFound("Record not found".to_string()),
+            DieselError::DatabaseError(DatabaseErrorKind::CheckViolation, info) => {
+                let constraint = info
+                    .message()
+                    .trim_start_matches("CHECK constraint failed: ");
+                // Length checks are named `<column>_length` by the migrations
+                match constraint.strip_suffix("_length") {
+                    Some(column) => {
+                        AppError::ValidationError(format!("{}: exceeds the maximum length", column))
+                    }
+                    None => AppError::ValidationError(format!("Constraint failed: {}", constraint)),
+                }
+            }
+            // Handlers check uniqueness first; this catches a concurrent writer
+            // that got there in between
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => {
+                let columns = info
+                    .message()
+                    .trim_start_matches("UNIQUE constraint failed: ");
+                AppError::Conflict(unique_violation_message(columns).to_string())
+            }
             _ => AppError::DatabaseError(error.to_string()),
         }
     }
 }
 
+/// Names the clashing value for the unique constraints the migrations define,
+/// given SQLite's `table.column` list, without exposing the schema.
+fn unique_violation_message(columns: &str) -> &'static str {
+    match columns {
+        "accounts.code" | "accounts.ledger_id, accounts.code" => "Account code already exists",
+        "transactions.reference" | "transactions.period_id, transactions.reference" => {
+            "Transaction reference already exists"
+        }
+        "idempotency_keys.ledger_id, idempotency_keys.idempotency_key" => {
+            "Idempotency key already exists"
+        }
+        _ => "Record already exists",
+    }
+}
+
+/// How r2d2 0.8 (0.8.10 at the time of writing) renders its only error. The
+/// last connection error, if any, follows as `": <cause>"`; the error exposes
+/// no other way to read it, so a change to this text must be matched here.
+const POOL_TIMEOUT_MESSAGE: &str = "timed out waiting for connection";
+
 impl From<r2d2::Error> for AppError {
+    /// r2d2 only ever fails by timing out. When it also carries the last error
+    /// from opening a connection the database itself is unreachable or
+    /// misconfigured; otherwise every connection was simply busy.
     fn from(error: r2d2::Error) -> Self {
-        AppError::DatabaseError(format!("Connection pool error: {}", error))
+        let message = error.to_string();
+        match message.strip_prefix(POOL_TIMEOUT_MESSAGE) {
+            Some("") => AppError::ServiceUnavailable(
+                "Database connection pool exhausted; try again later".to_string(),
+            ),
+            Some(rest) => AppError::InternalServerError(format!(
+                "Database connection failed: {}",
+                rest.strip_prefix(": ").unwrap_or(rest)
+            )),
+            // Not the text above, so r2d2 changed; report it whole
+            None => {
+                AppError::InternalServerError(format!("Database connection failed: {}", message))
+            }
+        }
+    }
+}
+
+/// Flattens `validator` errors into one "field: problem" message per failure.
+pub fn validation_messages(errors: &ValidationErrors) -> Vec<String> {
+    let mut messages: Vec<String> = errors
+        .field_errors()
+        .iter()
+        .flat_map(|(field, field_errors)| {
+            field_errors.iter().map(move |error| match error.message {
+                Some(ref message) => format!("{}: {}", field, message),
+                None => format!("{}: failed {} validation", field, error.code),
+            })
+        })
+        .collect();
+
+    messages.sort();
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use diesel::SqliteConnection;
+    use std::time::Duration;
+
+    fn pool(database_url: &str) -> Pool<ConnectionManager<SqliteConnection>> {
+        Pool::builder()
+            .max_size(1)
+            .connection_timeout(Duration::from_millis(100))
+            .build_unchecked(ConnectionManager::new(database_url))
+    }
+
+    #[test]
+    fn test_unique_violation_is_conflict() {
+        use diesel::{Connection, RunQueryDsl};
+
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        diesel::sql_query("CREATE TABLE accounts (code TEXT NOT NULL UNIQUE)")
+            .execute(&mut conn)
+            .unwrap();
+        let insert = "INSERT INTO accounts (code) VALUES ('1000')";
+        diesel::sql_query(insert).execute(&mut conn).unwrap();
+
+        let error = AppError::from(diesel::sql_query(insert).execute(&mut conn).unwrap_err());
+
+        assert!(
+            matches!(error, AppError::Conflict(ref msg) if msg == "Account code already exists"),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_unknown_unique_violation_hides_the_schema() {
+        assert_eq!(
+            unique_violation_message("transactions.period_id, transactions.reference"),
+            "Transaction reference already exists"
+        );
+        assert_eq!(
+            unique_violation_message("ledger_quotas.ledger_id"),
+            "Record already exists"
+        );
+    }
+
+    #[test]
+    fn test_exhausted_pool_is_service_unavailable() {
+        let pool = pool(":memory:");
+        let _held = pool.get().unwrap();
+
+        let error = AppError::from(pool.get().err().unwrap());
+
+        assert!(
+            matches!(error, AppError::ServiceUnavailable(ref msg) if msg.contains("exhausted")),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_connection_failure_is_internal_error() {
+        let pool = pool("/nonexistent-ledger-dir/ledger.db");
+
+        let error = AppError::from(pool.get().err().unwrap());
+
+        assert!(
+            matches!(error, AppError::InternalServerError(ref msg)
+                if msg.starts_with("Database connection failed: ")),
+            "{:?}",
+            error
+        );
     }
 }
@@ -0,0 +1,207 @@
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use chrono::{DateTime, NaiveDate, NaiveTime, SecondsFormat, TimeZone, Utc};
+use serde::Deserialize;
+use std::future::{ready, Ready};
+
+use crate::errors::AppError;
+
+/// Header a caller sets to `true` to approve a transaction over
+/// `MAX_TRANSACTION_AMOUNT`.
+pub const APPROVAL_HEADER: &str = "X-Ledger-Approval";
+
+/// Whether the request carries an approval for transactions over the amount cap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Approval(pub bool);
+
+impl FromRequest for Approval {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let approved = req
+            .headers()
+            .get(APPROVAL_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.eq_ignore_ascii_case("true") || value == "1");
+
+        ready(Ok(Approval(approved)))
+    }
+}
+
+/// Header naming a retry-safe `POST /transactions`; see `crate::idempotency`.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Longest `Idempotency-Key` accepted.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// The request's `Idempotency-Key`, if it sent one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotencyKey(pub Option<String>);
+
+impl FromRequest for IdempotencyKey {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let Some(value) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+            return ready(Ok(IdempotencyKey(None)));
+        };
+
+        let key = value.to_str().map(str::trim).unwrap_or_default();
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return ready(Err(AppError::BadRequest(format!(
+                "{} must be 1 to {} visible ASCII characters",
+                IDEMPOTENCY_KEY_HEADER, MAX_IDEMPOTENCY_KEY_LEN
+            ))));
+        }
+
+        ready(Ok(IdempotencyKey(Some(key.to_string()))))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawReportRange {
+    from: Option<String>,
+    #[serde(alias = "as_of")]
+    to: Option<String>,
+}
+
+/// Date range shared by the report endpoints, read from `from` and `to` (or
+/// `as_of`) query parameters. Each bound accepts RFC 3339 or `YYYY-MM-DD`; a bare
+/// `to` date covers that whole day. Missing bounds leave that side open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl ReportRange {
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> Result<Self, AppError> {
+        let from = match from {
+            Some(value) => parse_bound("from", value, NaiveTime::MIN)?,
+            None => open_start(),
+        };
+        let to = match to {
+            Some(value) => parse_bound("to", value, end_of_day())?,
+            None => open_end(),
+        };
+
+        if from > to {
+            return Err(AppError::BadRequest(
+                "from must not be after to".to_string(),
+            ));
+        }
+
+        Ok(Self { from, to })
+    }
+
+    /// Lower bound for comparing against stored `transaction_date` strings. A
+    /// midnight bound is written as a bare date so date-only rows on that day match.
+    pub fn from_bound(&self) -> String {
+        if self.from.time() == NaiveTime::MIN {
+            self.from.format("%Y-%m-%d").to_string()
+        } else {
+            self.from.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        }
+    }
+
+    /// Upper bound for comparing against stored `transaction_date` strings.
+    pub fn to_bound(&self) -> String {
+        self.to.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+}
+
+impl FromRequest for ReportRange {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let result = web::Query::<RawReportRange>::from_query(req.query_string())
+            .map_err(|e| AppError::BadRequest(e.to_string()))
+            .and_then(|raw| Self::parse(raw.from.as_deref(), raw.to.as_deref()));
+
+        ready(result)
+    }
+}
+
+fn parse_bound(field: &str, value: &str, day_time: NaiveTime) -> Result<DateTime<Utc>, AppError> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(Utc.from_utc_datetime(&date.and_time(day_time)));
+    }
+
+    Err(AppError::BadRequest(format!(
+        "{} must be an RFC 3339 timestamp or a YYYY-MM-DD date",
+        field
+    )))
+}
+
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_nano_opt(23, 59, 59, 999_999_999).unwrap()
+}
+
+fn open_start() -> DateTime<Utc> {
+    Utc.from_utc_datetime(
+        &NaiveDate::from_ymd_opt(1, 1, 1)
+            .unwrap()
+            .and_time(NaiveTime::MIN),
+    )
+}
+
+fn open_end() -> DateTime<Utc> {
+    Utc.from_utc_datetime(
+        &NaiveDate::from_ymd_opt(9999, 12, 31)
+            .unwrap()
+            .and_time(end_of_day()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_bounds_cover_whole_days() {
+        let range = ReportRange::parse(Some("2024-01-01"), Some("2024-01-31")).unwrap();
+
+        assert_eq!(range.from_bound(), "2024-01-01");
+        assert_eq!(range.to_bound(), "2024-01-31T23:59:59.999999999Z");
+        assert!("2024-01-31T18:00:00Z" <= range.to_bound().as_str());
+    }
+
+    #[test]
+    fn test_timestamps_are_normalized_to_utc() {
+        let range = ReportRange::parse(Some("2024-01-01T12:30:00+02:00"), None).unwrap();
+
+        assert_eq!(range.from_bound(), "2024-01-01T10:30:00Z");
+    }
+
+    #[test]
+    fn test_missing_bounds_are_open() {
+        let range = ReportRange::parse(None, None).unwrap();
+
+        assert!(range.from_bound().as_str() < "1900-01-01");
+        assert!(range.to_bound().as_str() > "2999-12-31");
+    }
+
+    #[test]
+    fn test_inverted_range_rejected() {
+        let err = ReportRange::parse(Some("2024-02-01"), Some("2024-01-31")).unwrap_err();
+
+        assert!(matches!(err, AppError::BadRequest(ref msg) if msg == "from must not be after to"));
+    }
+
+    #[test]
+    fn test_same_day_range_allowed() {
+        assert!(ReportRange::parse(Some("2024-01-31"), Some("2024-01-31")).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_date_rejected() {
+        let err = ReportRange::parse(Some("31/01/2024"), None).unwrap_err();
+
+        assert!(matches!(err, AppError::BadRequest(ref msg) if msg.starts_with("from must be")));
+    }
+}
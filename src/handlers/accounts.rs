@@ -1,34 +1,137 @@
+use std::collections::{HashMap, HashSet};
+
 use actix_web::{web, HttpResponse, Result, Scope};
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::dsl::exists;
 use diesel::prelude::*;
-use uuid::Uuid;
+use rust_decimal::Decimal;
+use serde_json::json;
 use validator::Validate;
 
+use crate::audit;
+use crate::cache::{AccountTreeCache, TrialBalanceCache};
+use crate::clock::Clock;
+use crate::config::AppConfig;
 use crate::database::DbPool;
-use crate::errors::AppError;
-use crate::models::{Account, ApiResponse, CreateAccountRequest, NewAccount, UpdateAccountRequest};
-use crate::schema::accounts::{self, dsl::*};
+use crate::errors::{validation_messages, AppError};
+use crate::handlers::balance;
+use crate::handlers::chart;
+use crate::handlers::ledger;
+use crate::handlers::ledgers::check_account_quota;
+use crate::handlers::reconciliation;
+use crate::handlers::{directed, page_bounds, BoxedOrder};
+use crate::ids;
+use crate::models::{
+    Account, AccountSortKey, AccountSyncRequest, AccountSyncSummary, AccountUpdateResult,
+    AccountValidationReport, AccountValidationRequest, AccountValidationResult, AccountsQuery,
+    ApiResponse, BatchItemStatus, BulkAccountUpdateRequest, CreateAccountRequest,
+    DeleteAccountQuery, NewAccount, ResponseMeta, SortOrder, UpdateAccountRequest,
+    DEFAULT_LEDGER_ID, SYSTEM_ACCOUNT_IDS,
+};
+use crate::money;
+use crate::schema::{accounts, entries};
 
 pub fn config() -> Scope {
     web::scope("/accounts")
         .route("", web::post().to(create_account))
         .route("", web::get().to(get_all_accounts))
+        .route("/bulk-update", web::post().to(bulk_update_accounts))
+        .route("/sync", web::post().to(sync_accounts))
+        .route("/validate", web::post().to(validate_accounts))
+        .route("/chart", web::get().to(chart::get_chart_of_accounts))
+        .route("/tree", web::get().to(chart::get_account_tree))
         .route("/{id}", web::get().to(get_account))
         .route("/{id}", web::put().to(update_account))
         .route("/{id}", web::delete().to(delete_account))
+        .route(
+            "/{id}/balance-at-transaction/{transaction_id}",
+            web::get().to(balance::get_balance_at_transaction),
+        )
+        .route(
+            "/{id}/reconciliation",
+            web::get().to(reconciliation::get_reconciliation),
+        )
+        .route("/{id}/ledger", web::get().to(ledger::get_account_ledger))
 }
 
 pub async fn create_account(
     pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    tree_cache: web::Data<AccountTreeCache>,
+    clock: web::Data<dyn Clock>,
     account_data: web::Json<CreateAccountRequest>,
 ) -> Result<HttpResponse, AppError> {
-    account_data
-        .validate()
-        .map_err(|e| AppError::ValidationError(format!("Validation failed: {:?}", e)))?;
+    let mut errors = match account_data.validate() {
+        Ok(()) => Vec::new(),
+        Err(e) => validation_messages(&e),
+    };
+    if let Err(AppError::ValidationError(msg)) = check_code_format(&config, &account_data.code) {
+        errors.push(msg);
+    }
+    if !errors.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::validation_errors(errors)));
+    }
+
+    if let Some(ref opening_date) = account_data.opening_date {
+        validate_opening_date(opening_date)?;
+    }
 
     let mut conn = pool.get()?;
-    let account_id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
+    let account_id = conn.transaction::<_, AppError, _>(|conn| {
+        insert_account(conn, &config, clock.get_ref(), &account_data)
+    })?;
+
+    cache.invalidate();
+
+    tree_cache.invalidate();
+
+    let account: Account = accounts::table.find(&account_id).first(&mut conn)?;
+
+    Ok(HttpResponse::Created()
+        .insert_header((
+            "Location",
+            format!("{}/accounts/{}", config.api_base_path, account.id),
+        ))
+        .json(ApiResponse::success(account)))
+}
+
+/// Inserts a validated account and returns its new id. Must be called inside a
+/// database transaction.
+fn insert_account(
+    conn: &mut SqliteConnection,
+    config: &AppConfig,
+    clock: &dyn Clock,
+    account_data: &CreateAccountRequest,
+) -> Result<String, AppError> {
+    check_code_range(
+        config,
+        &String::from(account_data.account_type.clone()),
+        &account_data.code,
+    )?;
+
+    let now = clock.now_rfc3339();
+    let ledger_id = account_data
+        .ledger_id
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LEDGER_ID.to_string());
+    let currency = account_data
+        .currency
+        .as_deref()
+        .unwrap_or(&config.currency)
+        .to_ascii_uppercase();
+    if !money::is_currency_code(&currency) {
+        return Err(AppError::ValidationError(
+            "currency must be a three-letter ISO 4217 code".to_string(),
+        ));
+    }
+
+    check_account_quota(conn, &ledger_id)?;
+    check_code_available(conn, &ledger_id, &account_data.code, None)?;
+    if let Some(ref parent_id) = account_data.parent_id {
+        check_parent(conn, parent_id, None)?;
+    }
+    let account_id = ids::new_id(conn, config, ids::ACCOUNT_PREFIX)?;
 
     let new_account = NewAccount {
         id: account_id.clone(),
@@ -39,25 +142,352 @@ pub async fn create_account(
         is_active: true,
         created_at: now.clone(),
         updated_at: now,
+        is_memo: account_data.is_memo,
+        ledger_id,
+        opening_balance: account_data
+            .opening_balance
+            .unwrap_or(Decimal::ZERO)
+            .to_string(),
+        opening_date: account_data.opening_date.clone(),
+        currency,
     };
 
     diesel::insert_into(accounts::table)
         .values(&new_account)
-        .execute(&mut conn)?;
+        .execute(conn)?;
 
-    let account: Account = accounts::table.find(&account_id).first(&mut conn)?;
+    audit::record(
+        conn,
+        clock,
+        audit::ENTITY_ACCOUNT,
+        &account_id,
+        audit::ACTION_CREATED,
+        Some(json!({ "code": new_account.code })),
+    )?;
 
-    Ok(HttpResponse::Created().json(ApiResponse::success(account)))
+    Ok(account_id)
 }
 
-pub async fn get_all_accounts(pool: web::Data<DbPool>) -> Result<HttpResponse, AppError> {
+/// Makes the chart of accounts match an external system of record in one
+/// database transaction. Accounts are matched by code: unknown codes are
+/// created, known ones updated (and reactivated), and with
+/// `deactivate_missing` every other active account except the system accounts
+/// is deactivated.
+pub async fn sync_accounts(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    tree_cache: web::Data<AccountTreeCache>,
+    clock: web::Data<dyn Clock>,
+    sync_data: web::Json<AccountSyncRequest>,
+) -> Result<HttpResponse, AppError> {
+    let sync_data = sync_data.into_inner();
+
+    let mut errors = Vec::new();
+    let mut codes: HashSet<(&str, &str)> = HashSet::new();
+    for (index, account_data) in sync_data.accounts.iter().enumerate() {
+        if let Err(e) = account_data.validate() {
+            errors.extend(
+                validation_messages(&e)
+                    .into_iter()
+                    .map(|msg| format!("account {}: {}", index, msg)),
+            );
+        }
+        if let Some(Err(AppError::ValidationError(msg))) = account_data
+            .opening_date
+            .as_deref()
+            .map(validate_opening_date)
+        {
+            errors.push(format!("account {}: {}", index, msg));
+        }
+        if let Err(AppError::ValidationError(msg)) = check_code_format(&config, &account_data.code)
+        {
+            errors.push(format!("account {}: {}", index, msg));
+        }
+        let ledger_id = account_data
+            .ledger_id
+            .as_deref()
+            .unwrap_or(DEFAULT_LEDGER_ID);
+        if !codes.insert((ledger_id, &account_data.code)) {
+            errors.push(format!(
+                "account {}: code {} appears more than once",
+                index, account_data.code
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(errors.join("; ")));
+    }
+
     let mut conn = pool.get()?;
 
-    let results: Vec<Account> = accounts::table
-        .order(accounts::created_at.desc())
-        .load(&mut conn)?;
+    let summary = conn.transaction::<_, AppError, _>(|conn| {
+        let mut summary = AccountSyncSummary::default();
 
-    Ok(HttpResponse::Ok().json(ApiResponse::success(results)))
+        for account_data in &sync_data.accounts {
+            // An archived account with the code is revived rather than duplicated
+            let ledger_id = account_data
+                .ledger_id
+                .as_deref()
+                .unwrap_or(DEFAULT_LEDGER_ID);
+            let existing: Option<Account> = accounts::table
+                .filter(accounts::ledger_id.eq(ledger_id))
+                .filter(accounts::code.eq(&account_data.code))
+                .order(accounts::is_active.desc())
+                .first(conn)
+                .optional()?;
+
+            let Some(existing) = existing else {
+                insert_account(conn, &config, clock.get_ref(), account_data)?;
+                summary.created += 1;
+                continue;
+            };
+
+            let update = UpdateAccountRequest {
+                name: Some(account_data.name.clone()),
+                account_type: Some(account_data.account_type.clone()),
+                parent_id: account_data.parent_id.clone(),
+                is_active: Some(true),
+                is_memo: Some(account_data.is_memo),
+                opening_balance: account_data.opening_balance,
+                opening_date: account_data.opening_date.clone(),
+                ..Default::default()
+            };
+            let (_, changed) =
+                apply_account_update(conn, &config, clock.get_ref(), &existing.id, &update)?;
+            if changed {
+                summary.updated += 1;
+            } else {
+                summary.unchanged += 1;
+            }
+        }
+
+        if sync_data.deactivate_missing {
+            let active: Vec<(String, String, String)> = accounts::table
+                .filter(accounts::is_active.eq(true))
+                .filter(accounts::id.ne_all(SYSTEM_ACCOUNT_IDS))
+                .select((accounts::id, accounts::ledger_id, accounts::code))
+                .load(conn)?;
+            let missing = active
+                .into_iter()
+                .filter(|(_, ledger_id, code)| {
+                    !codes.contains(&(ledger_id.as_str(), code.as_str()))
+                })
+                .map(|(id, _, _)| id);
+
+            let deactivate = UpdateAccountRequest {
+                is_active: Some(false),
+                ..Default::default()
+            };
+            for account_id in missing {
+                apply_account_update(conn, &config, clock.get_ref(), &account_id, &deactivate)?;
+                summary.deactivated += 1;
+            }
+        }
+
+        Ok(summary)
+    })?;
+
+    if summary.created + summary.updated + summary.deactivated > 0 {
+        cache.invalidate();
+        tree_cache.invalidate();
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(summary)))
+}
+
+/// Checks a whole chart of accounts without writing anything. Each record gets
+/// the checks `POST /accounts` applies plus a parent code that resolves; duplicate
+/// codes and parent cycles across the set are reported separately.
+pub async fn validate_accounts(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    validation_data: web::Json<AccountValidationRequest>,
+) -> Result<HttpResponse, AppError> {
+    let records = &validation_data.accounts;
+    let mut conn = pool.get()?;
+
+    let set_codes: HashSet<&str> = records.iter().map(|a| a.code.as_str()).collect();
+    let mut results = Vec::with_capacity(records.len());
+
+    for (index, account_data) in records.iter().enumerate() {
+        let mut errors = match account_data.validate() {
+            Ok(()) => Vec::new(),
+            Err(e) => validation_messages(&e),
+        };
+
+        let ledger_id = account_data
+            .ledger_id
+            .as_deref()
+            .unwrap_or(DEFAULT_LEDGER_ID);
+        let checks = [
+            account_data
+                .opening_date
+                .as_deref()
+                .map_or(Ok(()), validate_opening_date),
+            check_code_format(&config, &account_data.code),
+            check_code_range(
+                &config,
+                &String::from(account_data.account_type.clone()),
+                &account_data.code,
+            ),
+            check_code_available(&mut conn, ledger_id, &account_data.code, None),
+        ];
+        for check in checks {
+            match check {
+                Ok(()) => {}
+                Err(AppError::ValidationError(msg) | AppError::Conflict(msg)) => errors.push(msg),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if let Some(ref parent_code) = account_data.parent_id {
+            let resolvable = set_codes.contains(parent_code.as_str())
+                || diesel::select(exists(
+                    accounts::table
+                        .filter(accounts::ledger_id.eq(ledger_id))
+                        .filter(accounts::code.eq(parent_code))
+                        .filter(accounts::is_active.eq(true)),
+                ))
+                .get_result(&mut conn)?;
+            if !resolvable {
+                errors.push(format!("parent {} not found", parent_code));
+            }
+        }
+
+        results.push(AccountValidationResult {
+            index,
+            code: account_data.code.clone(),
+            errors,
+        });
+    }
+
+    let mut seen: HashSet<(&str, &str)> = HashSet::new();
+    let mut duplicate_codes: Vec<String> = records
+        .iter()
+        .filter(|a| {
+            let ledger_id = a.ledger_id.as_deref().unwrap_or(DEFAULT_LEDGER_ID);
+            !seen.insert((ledger_id, &a.code))
+        })
+        .map(|a| a.code.clone())
+        .collect();
+    duplicate_codes.sort();
+    duplicate_codes.dedup();
+
+    let cycles = parent_cycles(records);
+
+    let report = AccountValidationReport {
+        valid: results.iter().all(|r| r.errors.is_empty())
+            && duplicate_codes.is_empty()
+            && cycles.is_empty(),
+        accounts: results,
+        duplicate_codes,
+        cycles,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(report)))
+}
+
+/// Cycles formed by records whose `parent_id` names another record's code.
+/// Each record has at most one parent, so every walk ends at a root, a record
+/// already walked, or a cycle.
+fn parent_cycles(records: &[CreateAccountRequest]) -> Vec<Vec<String>> {
+    let mut by_code: HashMap<&str, usize> = HashMap::new();
+    for (index, account_data) in records.iter().enumerate() {
+        by_code.entry(&account_data.code).or_insert(index);
+    }
+
+    let mut walked_from: Vec<Option<usize>> = vec![None; records.len()];
+    let mut cycles = Vec::new();
+
+    for start in 0..records.len() {
+        let mut path = Vec::new();
+        let mut current = Some(start);
+
+        while let Some(index) = current.filter(|&i| walked_from[i].is_none()) {
+            walked_from[index] = Some(start);
+            path.push(index);
+            current = records[index]
+                .parent_id
+                .as_deref()
+                .and_then(|parent| by_code.get(parent).copied());
+        }
+
+        // Reaching a record from this same walk closes a cycle
+        if let Some(index) = current.filter(|&i| walked_from[i] == Some(start)) {
+            let cycle_start = path.iter().position(|&i| i == index).unwrap_or(0);
+            cycles.push(
+                path[cycle_start..]
+                    .iter()
+                    .map(|&i| records[i].code.clone())
+                    .collect(),
+            );
+        }
+    }
+
+    cycles
+}
+
+/// Lists one page of accounts newest first, or with `modified_since` only
+/// those updated after the cutoff, oldest change first so clients can sync
+/// incrementally. `sort_by` overrides either order.
+pub async fn get_all_accounts(
+    pool: web::Data<DbPool>,
+    query: web::Query<AccountsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut conn = pool.get()?;
+
+    // Normalize to the stored updated_at format so text comparison orders correctly
+    let cutoff = match query.modified_since {
+        Some(ref modified_since) => Some(
+            DateTime::parse_from_rfc3339(modified_since)
+                .map_err(|_| {
+                    AppError::BadRequest("modified_since must be an RFC 3339 timestamp".to_string())
+                })?
+                .with_timezone(&Utc)
+                .to_rfc3339(),
+        ),
+        None => None,
+    };
+    let filtered = || {
+        let mut accounts_query = accounts::table.into_boxed();
+        if let Some(ref cutoff) = cutoff {
+            accounts_query = accounts_query.filter(accounts::updated_at.gt(cutoff.clone()));
+        }
+        accounts_query
+    };
+
+    let accounts_query = match (query.sort_by, &cutoff) {
+        (Some(sort_by), _) => filtered()
+            .order(account_order(sort_by, query.order))
+            .then_order_by(accounts::id.asc()),
+        (None, Some(_)) => filtered().order((accounts::updated_at.asc(), accounts::id.asc())),
+        (None, None) => filtered().order((accounts::created_at.desc(), accounts::id.asc())),
+    };
+
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+    let total: i64 = filtered().count().get_result(&mut conn)?;
+    let results: Vec<Account> = accounts_query.limit(limit).offset(offset).load(&mut conn)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_with_meta(
+        results,
+        ResponseMeta {
+            total,
+            limit: Some(limit),
+            offset: Some(offset),
+            truncated: false,
+        },
+    )))
+}
+
+fn account_order(sort_by: AccountSortKey, order: SortOrder) -> BoxedOrder<accounts::table> {
+    match sort_by {
+        AccountSortKey::Code => directed(accounts::code, order),
+        AccountSortKey::Name => directed(accounts::name, order),
+        AccountSortKey::AccountType => directed(accounts::account_type, order),
+        AccountSortKey::CreatedAt => directed(accounts::created_at, order),
+        AccountSortKey::UpdatedAt => directed(accounts::updated_at, order),
+    }
 }
 
 pub async fn get_account(
@@ -74,68 +504,496 @@ pub async fn get_account(
 
 pub async fn update_account(
     pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    tree_cache: web::Data<AccountTreeCache>,
+    clock: web::Data<dyn Clock>,
     path: web::Path<String>,
     account_data: web::Json<UpdateAccountRequest>,
 ) -> Result<HttpResponse, AppError> {
-    account_data
-        .validate()
-        .map_err(|e| AppError::ValidationError(format!("Validation failed: {:?}", e)))?;
+    let mut errors = match account_data.validate() {
+        Ok(()) => Vec::new(),
+        Err(e) => validation_messages(&e),
+    };
+    if let Some(Err(AppError::ValidationError(msg))) = account_data
+        .code
+        .as_deref()
+        .map(|code| check_code_format(&config, code))
+    {
+        errors.push(msg);
+    }
+    if !errors.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::validation_errors(errors)));
+    }
+
+    if let Some(ref opening_date) = account_data.opening_date {
+        validate_opening_date(opening_date)?;
+    }
 
     let account_id = path.into_inner();
     let mut conn = pool.get()?;
-    let now = Utc::now().to_rfc3339();
 
-    // Build update query dynamically
-    let _update_query = diesel::update(accounts::table.find(&account_id));
+    let (account, changed) = conn.transaction::<_, AppError, _>(|conn| {
+        apply_account_update(conn, &config, clock.get_ref(), &account_id, &account_data)
+    })?;
+
+    if changed {
+        cache.invalidate();
+        tree_cache.invalidate();
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(account)))
+}
+
+/// Applies several account updates in one database transaction. Every item is
+/// validated and guarded exactly like `PUT /accounts/{id}`; if any item fails
+/// nothing is written and the response reports which one.
+pub async fn bulk_update_accounts(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    tree_cache: web::Data<AccountTreeCache>,
+    clock: web::Data<dyn Clock>,
+    bulk_data: web::Json<BulkAccountUpdateRequest>,
+) -> Result<HttpResponse, AppError> {
+    let bulk_data = bulk_data.into_inner();
+
+    if bulk_data.updates.is_empty() {
+        return Err(AppError::ValidationError(
+            "Bulk update must contain at least one account".to_string(),
+        ));
+    }
+
+    let mut results: Vec<AccountUpdateResult> = bulk_data
+        .updates
+        .iter()
+        .map(|item| AccountUpdateResult {
+            id: item.id.clone(),
+            status: BatchItemStatus::Pending,
+            errors: collect_update_errors(&item.update),
+        })
+        .collect();
+
+    let mut conn = pool.get()?;
+
+    let outcome = if results.iter().any(|r| !r.errors.is_empty()) {
+        Err(AppError::ValidationError(
+            "Bulk update rejected".to_string(),
+        ))
+    } else {
+        conn.transaction::<_, AppError, _>(|conn| {
+            for (item, result) in bulk_data.updates.iter().zip(results.iter_mut()) {
+                match apply_account_update(conn, &config, clock.get_ref(), &item.id, &item.update) {
+                    Ok((_, changed)) => {
+                        result.status = if changed {
+                            BatchItemStatus::Updated
+                        } else {
+                            BatchItemStatus::Unchanged
+                        };
+                    }
+                    Err(e) => {
+                        result.errors.push(e.to_string());
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(())
+        })
+    };
+
+    if outcome.is_err() {
+        for result in results.iter_mut() {
+            result.status = if result.errors.is_empty() {
+                BatchItemStatus::Skipped
+            } else {
+                BatchItemStatus::Failed
+            };
+        }
+
+        return Ok(HttpResponse::BadRequest().json(ApiResponse {
+            success: false,
+            data: Some(results),
+            message: Some("Bulk update rejected; no accounts were changed".to_string()),
+            errors: None,
+            meta: None,
+        }));
+    }
+
+    if results.iter().any(|r| r.status == BatchItemStatus::Updated) {
+        cache.invalidate();
+        tree_cache.invalidate();
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(results)))
+}
+
+fn collect_update_errors(update: &UpdateAccountRequest) -> Vec<String> {
+    let mut errors = match update.validate() {
+        Ok(()) => Vec::new(),
+        Err(e) => validation_messages(&e),
+    };
+
+    if let Some(Err(AppError::ValidationError(msg))) =
+        update.opening_date.as_deref().map(validate_opening_date)
+    {
+        errors.push(msg);
+    }
+
+    errors
+}
+
+/// Writes the fields of `account_data` that differ from the stored account and
+/// returns the account with whether anything changed. Must be called inside a
+/// database transaction.
+fn apply_account_update(
+    conn: &mut SqliteConnection,
+    config: &AppConfig,
+    clock: &dyn Clock,
+    account_id: &str,
+    account_data: &UpdateAccountRequest,
+) -> Result<(Account, bool), AppError> {
+    let current: Account = accounts::table.find(account_id).first(conn)?;
+
+    // Imports and opening balances post to the system accounts by id, so they
+    // must stay active, postable, non-memo and of their seeded type
+    if SYSTEM_ACCOUNT_IDS.contains(&account_id) {
+        let disables = account_data.is_active == Some(false)
+            || account_data.postable == Some(false)
+            || account_data.is_memo == Some(true)
+            || account_data
+                .account_type
+                .clone()
+                .is_some_and(|t| String::from(t) != current.account_type);
+        if disables {
+            return Err(AppError::Conflict(format!(
+                "Account {} is a system account; it cannot be deactivated, locked, made memo or retyped",
+                current.code
+            )));
+        }
+    }
+
+    if config.immutable_codes {
+        if let Some(ref new_code) = account_data.code {
+            if &current.code != new_code {
+                return Err(AppError::Conflict(
+                    "Account codes are immutable and cannot be changed".to_string(),
+                ));
+            }
+        }
+    }
+
+    let code_changes = account_data
+        .code
+        .as_ref()
+        .is_some_and(|c| *c != current.code);
+    let reactivates = account_data.is_active == Some(true) && !current.is_active;
+    if account_data.is_active.unwrap_or(current.is_active) && (code_changes || reactivates) {
+        let code = account_data.code.as_ref().unwrap_or(&current.code);
+        check_code_available(conn, &current.ledger_id, code, Some(account_id))?;
+    }
 
-    if let Some(ref new_code) = account_data.code {
-        diesel::update(accounts::table.find(&account_id))
+    let new_account_type = account_data
+        .account_type
+        .clone()
+        .map(String::from)
+        .filter(|t| *t != current.account_type);
+
+    // Accounts predating the ranges keep working until their code or type changes
+    if code_changes || new_account_type.is_some() {
+        check_code_range(
+            config,
+            new_account_type.as_ref().unwrap_or(&current.account_type),
+            account_data.code.as_ref().unwrap_or(&current.code),
+        )?;
+    }
+
+    // Retyping an account with postings would silently flip the sign of its history
+    if new_account_type.is_some() {
+        let has_entries: bool = diesel::select(exists(
+            entries::table.filter(entries::account_id.eq(account_id)),
+        ))
+        .get_result(conn)?;
+
+        if has_entries {
+            return Err(AppError::Conflict(format!(
+                "Account {} has entries; its account type cannot be changed",
+                current.code
+            )));
+        }
+    }
+
+    // Only fields that differ from the stored record count as changes
+    let mut changed = false;
+
+    if let Some(new_code) = account_data.code.as_ref().filter(|c| **c != current.code) {
+        diesel::update(accounts::table.find(account_id))
             .set(accounts::code.eq(new_code))
-            .execute(&mut conn)?;
+            .execute(conn)?;
+        changed = true;
     }
-    if let Some(ref new_name) = account_data.name {
-        diesel::update(accounts::table.find(&account_id))
+    if let Some(new_name) = account_data.name.as_ref().filter(|n| **n != current.name) {
+        diesel::update(accounts::table.find(account_id))
             .set(accounts::name.eq(new_name))
-            .execute(&mut conn)?;
+            .execute(conn)?;
+        changed = true;
     }
-    if let Some(ref new_account_type) = account_data.account_type {
-        diesel::update(accounts::table.find(&account_id))
-            .set(accounts::account_type.eq(String::from(new_account_type.clone())))
-            .execute(&mut conn)?;
+    if let Some(new_account_type) = new_account_type {
+        diesel::update(accounts::table.find(account_id))
+            .set(accounts::account_type.eq(new_account_type))
+            .execute(conn)?;
+        changed = true;
     }
-    if let Some(ref new_parent_id) = account_data.parent_id {
-        diesel::update(accounts::table.find(&account_id))
+    if let Some(new_parent_id) = account_data
+        .parent_id
+        .as_ref()
+        .filter(|p| current.parent_id.as_ref() != Some(*p))
+    {
+        check_parent(conn, new_parent_id, Some(account_id))?;
+        diesel::update(accounts::table.find(account_id))
             .set(accounts::parent_id.eq(new_parent_id))
-            .execute(&mut conn)?;
+            .execute(conn)?;
+        changed = true;
     }
-    if let Some(new_is_active) = account_data.is_active {
-        diesel::update(accounts::table.find(&account_id))
+    if let Some(new_is_active) = account_data.is_active.filter(|a| *a != current.is_active) {
+        diesel::update(accounts::table.find(account_id))
             .set(accounts::is_active.eq(new_is_active))
-            .execute(&mut conn)?;
+            .execute(conn)?;
+        changed = true;
+    }
+    if let Some(new_is_memo) = account_data.is_memo.filter(|m| *m != current.is_memo) {
+        diesel::update(accounts::table.find(account_id))
+            .set(accounts::is_memo.eq(new_is_memo))
+            .execute(conn)?;
+        changed = true;
+    }
+    if let Some(new_opening_balance) = account_data
+        .opening_balance
+        .filter(|b| current.opening_balance.parse::<Decimal>().ok() != Some(*b))
+    {
+        diesel::update(accounts::table.find(account_id))
+            .set(accounts::opening_balance.eq(new_opening_balance.to_string()))
+            .execute(conn)?;
+        changed = true;
+    }
+    if let Some(new_opening_date) = account_data
+        .opening_date
+        .as_ref()
+        .filter(|d| current.opening_date.as_ref() != Some(*d))
+    {
+        diesel::update(accounts::table.find(account_id))
+            .set(accounts::opening_date.eq(new_opening_date))
+            .execute(conn)?;
+        changed = true;
+    }
+    if let Some(new_postable) = account_data.postable.filter(|p| *p != current.postable) {
+        diesel::update(accounts::table.find(account_id))
+            .set(accounts::postable.eq(new_postable))
+            .execute(conn)?;
+        changed = true;
+    }
+
+    if !changed {
+        return Ok((current, false));
     }
 
-    // Always update the updated_at field
-    diesel::update(accounts::table.find(&account_id))
-        .set(accounts::updated_at.eq(now))
-        .execute(&mut conn)?;
+    diesel::update(accounts::table.find(account_id))
+        .set(accounts::updated_at.eq(clock.now_rfc3339()))
+        .execute(conn)?;
 
-    let updated_account: Account = accounts::table.find(&account_id).first(&mut conn)?;
+    audit::record(
+        conn,
+        clock,
+        audit::ENTITY_ACCOUNT,
+        account_id,
+        audit::ACTION_UPDATED,
+        None,
+    )?;
 
-    Ok(HttpResponse::Ok().json(ApiResponse::success(updated_account)))
+    let updated_account: Account = accounts::table.find(account_id).first(conn)?;
+
+    Ok((updated_account, true))
 }
 
+/// Deactivates the account, keeping it and its history on record. With
+/// `?hard=true` the row is removed instead, which is only allowed while no
+/// entry posts to it.
 pub async fn delete_account(
     pool: web::Data<DbPool>,
+    cache: web::Data<TrialBalanceCache>,
+    tree_cache: web::Data<AccountTreeCache>,
+    clock: web::Data<dyn Clock>,
     path: web::Path<String>,
+    query: web::Query<DeleteAccountQuery>,
 ) -> Result<HttpResponse, AppError> {
     let account_id = path.into_inner();
     let mut conn = pool.get()?;
 
-    let deleted_rows = diesel::delete(accounts::table.find(&account_id)).execute(&mut conn)?;
+    conn.transaction::<_, AppError, _>(|conn| {
+        let account: Account = accounts::table
+            .find(&account_id)
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("Account not found".to_string()))?;
 
-    if deleted_rows == 0 {
-        return Err(AppError::NotFound("Account not found".to_string()));
-    }
+        if SYSTEM_ACCOUNT_IDS.contains(&account.id.as_str()) {
+            return Err(AppError::Conflict(format!(
+                "Account {} is a system account and cannot be deleted or deactivated",
+                account.code
+            )));
+        }
+
+        if query.hard {
+            let has_children: bool = diesel::select(exists(
+                accounts::table.filter(accounts::parent_id.eq(&account_id)),
+            ))
+            .get_result(conn)?;
+            if has_children {
+                return Err(AppError::Conflict(format!(
+                    "Account {} has child accounts and cannot be deleted",
+                    account.code
+                )));
+            }
+
+            let has_entries: bool = diesel::select(exists(
+                entries::table.filter(entries::account_id.eq(&account_id)),
+            ))
+            .get_result(conn)?;
+            if has_entries {
+                return Err(AppError::BadRequest(format!(
+                    "Account {} has entries and cannot be deleted; deactivate it instead",
+                    account.code
+                )));
+            }
+
+            diesel::delete(accounts::table.find(&account_id)).execute(conn)?;
+        } else {
+            diesel::update(accounts::table.find(&account_id))
+                .set((
+                    accounts::is_active.eq(false),
+                    accounts::updated_at.eq(clock.now_rfc3339()),
+                ))
+                .execute(conn)?;
+        }
+
+        audit::record(
+            conn,
+            clock.get_ref(),
+            audit::ENTITY_ACCOUNT,
+            &account_id,
+            audit::ACTION_DELETED,
+            Some(json!({ "hard": query.hard })),
+        )
+    })?;
+
+    cache.invalidate();
+
+    tree_cache.invalidate();
 
     Ok(HttpResponse::NoContent().json(ApiResponse::success("Account deleted successfully")))
 }
+
+/// Rejects codes that do not match `LEDGER_ACCOUNT_CODE_PATTERN`.
+fn check_code_format(config: &AppConfig, code: &str) -> Result<(), AppError> {
+    if !config.account_code_pattern.is_match(code) {
+        return Err(AppError::ValidationError(format!(
+            "code: '{}' does not match the required format {}",
+            code,
+            config.account_code_pattern.as_str()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rejects a code outside the configured ranges for its account type.
+fn check_code_range(config: &AppConfig, account_type: &str, code: &str) -> Result<(), AppError> {
+    match config.account_code_ranges.get(account_type) {
+        Some(allowed) if !allowed.iter().any(|range| range.allows(code)) => {
+            let allowed: Vec<String> = allowed.iter().map(ToString::to_string).collect();
+            Err(AppError::ValidationError(format!(
+                "code {} is outside the range for {} accounts ({})",
+                code,
+                account_type,
+                allowed.join(", ")
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Codes are unique among a ledger's active accounts only; archived accounts
+/// and other ledgers may share one.
+fn check_code_available(
+    conn: &mut SqliteConnection,
+    ledger_id: &str,
+    code: &str,
+    exclude_id: Option<&str>,
+) -> Result<(), AppError> {
+    let mut active_query = accounts::table
+        .filter(accounts::ledger_id.eq(ledger_id))
+        .filter(accounts::code.eq(code))
+        .filter(accounts::is_active.eq(true))
+        .into_boxed();
+    if let Some(exclude_id) = exclude_id {
+        active_query = active_query.filter(accounts::id.ne(exclude_id));
+    }
+
+    let taken: i64 = active_query.count().get_result(conn)?;
+    if taken > 0 {
+        return Err(AppError::Conflict(format!(
+            "Account code {} is already used by an active account",
+            code
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rejects a parent that does not exist, or that is `account_id` itself or one
+/// of its descendants, since either would leave the hierarchy without a root.
+fn check_parent(
+    conn: &mut SqliteConnection,
+    parent_id: &str,
+    account_id: Option<&str>,
+) -> Result<(), AppError> {
+    if account_id == Some(parent_id) {
+        return Err(AppError::BadRequest(
+            "An account cannot be its own parent".to_string(),
+        ));
+    }
+    if !diesel::select(exists(accounts::table.find(parent_id))).get_result(conn)? {
+        return Err(AppError::BadRequest(
+            "Parent account does not exist".to_string(),
+        ));
+    }
+
+    let Some(account_id) = account_id else {
+        return Ok(());
+    };
+    // Walk up from the new parent; `seen` stops the walk on data that already loops
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut ancestor = Some(parent_id.to_string());
+    while let Some(id) = ancestor.filter(|id| seen.insert(id.clone())) {
+        if id == account_id {
+            return Err(AppError::BadRequest(
+                "Circular account hierarchy detected".to_string(),
+            ));
+        }
+        ancestor = accounts::table
+            .find(&id)
+            .select(accounts::parent_id)
+            .first::<Option<String>>(conn)
+            .optional()?
+            .flatten();
+    }
+
+    Ok(())
+}
+
+fn validate_opening_date(opening_date: &str) -> Result<(), AppError> {
+    NaiveDate::parse_from_str(opening_date, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| {
+            AppError::ValidationError("opening_date must be a YYYY-MM-DD date".to_string())
+        })
+}
@@ -0,0 +1,153 @@
+use actix_web::{web, HttpResponse, Result, Scope};
+use diesel::prelude::*;
+use diesel::sqlite::Sqlite;
+
+use crate::cache::{AccountTreeCache, TrialBalanceCache};
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::database::DbPool;
+use crate::errors::AppError;
+use crate::handlers::page_bounds;
+use crate::models::{ApiResponse, AuditLogEntry, AuditLogQuery, CorruptAmount, Paginated};
+use crate::schema::{audit_log, entries, unreadable_amounts};
+use crate::seed;
+
+pub fn config() -> Scope {
+    web::scope("/admin")
+        .route("/audit", web::get().to(get_audit_log))
+        .route("/corrupt-amounts", web::get().to(get_corrupt_amounts))
+        .route(
+            "/corrupt-amounts/{entry_id}/resolve",
+            web::post().to(resolve_corrupt_amounts),
+        )
+        .route("/seed-demo", web::post().to(seed_demo))
+}
+
+/// Lists audit log rows newest first, one page at a time.
+pub async fn get_audit_log(
+    pool: web::Data<DbPool>,
+    query: web::Query<AuditLogQuery>,
+) -> Result<HttpResponse, AppError> {
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+    let mut conn = pool.get()?;
+
+    let total: i64 = filtered_audit_log(&query).count().get_result(&mut conn)?;
+
+    let items: Vec<AuditLogEntry> = filtered_audit_log(&query)
+        .order((audit_log::created_at.desc(), audit_log::id.desc()))
+        .limit(limit)
+        .offset(offset)
+        .load(&mut conn)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(Paginated {
+        items,
+        total,
+        limit,
+        offset,
+    })))
+}
+
+fn filtered_audit_log(query: &AuditLogQuery) -> audit_log::BoxedQuery<'_, Sqlite> {
+    let mut audit_query = audit_log::table.into_boxed();
+
+    if let Some(ref entity_type) = query.entity_type {
+        audit_query = audit_query.filter(audit_log::entity_type.eq(entity_type));
+    }
+    if let Some(ref action) = query.action {
+        audit_query = audit_query.filter(audit_log::action.eq(action));
+    }
+    if let Some(ref from) = query.from {
+        audit_query = audit_query.filter(audit_log::created_at.ge(from));
+    }
+    if let Some(ref to) = query.to {
+        audit_query = audit_query.filter(audit_log::created_at.le(to));
+    }
+
+    audit_query
+}
+
+/// Lists the entry amounts the conversion to storage units could not read and
+/// replaced with zero, with the text each held. They stay listed until resolved.
+pub async fn get_corrupt_amounts(pool: web::Data<DbPool>) -> Result<HttpResponse, AppError> {
+    let mut conn = pool.get()?;
+
+    let corrupt: Vec<CorruptAmount> = unreadable_amounts::table
+        .inner_join(entries::table)
+        .filter(entries::amount_flagged.eq(true))
+        .select((
+            unreadable_amounts::entry_id,
+            entries::transaction_id,
+            entries::account_id,
+            unreadable_amounts::field,
+            unreadable_amounts::raw_value,
+        ))
+        .order((
+            unreadable_amounts::entry_id.asc(),
+            unreadable_amounts::field.asc(),
+        ))
+        .load(&mut conn)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(corrupt)))
+}
+
+/// Clears the review flag of an entry once its zeroed amounts have been dealt
+/// with, e.g. by reversing the transaction, and drops the recorded text.
+pub async fn resolve_corrupt_amounts(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let entry_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let updated = diesel::update(
+            entries::table
+                .find(&entry_id)
+                .filter(entries::amount_flagged.eq(true)),
+        )
+        .set(entries::amount_flagged.eq(false))
+        .execute(conn)?;
+        if updated == 0 {
+            return Err(AppError::NotFound(format!(
+                "No flagged entry with id {}",
+                entry_id
+            )));
+        }
+
+        diesel::delete(
+            unreadable_amounts::table.filter(unreadable_amounts::entry_id.eq(&entry_id)),
+        )
+        .execute(conn)?;
+        Ok(())
+    })?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Entry amounts resolved")))
+}
+
+/// Loads the demo dataset. Only available when development endpoints are enabled.
+pub async fn seed_demo(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    tree_cache: web::Data<AccountTreeCache>,
+    clock: web::Data<dyn Clock>,
+) -> Result<HttpResponse, AppError> {
+    if !config.dev_endpoints {
+        return Err(AppError::NotFound(
+            "Development endpoints are disabled".to_string(),
+        ));
+    }
+
+    let mut conn = pool.get()?;
+
+    let summary = seed::seed_demo(&mut conn, &config, clock.get_ref())?;
+
+    if summary.accounts_created > 0 || summary.transactions_created > 0 {
+        cache.invalidate();
+    }
+    if summary.accounts_created > 0 {
+        tree_cache.invalidate();
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(summary)))
+}
@@ -1,18 +1,24 @@
+use std::collections::HashMap;
+
 use actix_web::{web, HttpResponse, Result, Scope};
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use rust_decimal::Decimal;
 
 use crate::database::DbPool;
 use crate::errors::AppError;
-use crate::models::{Account, AccountBalance, ApiResponse, BalanceQuery, Entry};
-use crate::schema::{
-    accounts::{self, dsl::*},
-    entries::{self, dsl::*},
+use crate::extractors::ReportRange;
+use crate::models::{
+    Account, AccountBalance, AccountBalanceDetail, AccountType, ApiResponse, BalanceAtTransaction,
+    BalanceQuery, Entry, NormalSide, Transaction, TransactionStatus,
 };
+use crate::money::{self, Amount};
+use crate::schema::{accounts, entries, transactions};
 
 pub fn config() -> Scope {
     web::scope("/balance")
         .route("", web::get().to(get_balances))
+        .route("/export.csv", web::get().to(export_balances_csv))
         .route("/{account_id}", web::get().to(get_account_balance))
 }
 
@@ -22,85 +28,336 @@ pub async fn get_balances(
 ) -> Result<HttpResponse, AppError> {
     let mut conn = pool.get()?;
 
-    let mut account_query = accounts::table.into_boxed();
+    let balances = load_balances(&mut conn, &query)?;
 
-    if let Some(ref account_type_filter) = query.account_type {
-        account_query = account_query.filter(accounts::account_type.eq(account_type_filter));
-    }
+    Ok(HttpResponse::Ok().json(ApiResponse::success(balances)))
+}
 
-    let all_accounts: Vec<Account> = account_query.load(&mut conn)?;
+/// Same rows and filters as `get_balances`, as a CSV download.
+pub async fn export_balances_csv(
+    pool: web::Data<DbPool>,
+    query: web::Query<BalanceQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut conn = pool.get()?;
 
-    let mut balances = Vec::new();
+    let balances = load_balances(&mut conn, &query)?;
 
-    for account in all_accounts {
-        let account_entries: Vec<Entry> = entries::table
-            .filter(entries::account_id.eq(&account.id))
-            .load(&mut conn)?;
-
-        let mut debit_total = Decimal::ZERO;
-        let mut credit_total = Decimal::ZERO;
-
-        for entry in account_entries {
-            debit_total += entry.debit_amount.parse().unwrap_or(Decimal::ZERO);
-            credit_total += entry.credit_amount.parse().unwrap_or(Decimal::ZERO);
-        }
-
-        let balance = match account.account_type.as_str() {
-            "asset" | "expense" => debit_total - credit_total,
-            "liability" | "equity" | "revenue" => credit_total - debit_total,
-            _ => debit_total - credit_total,
-        };
-
-        balances.push(AccountBalance {
-            account_id: account.id,
-            account_code: account.code,
-            account_name: account.name,
-            account_type: account.account_type,
-            debit_total,
-            credit_total,
-            balance,
-        });
+    let mut csv =
+        String::from("account_code,account_name,account_type,debit_total,credit_total,balance\n");
+    for balance in &balances {
+        let row = [
+            csv_field(&balance.account_code),
+            csv_field(&balance.account_name),
+            csv_field(&balance.account_type),
+            money::format_amount(balance.debit_total.value, balance.debit_total.scale),
+            money::format_amount(balance.credit_total.value, balance.credit_total.scale),
+            money::format_amount(balance.balance.value, balance.balance.scale),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
     }
 
-    Ok(HttpResponse::Ok().json(ApiResponse::success(balances)))
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            "Content-Disposition",
+            "attachment; filename=\"balances.csv\"",
+        ))
+        .body(csv))
 }
 
 pub async fn get_account_balance(
     pool: web::Data<DbPool>,
     path: web::Path<String>,
+    query: web::Query<BalanceQuery>,
 ) -> Result<HttpResponse, AppError> {
     let acc_id = path.into_inner();
+    let range = ReportRange::parse(query.from_date.as_deref(), query.to_date.as_deref())?;
     let mut conn = pool.get()?;
 
-    let account: Account = accounts::table.find(&acc_id).first(&mut conn)?;
+    let account: Account = accounts::table
+        .find(&acc_id)
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("account {} not found", acc_id)))?;
 
-    let account_entries: Vec<Entry> = entries::table
+    let mut entry_query = entries::table
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
         .filter(entries::account_id.eq(&acc_id))
-        .load(&mut conn)?;
+        .filter(transactions::voided.eq(false))
+        .select(entries::all_columns)
+        .into_boxed();
+
+    if let Some(movement_start) = movement_start(&account) {
+        entry_query = entry_query.filter(transactions::posting_date.ge(movement_start));
+    }
+    // Bounded the same way as `load_balances`
+    if query.from_date.is_some() {
+        entry_query = entry_query.filter(transactions::posting_date.ge(range.from_bound()));
+    }
+    if query.to_date.is_some() {
+        entry_query = entry_query.filter(transactions::posting_date.le(range.to_bound()));
+    }
+
+    if !query.include_drafts {
+        entry_query =
+            entry_query.filter(transactions::status.eq(TransactionStatus::Posted.as_str()));
+    }
+
+    let account_entries: Vec<Entry> = entry_query.load(&mut conn)?;
+
+    // Built first so overflow is reported; the unreconciled subset is then in range
+    let balance = build_account_balance(account, &account_entries)?;
+
+    let (unreconciled_debits, unreconciled_credits) = account_entries
+        .iter()
+        .filter(|entry| !entry.reconciled)
+        .fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(debits, credits), entry| {
+                let debit: Decimal = money::from_storage_units(entry.debit_amount);
+                let credit: Decimal = money::from_storage_units(entry.credit_amount);
+                (debits + debit, credits + credit)
+            },
+        );
+    let unreconciled_balance = match balance.normal_side {
+        NormalSide::Debit => unreconciled_debits - unreconciled_credits,
+        NormalSide::Credit => unreconciled_credits - unreconciled_debits,
+    };
+
+    let scale = balance.balance.scale;
+    let detail = AccountBalanceDetail {
+        reconciled_balance: Amount::new(balance.balance.value - unreconciled_balance, scale),
+        unreconciled_balance: Amount::new(unreconciled_balance, scale),
+        balance,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(detail)))
+}
+
+/// Balance of an account immediately after a transaction, counting every
+/// posted entry ordered at or before it by (posting_date, created_at, id).
+pub async fn get_balance_at_transaction(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (acc_id, trans_id) = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let account: Account = accounts::table
+        .find(&acc_id)
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("account {} not found", acc_id)))?;
+    let transaction: Transaction = transactions::table
+        .find(&trans_id)
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("transaction {} not found", trans_id)))?;
+
+    let mut entry_query = entries::table
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .filter(entries::account_id.eq(&acc_id))
+        .filter(transactions::voided.eq(false))
+        .filter(transactions::status.eq(TransactionStatus::Posted.as_str()))
+        .filter(
+            transactions::posting_date
+                .lt(&transaction.posting_date)
+                .or(transactions::posting_date
+                    .eq(&transaction.posting_date)
+                    .and(transactions::created_at.lt(&transaction.created_at)))
+                .or(transactions::posting_date
+                    .eq(&transaction.posting_date)
+                    .and(transactions::created_at.eq(&transaction.created_at))
+                    .and(transactions::id.le(&transaction.id))),
+        )
+        .select(entries::all_columns)
+        .into_boxed();
+
+    if let Some(movement_start) = movement_start(&account) {
+        entry_query = entry_query.filter(transactions::posting_date.ge(movement_start));
+    }
+
+    let account_entries: Vec<Entry> = entry_query.load(&mut conn)?;
+
+    let account_involved: bool = diesel::select(diesel::dsl::exists(
+        entries::table
+            .filter(entries::transaction_id.eq(&trans_id))
+            .filter(entries::account_id.eq(&acc_id)),
+    ))
+    .get_result(&mut conn)?;
+
+    let snapshot = BalanceAtTransaction {
+        transaction_id: transaction.id,
+        transaction_reference: transaction.reference,
+        transaction_date: transaction.transaction_date,
+        account_involved,
+        balance: build_account_balance(account, &account_entries)?,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(snapshot)))
+}
+
+/// Loads the balance of every account matching the query filters. Void
+/// transactions never count; drafts only when `include_drafts` is set.
+pub(crate) fn load_balances(
+    conn: &mut diesel::SqliteConnection,
+    query: &BalanceQuery,
+) -> Result<Vec<AccountBalance>, AppError> {
+    // Both bounds are inclusive; a bare `to_date` covers that whole day
+    let range = ReportRange::parse(query.from_date.as_deref(), query.to_date.as_deref())?;
+    let mut account_query = accounts::table.into_boxed();
+
+    if let Some(ref account_type_filter) = query.account_type {
+        account_query = account_query.filter(accounts::account_type.eq(account_type_filter));
+    }
+
+    let all_accounts: Vec<Account> = account_query.load(conn)?;
+
+    // One query for the entries of every account, grouped here, rather than
+    // one query per account
+    let mut entry_query = entries::table
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .inner_join(accounts::table.on(accounts::id.eq(entries::account_id)))
+        .filter(transactions::voided.eq(false))
+        .select((entries::all_columns, transactions::posting_date))
+        .into_boxed();
+
+    if let Some(ref account_type_filter) = query.account_type {
+        entry_query = entry_query.filter(accounts::account_type.eq(account_type_filter));
+    }
+    if query.from_date.is_some() {
+        entry_query = entry_query.filter(transactions::posting_date.ge(range.from_bound()));
+    }
+    if query.to_date.is_some() {
+        entry_query = entry_query.filter(transactions::posting_date.le(range.to_bound()));
+    }
+    if !query.include_drafts {
+        entry_query =
+            entry_query.filter(transactions::status.eq(TransactionStatus::Posted.as_str()));
+    }
+
+    let mut entries_by_account: HashMap<String, Vec<(Entry, String)>> = HashMap::new();
+    for (entry, posting_date) in entry_query.load::<(Entry, String)>(conn)? {
+        entries_by_account
+            .entry(entry.account_id.clone())
+            .or_default()
+            .push((entry, posting_date));
+    }
+
+    let mut balances = Vec::with_capacity(all_accounts.len());
+
+    for account in all_accounts {
+        let movement_start = movement_start(&account);
+        let account_entries: Vec<Entry> = entries_by_account
+            .remove(&account.id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, posting_date)| {
+                movement_start
+                    .as_deref()
+                    .is_none_or(|start| posting_date.as_str() >= start)
+            })
+            .map(|(entry, _)| entry)
+            .collect();
+
+        balances.push(build_account_balance(account, &account_entries)?);
+    }
+
+    Ok(balances)
+}
+
+fn build_account_balance(
+    account: Account,
+    account_entries: &[Entry],
+) -> Result<AccountBalance, AppError> {
+    // Sums past Decimal's range are reported rather than allowed to panic
+    let overflow =
+        || AppError::InternalServerError(format!("balance overflow for account {}", account.id));
 
     let mut debit_total = Decimal::ZERO;
     let mut credit_total = Decimal::ZERO;
 
     for entry in account_entries {
-        debit_total += entry.debit_amount.parse().unwrap_or(Decimal::ZERO);
-        credit_total += entry.credit_amount.parse().unwrap_or(Decimal::ZERO);
+        check_entry_readable(&entry.id, entry.amount_flagged)?;
+        debit_total = debit_total
+            .checked_add(money::from_storage_units(entry.debit_amount))
+            .ok_or_else(overflow)?;
+        credit_total = credit_total
+            .checked_add(money::from_storage_units(entry.credit_amount))
+            .ok_or_else(overflow)?;
     }
 
-    let balance = match account.account_type.as_str() {
-        "asset" | "expense" => debit_total - credit_total,
-        "liability" | "equity" | "revenue" => credit_total - debit_total,
-        _ => debit_total - credit_total,
-    };
+    let opening_balance = money::parse_stored(&account.opening_balance, || {
+        format!("opening balance of account {}", account.code)
+    })?;
+    let balance = opening_balance
+        .checked_add(signed_balance(&account, debit_total, credit_total)?)
+        .ok_or_else(overflow)?;
+
+    // signed_balance has rejected unknown types, so the conversion cannot default
+    let normal_side = AccountType::from(account.account_type.clone()).normal_side();
+    let scale = money::currency_scale(&account.currency);
 
-    let account_balance = AccountBalance {
+    Ok(AccountBalance {
         account_id: account.id,
         account_code: account.code,
         account_name: account.name,
         account_type: account.account_type,
-        debit_total,
-        credit_total,
-        balance,
-    };
+        currency: account.currency,
+        opening_balance: Amount::new(opening_balance, scale),
+        debit_total: Amount::new(debit_total, scale),
+        credit_total: Amount::new(credit_total, scale),
+        balance: Amount::new(balance, scale),
+        is_memo: account.is_memo,
+        normal_side,
+        balance_is_normal: balance >= Decimal::ZERO,
+    })
+}
+
+/// Flagged entries had an amount the integer conversion could not read and
+/// now hold 0 in its place, so any total including them would be wrong.
+pub(crate) fn check_entry_readable(entry_id: &str, amount_flagged: bool) -> Result<(), AppError> {
+    if amount_flagged {
+        return Err(AppError::InternalServerError(format!(
+            "entry {} has an unreadable amount; review it through /admin/corrupt-amounts",
+            entry_id
+        )));
+    }
+    Ok(())
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
 
-    Ok(HttpResponse::Ok().json(ApiResponse::success(account_balance)))
+/// First posting date whose entries move the balance past the opening
+/// figure: the day after `opening_date`, or `None` when every entry counts.
+pub(crate) fn movement_start(account: &Account) -> Option<String> {
+    let opening_date = account.opening_date.as_deref()?;
+    let day = NaiveDate::parse_from_str(opening_date, "%Y-%m-%d").ok()?;
+    day.succ_opt()
+        .map(|next| next.format("%Y-%m-%d").to_string())
+}
+
+/// Signs a balance according to the normal side of the account type.
+/// Unknown types are an error rather than being treated as assets.
+pub fn signed_balance(
+    account: &Account,
+    debit_total: Decimal,
+    credit_total: Decimal,
+) -> Result<Decimal, AppError> {
+    match account.account_type.as_str() {
+        "asset" | "expense" => Ok(debit_total - credit_total),
+        "liability" | "equity" | "revenue" => Ok(credit_total - debit_total),
+        _ => Err(AppError::InternalServerError(format!(
+            "unknown account_type for account {}",
+            account.id
+        ))),
+    }
 }
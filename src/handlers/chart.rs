@@ -0,0 +1,181 @@
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::cache::AccountTreeCache;
+use crate::database::DbPool;
+use crate::errors::AppError;
+use crate::extractors::ReportRange;
+use crate::handlers::balance::load_balances;
+use crate::models::{Account, AccountTreeNode, ApiResponse, BalanceQuery, ChartNode, ChartQuery};
+use crate::money::{self, Amount};
+use crate::schema::accounts;
+
+/// The whole chart of accounts as a tree, each node carrying its own balance as
+/// of `as_of` and the balance rolled up from its sub-accounts.
+pub async fn get_chart_of_accounts(
+    pool: web::Data<DbPool>,
+    query: web::Query<ChartQuery>,
+) -> Result<HttpResponse, AppError> {
+    let range = ReportRange::parse(None, query.as_of.as_deref())?;
+    let mut conn = pool.get()?;
+
+    let all_accounts: Vec<Account> = accounts::table
+        .order(accounts::code.asc())
+        .load(&mut conn)?;
+
+    let balance_query = BalanceQuery {
+        account_id: None,
+        account_type: None,
+        from_date: None,
+        to_date: Some(range.to_bound()),
+        include_drafts: query.include_drafts,
+    };
+    let balances: HashMap<String, Amount> = load_balances(&mut conn, &balance_query)?
+        .into_iter()
+        .map(|b| (b.account_id, b.balance))
+        .collect();
+
+    let (roots, children) = hierarchy(&all_accounts);
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut chart = Vec::new();
+
+    for root in roots {
+        chart.push(build_node(root, &children, &balances, &mut visited));
+    }
+
+    // Accounts whose parents form a cycle are never reached from a root; list
+    // them at the top level rather than dropping them
+    for account in &all_accounts {
+        if !visited.contains(account.id.as_str()) {
+            chart.push(build_node(account, &children, &balances, &mut visited));
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(chart)))
+}
+
+/// The account hierarchy without balances, served from `AccountTreeCache` and
+/// rebuilt from the database only after an account change.
+pub async fn get_account_tree(
+    pool: web::Data<DbPool>,
+    tree_cache: web::Data<AccountTreeCache>,
+) -> Result<HttpResponse, AppError> {
+    if let Some(tree) = tree_cache.get() {
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(tree)));
+    }
+
+    let mut conn = pool.get()?;
+    let all_accounts: Vec<Account> = accounts::table
+        .order(accounts::code.asc())
+        .load(&mut conn)?;
+
+    let (roots, children) = hierarchy(&all_accounts);
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut tree = Vec::new();
+
+    for root in roots {
+        tree.push(build_tree_node(root, &children, &mut visited));
+    }
+    // As in the chart, accounts caught in a parent cycle go to the top level
+    for account in &all_accounts {
+        if !visited.contains(account.id.as_str()) {
+            tree.push(build_tree_node(account, &children, &mut visited));
+        }
+    }
+
+    tree_cache.insert(tree.clone());
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(tree)))
+}
+
+/// Top-level accounts, meaning those without a known parent, and the children
+/// of every account, both in the order of `all_accounts`.
+fn hierarchy(all_accounts: &[Account]) -> (Vec<&Account>, HashMap<&str, Vec<&Account>>) {
+    let known: HashSet<&str> = all_accounts.iter().map(|a| a.id.as_str()).collect();
+    let mut roots = Vec::new();
+    let mut children: HashMap<&str, Vec<&Account>> = HashMap::new();
+
+    for account in all_accounts {
+        match account.parent_id.as_deref().filter(|p| known.contains(p)) {
+            Some(parent_id) => children.entry(parent_id).or_default().push(account),
+            None => roots.push(account),
+        }
+    }
+
+    (roots, children)
+}
+
+fn build_tree_node<'a>(
+    account: &'a Account,
+    children: &HashMap<&str, Vec<&'a Account>>,
+    visited: &mut HashSet<&'a str>,
+) -> AccountTreeNode {
+    visited.insert(&account.id);
+
+    let mut child_nodes = Vec::new();
+    for child in children.get(account.id.as_str()).into_iter().flatten() {
+        if !visited.contains(child.id.as_str()) {
+            child_nodes.push(build_tree_node(child, children, visited));
+        }
+    }
+
+    AccountTreeNode {
+        account_id: account.id.clone(),
+        account_code: account.code.clone(),
+        account_name: account.name.clone(),
+        account_type: account.account_type.clone(),
+        is_active: account.is_active,
+        is_memo: account.is_memo,
+        children: child_nodes,
+    }
+}
+
+fn build_node<'a>(
+    account: &'a Account,
+    children: &HashMap<&str, Vec<&'a Account>>,
+    balances: &HashMap<String, Amount>,
+    visited: &mut HashSet<&'a str>,
+) -> ChartNode {
+    visited.insert(&account.id);
+
+    let balance = balances
+        .get(&account.id)
+        .copied()
+        .unwrap_or_else(|| Amount::zero(money::currency_scale(&account.currency)));
+    let mut rolled_up_balance = balance.value;
+    let mut child_nodes = Vec::new();
+
+    for child in children.get(account.id.as_str()).into_iter().flatten() {
+        if visited.contains(child.id.as_str()) {
+            continue;
+        }
+        let node = build_node(child, children, balances, visited);
+
+        // Memo accounts never count towards the totals above them
+        if !node.is_memo {
+            if debit_normal(&node.account_type) == debit_normal(&account.account_type) {
+                rolled_up_balance += node.rolled_up_balance.value;
+            } else {
+                rolled_up_balance -= node.rolled_up_balance.value;
+            }
+        }
+        child_nodes.push(node);
+    }
+
+    ChartNode {
+        account_id: account.id.clone(),
+        account_code: account.code.clone(),
+        account_name: account.name.clone(),
+        account_type: account.account_type.clone(),
+        is_active: account.is_active,
+        is_memo: account.is_memo,
+        balance,
+        rolled_up_balance: Amount::new(rolled_up_balance, balance.scale),
+        children: child_nodes,
+    }
+}
+
+fn debit_normal(account_type: &str) -> bool {
+    matches!(account_type, "asset" | "expense")
+}
@@ -0,0 +1,184 @@
+use actix_web::{web, HttpResponse, Result, Scope};
+use diesel::prelude::*;
+
+use crate::audit;
+use crate::clock::Clock;
+use crate::database::DbPool;
+use crate::errors::AppError;
+use crate::handlers::page_bounds;
+use crate::models::{
+    resolve_entry_description, Account, AmountFormatQuery, ApiResponse, EntriesQuery, Entry,
+    EntryAmounts, EntryDetail, EntryWithAccount, ResponseMeta, Transaction,
+};
+use crate::money::{self, Amount};
+use crate::schema::{accounts, entries, transactions};
+
+pub fn config() -> Scope {
+    web::scope("/entries")
+        .route("", web::get().to(get_entries))
+        .route("/{id}", web::get().to(get_entry))
+        .route("/{id}/reconcile", web::post().to(reconcile_entry))
+}
+
+/// Lists entries across transactions in posting order, optionally narrowed to
+/// one account and a transaction date range.
+pub async fn get_entries(
+    pool: web::Data<DbPool>,
+    query: web::Query<EntriesQuery>,
+) -> Result<HttpResponse, AppError> {
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+
+    let mut conn = pool.get()?;
+
+    let mut count_query = entries::table
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .into_boxed();
+    let mut entry_query = entries::table
+        .inner_join(accounts::table.on(accounts::id.eq(entries::account_id)))
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .select((
+            entries::all_columns,
+            accounts::all_columns,
+            transactions::description,
+        ))
+        .into_boxed();
+
+    if let Some(ref account_id) = query.account_id {
+        count_query = count_query.filter(entries::account_id.eq(account_id));
+        entry_query = entry_query.filter(entries::account_id.eq(account_id));
+    }
+    if let Some(ref from) = query.from {
+        count_query = count_query.filter(transactions::transaction_date.ge(from));
+        entry_query = entry_query.filter(transactions::transaction_date.ge(from));
+    }
+    if let Some(ref to) = query.to {
+        count_query = count_query.filter(transactions::transaction_date.le(to));
+        entry_query = entry_query.filter(transactions::transaction_date.le(to));
+    }
+
+    let total: i64 = count_query.count().get_result(&mut conn)?;
+
+    let rows: Vec<(Entry, Account, String)> = entry_query
+        .order((
+            transactions::transaction_date.asc(),
+            entries::created_at.asc(),
+            entries::id.asc(),
+        ))
+        .limit(limit)
+        .offset(offset)
+        .load(&mut conn)?;
+
+    let results: Vec<EntryWithAccount> = rows
+        .into_iter()
+        .map(|(entry, account, transaction_description)| {
+            entry_with_account(entry, account, &transaction_description)
+                .with_amount_format(query.amount_format)
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_with_meta(
+        results,
+        ResponseMeta {
+            total,
+            limit: Some(limit),
+            offset: Some(offset),
+            truncated: false,
+        },
+    )))
+}
+
+/// One entry with its account and the reference and date of its transaction.
+pub async fn get_entry(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<AmountFormatQuery>,
+) -> Result<HttpResponse, AppError> {
+    let entry_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let (entry, account, transaction): (Entry, Account, Transaction) = entries::table
+        .inner_join(accounts::table.on(accounts::id.eq(entries::account_id)))
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .filter(entries::id.eq(&entry_id))
+        .select((
+            entries::all_columns,
+            accounts::all_columns,
+            transactions::all_columns,
+        ))
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("entry {} not found", entry_id)))?;
+
+    let detail = EntryDetail {
+        entry: entry_with_account(entry, account, &transaction.description)
+            .with_amount_format(query.amount_format),
+        transaction_reference: transaction.reference,
+        transaction_date: transaction.transaction_date,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(detail)))
+}
+
+pub(crate) fn entry_with_account(
+    entry: Entry,
+    account: Account,
+    transaction_description: &str,
+) -> EntryWithAccount {
+    let (description, description_inherited) =
+        resolve_entry_description(entry.description, transaction_description);
+    let scale = money::currency_scale(&entry.currency);
+    EntryWithAccount {
+        id: entry.id,
+        transaction_id: entry.transaction_id,
+        account_id: entry.account_id,
+        account_code: account.code,
+        account_name: account.name,
+        amounts: EntryAmounts::Columns {
+            debit_amount: Amount::new(money::from_storage_units(entry.debit_amount), scale),
+            credit_amount: Amount::new(money::from_storage_units(entry.credit_amount), scale),
+        },
+        currency: entry.currency,
+        description,
+        description_inherited,
+        created_at: entry.created_at,
+    }
+}
+
+/// Marks an entry as matched against a statement.
+pub async fn reconcile_entry(
+    pool: web::Data<DbPool>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let entry_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let entry = conn.transaction::<_, AppError, _>(|conn| {
+        let entry: Entry = entries::table
+            .find(&entry_id)
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("entry {} not found", entry_id)))?;
+
+        if entry.reconciled {
+            return Ok(entry);
+        }
+
+        diesel::update(entries::table.find(&entry_id))
+            .set(entries::reconciled.eq(true))
+            .execute(conn)?;
+
+        audit::record(
+            conn,
+            clock.get_ref(),
+            audit::ENTITY_ENTRY,
+            &entry_id,
+            audit::ACTION_RECONCILED,
+            None,
+        )?;
+
+        Ok(entries::table.find(&entry_id).first(conn)?)
+    })?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(entry)))
+}
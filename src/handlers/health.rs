@@ -1,6 +1,80 @@
-use actix_web::{HttpResponse, Result};
-use crate::models::ApiResponse;
+use crate::database::{self, DbPool};
+use crate::models::{ApiResponse, ReadinessCheck, ReadinessReport};
+use actix_web::{web, HttpResponse, Result};
+use diesel::RunQueryDsl;
+use std::time::Duration;
+
+/// How long the readiness probe waits for a pooled connection.
+const READY_CONNECTION_TIMEOUT: Duration = Duration::from_secs(1);
 
 pub async fn health_check() -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(ApiResponse::success("OK".to_string())))
-}
\ No newline at end of file
+}
+
+/// Reports whether this instance can serve traffic: the pool has room, the
+/// database answers and every migration is applied. Any failing check makes
+/// the whole response `503` so load balancers stop routing to it.
+pub async fn readiness_check(pool: web::Data<DbPool>) -> Result<HttpResponse> {
+    let mut checks = Vec::new();
+
+    // Read before taking a connection for the other checks
+    let state = pool.state();
+    let max_size = pool.max_size();
+    checks.push(ReadinessCheck {
+        name: "pool".to_string(),
+        ok: state.idle_connections > 0 || state.connections < max_size,
+        detail: format!(
+            "{} of {} connections open, {} idle",
+            state.connections, max_size, state.idle_connections
+        ),
+    });
+
+    match pool.get_timeout(READY_CONNECTION_TIMEOUT) {
+        Ok(mut conn) => {
+            let ping = diesel::sql_query("SELECT 1").execute(&mut conn);
+            checks.push(ReadinessCheck {
+                name: "database".to_string(),
+                ok: ping.is_ok(),
+                detail: match ping {
+                    Ok(_) => "reachable".to_string(),
+                    Err(e) => e.to_string(),
+                },
+            });
+
+            let (ok, detail) = match database::pending_migrations(&mut conn) {
+                Ok(pending) if pending.is_empty() => (true, "up to date".to_string()),
+                Ok(pending) => (false, format!("pending: {}", pending.join(", "))),
+                Err(e) => (false, e.to_string()),
+            };
+            checks.push(ReadinessCheck {
+                name: "migrations".to_string(),
+                ok,
+                detail,
+            });
+        }
+        Err(e) => {
+            for name in ["database", "migrations"] {
+                checks.push(ReadinessCheck {
+                    name: name.to_string(),
+                    ok: false,
+                    detail: format!("no connection: {}", e),
+                });
+            }
+        }
+    }
+
+    let ready = checks.iter().all(|check| check.ok);
+    let report = ReadinessReport { ready, checks };
+
+    if ready {
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(report)));
+    }
+
+    Ok(HttpResponse::ServiceUnavailable().json(ApiResponse {
+        success: false,
+        data: Some(report),
+        message: Some("Not ready".to_string()),
+        errors: None,
+        meta: None,
+    }))
+}
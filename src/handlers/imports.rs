@@ -0,0 +1,406 @@
+use actix_web::{web, HttpResponse, Result, Scope};
+use diesel::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::bank_feed::{self, CategorizationRule, NormalizedBankTxn};
+use crate::cache::TrialBalanceCache;
+use crate::camt053;
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::csv_import;
+use crate::database::DbPool;
+use crate::errors::AppError;
+use crate::handlers::transactions::{
+    collect_transaction_errors, get_transaction_with_entries_by_id, insert_transaction,
+};
+use crate::models::{
+    Account, ApiResponse, CreateEntryRequest, CreateTransactionRequest, TransactionWithEntries,
+    SUSPENSE_ACCOUNT_ID,
+};
+use crate::schema::accounts;
+
+pub fn config() -> Scope {
+    web::scope("/import")
+        .route("/camt053", web::post().to(import_camt053))
+        .route("/csv", web::post().to(import_csv))
+        .route("/bank", web::post().to(import_bank))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatementImportQuery {
+    pub account_id: String,
+    pub contra_account_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BankImportQuery {
+    pub account_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BankImportRequest {
+    pub transactions: Vec<NormalizedBankTxn>,
+    #[serde(default)]
+    pub rules: Vec<CategorizationRule>,
+    /// Contra account for transactions no rule matches. If unset, the configured
+    /// income or expense contra for the transaction's sign, else suspense.
+    pub default_contra_account_id: Option<String>,
+}
+
+/// Posts every statement entry as a two-entry transaction between the bank account
+/// and the contra account. The whole statement is imported atomically.
+pub async fn import_camt053(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    query: web::Query<StatementImportQuery>,
+    body: String,
+) -> Result<HttpResponse, AppError> {
+    let statement_entries = camt053::parse_statement(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid CAMT.053 document: {}", e)))?;
+
+    let mut conn = pool.get()?;
+
+    let bank_account: Account = accounts::table
+        .find(&query.account_id)
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound("Bank account not found".to_string()))?;
+
+    let contra_exists: i64 = accounts::table
+        .find(&query.contra_account_id)
+        .count()
+        .get_result(&mut conn)?;
+    if contra_exists == 0 {
+        return Err(AppError::NotFound("Contra account not found".to_string()));
+    }
+
+    let requests: Vec<CreateTransactionRequest> = statement_entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            statement_transaction(entry, index, &bank_account, &query.contra_account_id)
+        })
+        .collect();
+
+    let errors: Vec<String> = requests
+        .iter()
+        .enumerate()
+        .flat_map(|(index, request)| {
+            collect_transaction_errors(request)
+                .into_iter()
+                .map(move |e| format!("entry {}: {}", index, e))
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(errors.join("; ")));
+    }
+
+    let imported = conn.transaction::<_, AppError, _>(|conn| {
+        insert_all(conn, &config, clock.get_ref(), &requests)
+    })?;
+
+    if !imported.is_empty() {
+        cache.invalidate();
+    }
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(imported)))
+}
+
+/// Imports a journal CSV (see `csv_import`). Accounts are resolved by code or id
+/// in a single pass before anything is written, and the file is imported atomically.
+pub async fn import_csv(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    body: String,
+) -> Result<HttpResponse, AppError> {
+    let rows = csv_import::parse_journal(&body)
+        .map_err(|errors| AppError::ValidationError(errors.join("; ")))?;
+
+    let mut conn = pool.get()?;
+
+    let mut account_refs: Vec<&str> = rows.iter().map(|row| row.account.as_str()).collect();
+    account_refs.sort_unstable();
+    account_refs.dedup();
+
+    let known_accounts: Vec<Account> = accounts::table
+        .filter(
+            accounts::code
+                .eq_any(&account_refs)
+                .or(accounts::id.eq_any(&account_refs)),
+        )
+        .load(&mut conn)?;
+
+    // Ids win over codes should a value happen to match both, and an active
+    // account wins over archived ones sharing its code
+    let mut resolved: HashMap<&str, &Account> = HashMap::new();
+    for account in &known_accounts {
+        resolved
+            .entry(account.code.as_str())
+            .and_modify(|existing| {
+                if account.is_active && !existing.is_active {
+                    *existing = account;
+                }
+            })
+            .or_insert(account);
+    }
+    for account in &known_accounts {
+        resolved.insert(account.id.as_str(), account);
+    }
+
+    let unknown: Vec<String> = rows
+        .iter()
+        .filter(|row| !resolved.contains_key(row.account.as_str()))
+        .map(|row| format!("line {}: unknown account '{}'", row.line, row.account))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(AppError::ValidationError(unknown.join("; ")));
+    }
+
+    let mut requests: Vec<CreateTransactionRequest> = Vec::new();
+    for row in &rows {
+        let account = resolved[row.account.as_str()];
+        let entry = CreateEntryRequest {
+            account_id: account.id.clone(),
+            debit_amount: row.debit,
+            credit_amount: row.credit,
+            description: (!row.description.is_empty()).then(|| row.description.clone()),
+            currency: None,
+        };
+
+        match requests.iter_mut().find(|r| r.reference == row.reference) {
+            Some(request) => request.entries.push(entry),
+            None => requests.push(CreateTransactionRequest {
+                reference: row.reference.clone(),
+                description: row.description.clone(),
+                transaction_date: row.date.clone(),
+                posting_date: None,
+                metadata: None,
+                entries: vec![entry],
+                ledger_id: Some(account.ledger_id.clone()),
+                status: None,
+            }),
+        }
+    }
+
+    let errors: Vec<String> = requests
+        .iter()
+        .flat_map(|request| {
+            collect_transaction_errors(request)
+                .into_iter()
+                .map(move |e| format!("transaction {}: {}", request.reference, e))
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(errors.join("; ")));
+    }
+
+    let imported = conn.transaction::<_, AppError, _>(|conn| {
+        insert_all(conn, &config, clock.get_ref(), &requests)
+    })?;
+
+    if !imported.is_empty() {
+        cache.invalidate();
+    }
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(imported)))
+}
+
+/// Posts normalized bank feed transactions against the bank account. Each one's
+/// contra account comes from the first matching categorization rule, falling back
+/// to the default contra account and then to the configured income or expense
+/// contra for its sign. The feed is imported atomically.
+pub async fn import_bank(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    query: web::Query<BankImportQuery>,
+    import_data: web::Json<BankImportRequest>,
+) -> Result<HttpResponse, AppError> {
+    let mut conn = pool.get()?;
+
+    let bank_account: Account = accounts::table
+        .find(&query.account_id)
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound("Bank account not found".to_string()))?;
+
+    let sign_contras = config.import_sign_contras();
+
+    // A matching rule wins, then the request's default, then the configured
+    // contra for the transaction's direction, then suspense
+    let contra_ids: Vec<&str> = import_data
+        .transactions
+        .iter()
+        .map(|txn| {
+            bank_feed::categorize(&import_data.rules, &txn.description)
+                .map(|rule| rule.contra_account_id.as_str())
+                .or(import_data.default_contra_account_id.as_deref())
+                .or(sign_contras.for_amount(txn.amount))
+                .unwrap_or(SUSPENSE_ACCOUNT_ID)
+        })
+        .collect();
+
+    let known_contras: Vec<String> = accounts::table
+        .filter(accounts::id.eq_any(&contra_ids))
+        .select(accounts::id)
+        .load(&mut conn)?;
+
+    let mut errors: Vec<String> = Vec::new();
+    for (index, (txn, contra_id)) in import_data.transactions.iter().zip(&contra_ids).enumerate() {
+        if txn.amount.is_zero() {
+            errors.push(format!("transaction {}: amount must not be zero", index));
+        }
+        if !known_contras.iter().any(|id| id == contra_id) {
+            errors.push(format!(
+                "transaction {}: unknown contra account '{}'",
+                index, contra_id
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(errors.join("; ")));
+    }
+
+    let requests: Vec<CreateTransactionRequest> = import_data
+        .transactions
+        .iter()
+        .zip(&contra_ids)
+        .enumerate()
+        .map(|(index, (txn, contra_id))| bank_transaction(txn, index, &bank_account, contra_id))
+        .collect();
+
+    let errors: Vec<String> = requests
+        .iter()
+        .enumerate()
+        .flat_map(|(index, request)| {
+            collect_transaction_errors(request)
+                .into_iter()
+                .map(move |e| format!("transaction {}: {}", index, e))
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(errors.join("; ")));
+    }
+
+    let imported = conn.transaction::<_, AppError, _>(|conn| {
+        insert_all(conn, &config, clock.get_ref(), &requests)
+    })?;
+
+    if !imported.is_empty() {
+        cache.invalidate();
+    }
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(imported)))
+}
+
+/// Inserts every request in order and returns them as stored. Must be called
+/// inside a database transaction.
+fn insert_all(
+    conn: &mut SqliteConnection,
+    config: &AppConfig,
+    clock: &dyn Clock,
+    requests: &[CreateTransactionRequest],
+) -> Result<Vec<TransactionWithEntries>, AppError> {
+    let mut imported: Vec<TransactionWithEntries> = Vec::with_capacity(requests.len());
+    for request in requests {
+        let transaction_id = insert_transaction(conn, config, clock, request)?;
+        imported.push(get_transaction_with_entries_by_id(conn, &transaction_id)?);
+    }
+    Ok(imported)
+}
+
+fn bank_transaction(
+    txn: &NormalizedBankTxn,
+    index: usize,
+    bank_account: &Account,
+    contra_account_id: &str,
+) -> CreateTransactionRequest {
+    let amount = txn.amount.abs();
+    // Positive amounts are money in and debit the bank; negative ones credit it
+    let (debit_account, credit_account) = if txn.amount.is_sign_positive() {
+        (bank_account.id.clone(), contra_account_id.to_string())
+    } else {
+        (contra_account_id.to_string(), bank_account.id.clone())
+    };
+
+    CreateTransactionRequest {
+        reference: format!("BANK-{}-{}", bank_account.code, index + 1),
+        description: txn.description.clone(),
+        transaction_date: Some(txn.date.clone()),
+        posting_date: None,
+        metadata: None,
+        entries: vec![
+            CreateEntryRequest {
+                account_id: debit_account,
+                debit_amount: Some(amount),
+                credit_amount: None,
+                description: txn.counterparty.clone(),
+                currency: None,
+            },
+            CreateEntryRequest {
+                account_id: credit_account,
+                debit_amount: None,
+                credit_amount: Some(amount),
+                description: txn.counterparty.clone(),
+                currency: None,
+            },
+        ],
+        ledger_id: Some(bank_account.ledger_id.clone()),
+        status: None,
+    }
+}
+
+fn statement_transaction(
+    entry: &camt053::StatementEntry,
+    index: usize,
+    bank_account: &Account,
+    contra_account_id: &str,
+) -> CreateTransactionRequest {
+    // Money coming in debits the bank; money going out credits it
+    let (debit_account, credit_account) = if entry.is_credit {
+        (bank_account.id.clone(), contra_account_id.to_string())
+    } else {
+        (contra_account_id.to_string(), bank_account.id.clone())
+    };
+
+    CreateTransactionRequest {
+        reference: entry
+            .reference
+            .clone()
+            .unwrap_or_else(|| format!("CAMT-{}-{}", bank_account.code, index + 1)),
+        description: entry
+            .description
+            .clone()
+            .unwrap_or_else(|| "CAMT.053 import".to_string()),
+        transaction_date: entry.booking_date.clone(),
+        posting_date: None,
+        metadata: None,
+        entries: vec![
+            CreateEntryRequest {
+                account_id: debit_account,
+                debit_amount: Some(entry.amount),
+                credit_amount: None,
+                description: entry.description.clone(),
+                currency: None,
+            },
+            CreateEntryRequest {
+                account_id: credit_account,
+                debit_amount: None,
+                credit_amount: Some(entry.amount),
+                description: entry.description.clone(),
+                currency: None,
+            },
+        ],
+        ledger_id: Some(bank_account.ledger_id.clone()),
+        status: None,
+    }
+}
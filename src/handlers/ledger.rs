@@ -0,0 +1,126 @@
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::database::DbPool;
+use crate::errors::AppError;
+use crate::extractors::ReportRange;
+use crate::handlers::balance::{check_entry_readable, movement_start, signed_balance};
+use crate::models::{
+    Account, AccountLedger, AccountLedgerQuery, ApiResponse, Entry, LedgerEntry, LedgerQuery,
+    Transaction, TransactionStatus,
+};
+use crate::money::{self, Amount};
+use crate::schema::{accounts, entries, transactions};
+
+/// The statement of one account: every entry in posting order with the running
+/// balance after it, optionally limited to `from_date`..=`to_date`.
+pub async fn get_account_ledger(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<AccountLedgerQuery>,
+) -> Result<HttpResponse, AppError> {
+    let account_id = path.into_inner();
+    let range = ReportRange::parse(query.from_date.as_deref(), query.to_date.as_deref())?;
+    let mut conn = pool.get()?;
+
+    let account: Account = accounts::table
+        .find(&account_id)
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("account {} not found", account_id)))?;
+
+    let ledger_query = LedgerQuery {
+        from: query.from_date.as_ref().map(|_| range.from_bound()),
+        to: query.to_date.as_ref().map(|_| range.to_bound()),
+        include_drafts: query.include_drafts,
+    };
+    let ledger = load_account_ledger(&mut conn, account, &ledger_query)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(ledger)))
+}
+
+/// Builds the chronological ledger of one account with a running balance
+/// signed according to the account type. The running balance starts from the
+/// balance brought forward: the account's opening balance plus every entry
+/// posted before `from`, so the last row always matches `closing_balance`.
+pub(crate) fn load_account_ledger(
+    conn: &mut diesel::SqliteConnection,
+    account: Account,
+    query: &LedgerQuery,
+) -> Result<AccountLedger, AppError> {
+    let mut entry_query = entries::table
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .filter(entries::account_id.eq(&account.id))
+        .filter(transactions::voided.eq(false))
+        .into_boxed();
+
+    // Entries up to the opening date are already part of the opening balance
+    if let Some(movement_start) = movement_start(&account) {
+        entry_query = entry_query.filter(transactions::posting_date.ge(movement_start));
+    }
+    if let Some(ref to) = query.to {
+        entry_query = entry_query.filter(transactions::posting_date.le(to));
+    }
+    if !query.include_drafts {
+        entry_query =
+            entry_query.filter(transactions::status.eq(TransactionStatus::Posted.as_str()));
+    }
+
+    let rows: Vec<(Entry, Transaction)> = entry_query
+        .order((
+            transactions::posting_date.asc(),
+            entries::created_at.asc(),
+            entries::id.asc(),
+        ))
+        .load(conn)?;
+
+    let opening_balance = money::parse_stored(&account.opening_balance, || {
+        format!("opening balance of account {}", account.code)
+    })?;
+    let mut running_balance = opening_balance;
+    let mut net_change = Decimal::ZERO;
+    let mut ledger_entries = Vec::with_capacity(rows.len());
+    let scale = money::currency_scale(&account.currency);
+
+    for (entry, transaction) in rows {
+        check_entry_readable(&entry.id, entry.amount_flagged)?;
+        let debit_amount: Decimal = money::from_storage_units(entry.debit_amount);
+        let credit_amount: Decimal = money::from_storage_units(entry.credit_amount);
+        let movement = signed_balance(&account, debit_amount, credit_amount)?;
+        running_balance += movement;
+
+        let before_window = query
+            .from
+            .as_ref()
+            .is_some_and(|from| transaction.posting_date < *from);
+        if before_window {
+            continue;
+        }
+        net_change += movement;
+
+        ledger_entries.push(LedgerEntry {
+            entry_id: entry.id,
+            transaction_id: transaction.id,
+            transaction_reference: transaction.reference,
+            transaction_description: transaction.description,
+            transaction_date: transaction.transaction_date,
+            posting_date: transaction.posting_date,
+            debit_amount: Amount::new(debit_amount, scale),
+            credit_amount: Amount::new(credit_amount, scale),
+            description: entry.description,
+            running_balance: Amount::new(running_balance, scale),
+        });
+    }
+
+    Ok(AccountLedger {
+        account_id: account.id,
+        account_code: account.code,
+        account_name: account.name,
+        account_type: account.account_type,
+        opening_balance: Amount::new(running_balance - net_change, scale),
+        closing_balance: Amount::new(running_balance, scale),
+        net_change: Amount::new(net_change, scale),
+        entries: ledger_entries,
+    })
+}
@@ -0,0 +1,133 @@
+use actix_web::{web, HttpResponse, Result, Scope};
+use diesel::prelude::*;
+use validator::Validate;
+
+use crate::clock::Clock;
+use crate::database::DbPool;
+use crate::errors::{validation_messages, AppError};
+use crate::models::{ApiResponse, LedgerQuota, QuotaUsage, UpdateQuotaRequest};
+use crate::schema::{accounts, ledger_quotas, transactions};
+
+pub fn config() -> Scope {
+    web::scope("/ledgers")
+        .route("/{ledger_id}/quota", web::get().to(get_quota))
+        .route("/{ledger_id}/quota", web::put().to(update_quota))
+}
+
+pub async fn get_quota(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let ledger = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let usage = load_quota_usage(&mut conn, &ledger)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(usage)))
+}
+
+pub async fn update_quota(
+    pool: web::Data<DbPool>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+    quota_data: web::Json<UpdateQuotaRequest>,
+) -> Result<HttpResponse, AppError> {
+    if let Err(e) = quota_data.validate() {
+        let errors = validation_messages(&e);
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::validation_errors(errors)));
+    }
+
+    let ledger = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let quota = LedgerQuota {
+        ledger_id: ledger.clone(),
+        max_accounts: quota_data.max_accounts,
+        max_transactions: quota_data.max_transactions,
+        updated_at: clock.now_rfc3339(),
+    };
+
+    diesel::replace_into(ledger_quotas::table)
+        .values(&quota)
+        .execute(&mut conn)?;
+
+    let usage = load_quota_usage(&mut conn, &ledger)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(usage)))
+}
+
+fn load_quota(
+    conn: &mut diesel::SqliteConnection,
+    ledger: &str,
+) -> Result<Option<LedgerQuota>, AppError> {
+    Ok(ledger_quotas::table
+        .find(ledger)
+        .first::<LedgerQuota>(conn)
+        .optional()?)
+}
+
+fn load_quota_usage(
+    conn: &mut diesel::SqliteConnection,
+    ledger: &str,
+) -> Result<QuotaUsage, AppError> {
+    let quota = load_quota(conn, ledger)?;
+
+    let account_count: i64 = accounts::table
+        .filter(accounts::ledger_id.eq(ledger))
+        .count()
+        .get_result(conn)?;
+    let transaction_count: i64 = transactions::table
+        .filter(transactions::ledger_id.eq(ledger))
+        .count()
+        .get_result(conn)?;
+
+    Ok(QuotaUsage {
+        ledger_id: ledger.to_string(),
+        max_accounts: quota.as_ref().and_then(|q| q.max_accounts),
+        max_transactions: quota.as_ref().and_then(|q| q.max_transactions),
+        account_count,
+        transaction_count,
+    })
+}
+
+/// Rejects a new account once the ledger has reached its account quota.
+pub(crate) fn check_account_quota(
+    conn: &mut diesel::SqliteConnection,
+    ledger: &str,
+) -> Result<(), AppError> {
+    let Some(max_accounts) = load_quota(conn, ledger)?.and_then(|q| q.max_accounts) else {
+        return Ok(());
+    };
+
+    let account_count: i64 = accounts::table
+        .filter(accounts::ledger_id.eq(ledger))
+        .count()
+        .get_result(conn)?;
+
+    if account_count >= max_accounts {
+        return Err(AppError::Conflict("quota exceeded".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Rejects a new transaction once the ledger has reached its transaction quota.
+pub(crate) fn check_transaction_quota(
+    conn: &mut diesel::SqliteConnection,
+    ledger: &str,
+) -> Result<(), AppError> {
+    let Some(max_transactions) = load_quota(conn, ledger)?.and_then(|q| q.max_transactions) else {
+        return Ok(());
+    };
+
+    let transaction_count: i64 = transactions::table
+        .filter(transactions::ledger_id.eq(ledger))
+        .count()
+        .get_result(conn)?;
+
+    if transaction_count >= max_transactions {
+        return Err(AppError::Conflict("quota exceeded".to_string()));
+    }
+
+    Ok(())
+}
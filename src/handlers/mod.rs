@@ -1,4 +1,104 @@
+use actix_web::error::JsonPayloadError;
+use actix_web::web;
+use diesel::expression::expression_types::NotSelectable;
+use diesel::helper_types::{Asc, Desc};
+use diesel::prelude::*;
+use diesel::sqlite::Sqlite;
+
+use crate::config::{AppConfig, DEFAULT_API_BASE_PATH};
+use crate::errors::AppError;
+use crate::models::SortOrder;
+
 pub mod accounts;
+pub mod admin;
 pub mod balance;
+pub mod chart;
+pub mod entries;
 pub mod health;
-pub mod transactions;
\ No newline at end of file
+pub mod imports;
+pub mod ledger;
+pub mod ledgers;
+pub mod periods;
+pub mod reconciliation;
+pub mod reports;
+pub mod transactions;
+
+/// Liveness and readiness probes, served outside `api_base_path`. Load
+/// shedding and authentication let them through so orchestrators can always
+/// reach them.
+pub const PROBE_PATHS: [&str; 2] = ["/health", "/ready"];
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// Resolves optional `limit`/`offset` query parameters, rejecting values out of range.
+pub(crate) fn page_bounds(limit: Option<i64>, offset: Option<i64>) -> Result<(i64, i64), AppError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let offset = offset.unwrap_or(0);
+
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    if offset < 0 {
+        return Err(AppError::BadRequest(
+            "offset must not be negative".to_string(),
+        ));
+    }
+
+    Ok((limit, offset))
+}
+
+/// An `ORDER BY` term for a boxed query on table `T`.
+pub(crate) type BoxedOrder<T> = Box<dyn BoxableExpression<T, Sqlite, SqlType = NotSelectable>>;
+
+/// Sorts by `column` in the requested direction.
+pub(crate) fn directed<T, C>(column: C, order: SortOrder) -> BoxedOrder<T>
+where
+    C: ExpressionMethods,
+    Asc<C>: BoxableExpression<T, Sqlite, SqlType = NotSelectable> + 'static,
+    Desc<C>: BoxableExpression<T, Sqlite, SqlType = NotSelectable> + 'static,
+{
+    match order {
+        SortOrder::Asc => Box::new(column.asc()),
+        SortOrder::Desc => Box::new(column.desc()),
+    }
+}
+
+/// JSON body settings for the write endpoints. Unless disabled in the config, a
+/// body sent with another content type is rejected with 415.
+pub fn json_config(config: &AppConfig) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .content_type_required(config.require_json_content_type)
+        .error_handler(|err, _req| match err {
+            JsonPayloadError::ContentType => {
+                AppError::UnsupportedMediaType("expected application/json".to_string()).into()
+            }
+            other => other.into(),
+        })
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    configure_at(cfg, DEFAULT_API_BASE_PATH);
+}
+
+/// Registers the API under `base_path` instead of the default `/api/v1`.
+/// `/health` and `/ready` always stay at the root.
+pub fn configure_at(cfg: &mut web::ServiceConfig, base_path: &str) {
+    cfg.service(
+        web::scope(base_path)
+            .service(accounts::config())
+            .service(transactions::config())
+            .service(balance::config())
+            .service(entries::config())
+            .service(ledgers::config())
+            .service(periods::config())
+            .service(admin::config())
+            .service(imports::config())
+            .service(reports::config()),
+    )
+    .service(web::resource("/health").route(web::get().to(health::health_check)))
+    .service(web::resource("/ready").route(web::get().to(health::readiness_check)));
+}
@@ -0,0 +1,325 @@
+use actix_web::{web, HttpResponse, Result, Scope};
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use log::warn;
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::clock::Clock;
+use crate::database::DbPool;
+use crate::errors::{validation_messages, AppError};
+use crate::extractors::ReportRange;
+use crate::handlers::balance::load_balances;
+use crate::models::{
+    AccountBalance, ApiResponse, BalanceQuery, CreatePeriodRequest, Period, PeriodSnapshot,
+    PeriodSnapshotRow, SnapshotBalance,
+};
+use crate::money::{self, Amount};
+use crate::schema::{period_snapshots, periods};
+
+pub fn config() -> Scope {
+    web::scope("/periods")
+        .route("", web::post().to(create_period))
+        .route("", web::get().to(get_all_periods))
+        .route("/{id}/close", web::post().to(close_period))
+        .route("/{id}/snapshot", web::post().to(create_snapshot))
+        .route("/{id}/snapshot", web::get().to(get_snapshot))
+}
+
+pub async fn create_period(
+    pool: web::Data<DbPool>,
+    clock: web::Data<dyn Clock>,
+    period_data: web::Json<CreatePeriodRequest>,
+) -> Result<HttpResponse, AppError> {
+    if let Err(e) = period_data.validate() {
+        let errors = validation_messages(&e);
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::validation_errors(errors)));
+    }
+
+    let start = parse_period_date("start_date", &period_data.start_date)?;
+    let end = parse_period_date("end_date", &period_data.end_date)?;
+    if end < start {
+        return Err(AppError::ValidationError(
+            "end_date must not be before start_date".to_string(),
+        ));
+    }
+
+    let mut conn = pool.get()?;
+
+    let period = Period {
+        id: Uuid::new_v4().to_string(),
+        name: period_data.name.clone(),
+        start_date: period_data.start_date.clone(),
+        end_date: period_data.end_date.clone(),
+        closed_at: None,
+        created_at: clock.now_rfc3339(),
+    };
+
+    diesel::insert_into(periods::table)
+        .values(&period)
+        .execute(&mut conn)?;
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(period)))
+}
+
+pub async fn get_all_periods(pool: web::Data<DbPool>) -> Result<HttpResponse, AppError> {
+    let mut conn = pool.get()?;
+
+    let results: Vec<Period> = periods::table
+        .order(periods::start_date.asc())
+        .load(&mut conn)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(results)))
+}
+
+pub async fn close_period(
+    pool: web::Data<DbPool>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let period_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let period: Period = periods::table.find(&period_id).first(&mut conn)?;
+    if period.closed_at.is_some() {
+        return Err(AppError::Conflict("Period is already closed".to_string()));
+    }
+
+    diesel::update(periods::table.find(&period_id))
+        .set(periods::closed_at.eq(clock.now_rfc3339()))
+        .execute(&mut conn)?;
+
+    let period: Period = periods::table.find(&period_id).first(&mut conn)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(period)))
+}
+
+/// Freezes every account balance as of the period's last day. A period has at
+/// most one snapshot; later back-dated edits show up as drift on `get_snapshot`.
+pub async fn create_snapshot(
+    pool: web::Data<DbPool>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let period_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let period = find_period(&mut conn, &period_id)?;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let existing: i64 = period_snapshots::table
+            .filter(period_snapshots::period_id.eq(&period.id))
+            .count()
+            .get_result(conn)?;
+        if existing > 0 {
+            return Err(AppError::Conflict(format!(
+                "Period {} already has a snapshot",
+                period.name
+            )));
+        }
+
+        let now = clock.now_rfc3339();
+        let rows: Vec<PeriodSnapshotRow> = period_end_balances(conn, &period)?
+            .into_iter()
+            .map(|balance| PeriodSnapshotRow {
+                period_id: period.id.clone(),
+                account_id: balance.account_id,
+                account_code: balance.account_code,
+                account_name: balance.account_name,
+                balance: balance.balance.value.to_string(),
+                created_at: now.clone(),
+            })
+            .collect();
+
+        diesel::insert_into(period_snapshots::table)
+            .values(&rows)
+            .execute(conn)?;
+
+        Ok(())
+    })?;
+
+    let snapshot = load_snapshot(&mut conn, period)?;
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(snapshot)))
+}
+
+pub async fn get_snapshot(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let period_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let period = find_period(&mut conn, &period_id)?;
+    let snapshot = load_snapshot(&mut conn, period)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(snapshot)))
+}
+
+pub(crate) fn find_period(
+    conn: &mut diesel::SqliteConnection,
+    period_id: &str,
+) -> Result<Period, AppError> {
+    periods::table
+        .find(period_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("period {} not found", period_id)))
+}
+
+/// Posted balances of every account through the period's last day.
+pub(crate) fn period_end_balances(
+    conn: &mut diesel::SqliteConnection,
+    period: &Period,
+) -> Result<Vec<AccountBalance>, AppError> {
+    let range = ReportRange::parse(None, Some(&period.end_date))?;
+    let balance_query = BalanceQuery {
+        account_id: None,
+        account_type: None,
+        from_date: None,
+        to_date: Some(range.to_bound()),
+        include_drafts: false,
+    };
+
+    load_balances(conn, &balance_query)
+}
+
+/// Reads a period's snapshot and compares each frozen balance with the live one.
+fn load_snapshot(
+    conn: &mut diesel::SqliteConnection,
+    period: Period,
+) -> Result<PeriodSnapshot, AppError> {
+    let rows: Vec<PeriodSnapshotRow> = period_snapshots::table
+        .filter(period_snapshots::period_id.eq(&period.id))
+        .order(period_snapshots::account_code.asc())
+        .load(conn)?;
+
+    let Some(created_at) = rows.first().map(|row| row.created_at.clone()) else {
+        return Err(AppError::NotFound(format!(
+            "Period {} has no snapshot",
+            period.name
+        )));
+    };
+
+    let mut live: HashMap<String, AccountBalance> = period_end_balances(conn, &period)?
+        .into_iter()
+        .map(|balance| (balance.account_id.clone(), balance))
+        .collect();
+
+    let mut balances: Vec<SnapshotBalance> = rows
+        .into_iter()
+        .map(|row| {
+            let balance = money::parse_stored(&row.balance, || {
+                format!("snapshot balance of account {}", row.account_code)
+            })?;
+            // An account missing from the live balances renders at the
+            // stored value's own scale
+            let live_balance = live
+                .remove(&row.account_id)
+                .map_or(Amount::zero(balance.scale()), |live| live.balance);
+            let scale = live_balance.scale;
+            Ok(SnapshotBalance {
+                account_id: row.account_id,
+                account_code: row.account_code,
+                account_name: row.account_name,
+                balance: Amount::new(balance, scale),
+                live_balance,
+                drift: Amount::new(live_balance.value - balance, scale),
+            })
+        })
+        .collect::<Result<_, AppError>>()?;
+
+    // Accounts created after the snapshot only matter once they carry a balance
+    let mut new_accounts: Vec<SnapshotBalance> = live
+        .into_values()
+        .filter(|live| !live.balance.value.is_zero())
+        .map(|live| SnapshotBalance {
+            account_id: live.account_id,
+            account_code: live.account_code,
+            account_name: live.account_name,
+            balance: Amount::zero(live.balance.scale),
+            live_balance: live.balance,
+            drift: live.balance,
+        })
+        .collect();
+    new_accounts.sort_by(|a, b| a.account_code.cmp(&b.account_code));
+    balances.extend(new_accounts);
+
+    Ok(PeriodSnapshot {
+        period_id: period.id,
+        period_name: period.name,
+        as_of: period.end_date,
+        created_at,
+        has_drift: balances.iter().any(|b| !b.drift.value.is_zero()),
+        balances,
+    })
+}
+
+/// Logs a warning when a change posting on `posting_date` lands in a period
+/// whose balances were already frozen by a snapshot.
+pub(crate) fn warn_if_snapshotted(
+    conn: &mut diesel::SqliteConnection,
+    posting_date: &str,
+) -> Result<(), AppError> {
+    let snapshotted: Option<String> = periods::table
+        .inner_join(period_snapshots::table)
+        .filter(periods::start_date.le(day_of(posting_date)))
+        .filter(periods::end_date.ge(day_of(posting_date)))
+        .select(periods::name)
+        .first(conn)
+        .optional()?;
+
+    if let Some(period_name) = snapshotted {
+        warn!(
+            "Change dated {} alters period {} after its snapshot; balances will drift",
+            posting_date, period_name
+        );
+    }
+
+    Ok(())
+}
+
+/// Rejects changes to a transaction posting inside a closed period.
+pub(crate) fn ensure_period_open(
+    conn: &mut diesel::SqliteConnection,
+    posting_date: &str,
+) -> Result<(), AppError> {
+    let closed: Option<Period> = periods::table
+        .filter(periods::closed_at.is_not_null())
+        .filter(periods::start_date.le(day_of(posting_date)))
+        .filter(periods::end_date.ge(day_of(posting_date)))
+        .first(conn)
+        .optional()?;
+
+    match closed {
+        Some(period) => Err(AppError::Conflict(format!(
+            "Period {} is closed",
+            period.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Finds the period whose dates cover `posting_date`, open or closed.
+pub(crate) fn period_covering(
+    conn: &mut diesel::SqliteConnection,
+    posting_date: &str,
+) -> Result<Option<Period>, AppError> {
+    Ok(periods::table
+        .filter(periods::start_date.le(day_of(posting_date)))
+        .filter(periods::end_date.ge(day_of(posting_date)))
+        .order(periods::start_date.desc())
+        .first(conn)
+        .optional()?)
+}
+
+/// Transaction dates may carry a time; periods are whole days.
+fn day_of(transaction_date: &str) -> &str {
+    transaction_date.get(..10).unwrap_or(transaction_date)
+}
+
+fn parse_period_date(field: &str, value: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| AppError::ValidationError(format!("{} must be a YYYY-MM-DD date", field)))
+}
@@ -0,0 +1,96 @@
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::database::DbPool;
+use crate::errors::AppError;
+use crate::extractors::ReportRange;
+use crate::handlers::balance::{check_entry_readable, movement_start, signed_balance};
+use crate::models::{
+    Account, ApiResponse, Entry, Reconciliation, ReconciliationQuery, Transaction,
+    TransactionStatus, UnreconciledEntry,
+};
+use crate::money::{self, Amount};
+use crate::schema::{accounts, entries, transactions};
+
+/// Reconciles an account against a statement balance as of a date, listing the
+/// posted entries up to that date that have not been reconciled yet.
+pub async fn get_reconciliation(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<ReconciliationQuery>,
+) -> Result<HttpResponse, AppError> {
+    let account_id = path.into_inner();
+    let range = ReportRange::parse(None, query.as_of.as_deref())?;
+    let mut conn = pool.get()?;
+
+    let account: Account = accounts::table
+        .find(&account_id)
+        .first(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("account {} not found", account_id)))?;
+
+    let mut entry_query = entries::table
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .filter(entries::account_id.eq(&account.id))
+        .filter(transactions::voided.eq(false))
+        .filter(transactions::status.eq(TransactionStatus::Posted.as_str()))
+        .filter(transactions::posting_date.le(range.to_bound()))
+        .into_boxed();
+
+    if let Some(movement_start) = movement_start(&account) {
+        entry_query = entry_query.filter(transactions::posting_date.ge(movement_start));
+    }
+
+    let rows: Vec<(Entry, Transaction)> = entry_query
+        .order((
+            transactions::posting_date.asc(),
+            entries::created_at.asc(),
+            entries::id.asc(),
+        ))
+        .load(&mut conn)?;
+
+    let opening_balance = money::parse_stored(&account.opening_balance, || {
+        format!("opening balance of account {}", account.code)
+    })?;
+    let mut ledger_balance = opening_balance;
+    let mut reconciled_balance = opening_balance;
+    let mut unreconciled_total = Decimal::ZERO;
+    let mut unreconciled_entries = Vec::new();
+    let scale = money::currency_scale(&account.currency);
+
+    for (entry, transaction) in rows {
+        check_entry_readable(&entry.id, entry.amount_flagged)?;
+        let debit: Decimal = money::from_storage_units(entry.debit_amount);
+        let credit: Decimal = money::from_storage_units(entry.credit_amount);
+        let amount = signed_balance(&account, debit, credit)?;
+
+        ledger_balance += amount;
+        if entry.reconciled {
+            reconciled_balance += amount;
+        } else {
+            unreconciled_total += amount;
+            unreconciled_entries.push(UnreconciledEntry {
+                entry_id: entry.id,
+                transaction_id: transaction.id,
+                transaction_reference: transaction.reference,
+                transaction_date: transaction.transaction_date,
+                description: entry.description,
+                amount: Amount::new(amount, scale),
+            });
+        }
+    }
+
+    let reconciliation = Reconciliation {
+        account_id: account.id,
+        as_of: query.as_of.clone(),
+        ledger_balance: Amount::new(ledger_balance, scale),
+        reconciled_balance: Amount::new(reconciled_balance, scale),
+        statement_balance: Amount::new(query.statement_balance, scale),
+        difference: Amount::new(query.statement_balance - reconciled_balance, scale),
+        unreconciled_total: Amount::new(unreconciled_total, scale),
+        unreconciled_entries,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(reconciliation)))
+}
@@ -0,0 +1,501 @@
+use std::collections::{BTreeMap, HashMap};
+
+use actix_web::{web, HttpResponse, Result, Scope};
+use chrono::{Datelike, Days, Months, NaiveDate, SecondsFormat, TimeZone, Utc};
+use diesel::prelude::*;
+use futures_util::stream::{self, StreamExt};
+use rust_decimal::Decimal;
+
+use crate::cache::TrialBalanceCache;
+use crate::config::AppConfig;
+use crate::database::DbPool;
+use crate::errors::AppError;
+use crate::extractors::ReportRange;
+use crate::handlers::balance::{
+    check_entry_readable, load_balances, movement_start, signed_balance,
+};
+use crate::handlers::ledger::load_account_ledger;
+use crate::handlers::periods::{find_period, period_end_balances};
+use crate::models::{
+    Account, AccountBalance, AccountBalanceDelta, AccountContribution, AccountTypeSummary,
+    AccountTypeSummaryQuery, ApiResponse, BalancePoint, BalanceQuery, BalanceSeries,
+    BalanceSeriesQuery, BalanceSheetCompareQuery, BalanceSheetComparison, BalanceSheetSectionDelta,
+    CurrencyTotals, LedgerQuery, SeriesInterval, TransactionStatus, TrialBalance,
+    TrialBalanceQuery,
+};
+use crate::money::{self, Amount};
+use crate::schema::{accounts, entries, transactions};
+
+/// Most points one balance series may have.
+const MAX_SERIES_POINTS: usize = 366;
+
+const DEFAULT_SUMMARY_TOP: usize = 5;
+const MAX_SUMMARY_TOP: usize = 50;
+
+const ACCOUNT_TYPES: [&str; 5] = ["asset", "liability", "equity", "revenue", "expense"];
+const BALANCE_SHEET_TYPES: [&str; 3] = ["asset", "liability", "equity"];
+
+pub fn config() -> Scope {
+    web::scope("/reports")
+        .route("/trial-balance", web::get().to(get_trial_balance))
+        .route("/general-ledger", web::get().to(get_general_ledger))
+        .route("/balance-by-date", web::get().to(get_balance_by_date))
+        .route(
+            "/account-type-summary",
+            web::get().to(get_account_type_summary),
+        )
+        .route(
+            "/balance-sheet/compare",
+            web::get().to(get_balance_sheet_comparison),
+        )
+}
+
+/// Sums debits and credits per currency; amounts in different currencies are
+/// never added together. The ledger balances when every currency does.
+pub async fn get_trial_balance(
+    pool: web::Data<DbPool>,
+    cache: web::Data<TrialBalanceCache>,
+    config: web::Data<AppConfig>,
+    range: ReportRange,
+    query: web::Query<TrialBalanceQuery>,
+) -> Result<HttpResponse, AppError> {
+    let cache_key = format!(
+        "{}|{}|{}",
+        range.from_bound(),
+        range.to_bound(),
+        query.include_drafts
+    );
+
+    if let Some(trial_balance) = cache.get(&cache_key) {
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(trial_balance)));
+    }
+    // Taken before reading so a write that lands meanwhile keeps the result out
+    let generation = cache.generation();
+
+    let mut conn = pool.get()?;
+
+    let balance_query = BalanceQuery {
+        account_id: None,
+        account_type: None,
+        from_date: Some(range.from_bound()),
+        to_date: Some(range.to_bound()),
+        include_drafts: query.include_drafts,
+    };
+    let balances = load_balances(&mut conn, &balance_query)?;
+
+    // Memo accounts are listed with their totals but never count towards the equation
+    let mut sums: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();
+
+    let overflow =
+        || AppError::InternalServerError("balance overflow in trial balance totals".to_string());
+    for balance in balances.iter().filter(|b| !b.is_memo) {
+        let (debits, credits) = sums.entry(balance.currency.as_str()).or_default();
+        *debits = debits
+            .checked_add(balance.debit_total.value)
+            .ok_or_else(overflow)?;
+        *credits = credits
+            .checked_add(balance.credit_total.value)
+            .ok_or_else(overflow)?;
+    }
+
+    let totals: Vec<CurrencyTotals> = sums
+        .into_iter()
+        .map(|(currency, (total_debits, total_credits))| CurrencyTotals {
+            currency: currency.to_string(),
+            total_debits: Amount::new(total_debits, money::currency_scale(currency)),
+            total_credits: Amount::new(total_credits, money::currency_scale(currency)),
+            is_balanced: total_debits == total_credits,
+        })
+        .collect();
+    let (total_debits, total_credits) = match totals.as_slice() {
+        [] => (
+            Some(Amount::zero(config.amount_scale())),
+            Some(Amount::zero(config.amount_scale())),
+        ),
+        [only] => (Some(only.total_debits), Some(only.total_credits)),
+        _ => (None, None),
+    };
+
+    let trial_balance = TrialBalance {
+        is_balanced: totals.iter().all(|t| t.is_balanced),
+        accounts: balances,
+        total_debits,
+        total_credits,
+        totals,
+    };
+
+    cache.insert(cache_key, trial_balance.clone(), generation);
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(trial_balance)))
+}
+
+/// Streams the ledger of every account in code order, one account section at a time,
+/// so the full document is never buffered in memory.
+pub async fn get_general_ledger(
+    pool: web::Data<DbPool>,
+    range: ReportRange,
+    query: web::Query<LedgerQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut conn = pool.get()?;
+
+    let all_accounts: Vec<Account> = accounts::table
+        .order(accounts::code.asc())
+        .load(&mut conn)?;
+
+    let pool = pool.get_ref().clone();
+    let query = LedgerQuery {
+        from: Some(range.from_bound()),
+        to: Some(range.to_bound()),
+        include_drafts: query.include_drafts,
+    };
+
+    let sections =
+        stream::iter(all_accounts.into_iter().enumerate()).map(move |(index, account)| {
+            general_ledger_section(&pool, account, &query, index).map_err(actix_web::Error::from)
+        });
+
+    let body = stream::once(async {
+        Ok::<_, actix_web::Error>(web::Bytes::from_static(b"{\"success\":true,\"data\":["))
+    })
+    .chain(sections)
+    .chain(stream::once(async {
+        Ok(web::Bytes::from_static(
+            b"],\"message\":null,\"errors\":null}",
+        ))
+    }));
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .streaming(body))
+}
+
+fn general_ledger_section(
+    pool: &DbPool,
+    account: Account,
+    query: &LedgerQuery,
+    index: usize,
+) -> Result<web::Bytes, AppError> {
+    let mut conn = pool.get()?;
+    let ledger = load_account_ledger(&mut conn, account, query)?;
+
+    let mut chunk = Vec::new();
+    if index > 0 {
+        chunk.push(b',');
+    }
+    serde_json::to_writer(&mut chunk, &ledger)
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    Ok(web::Bytes::from(chunk))
+}
+
+/// The combined balance of every non-memo account of one type at the end of
+/// each interval between `from` and `to`. Each point is cumulative: opening
+/// balances plus every entry posted up to that point.
+pub async fn get_balance_by_date(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    range: ReportRange,
+    query: web::Query<BalanceSeriesQuery>,
+) -> Result<HttpResponse, AppError> {
+    let account_type = String::from(query.account_type.clone());
+    let point_dates = interval_ends(
+        range.from.date_naive(),
+        range.to.date_naive(),
+        query.interval,
+    )?;
+
+    let mut conn = pool.get()?;
+
+    let type_accounts: HashMap<String, Account> = accounts::table
+        .filter(accounts::account_type.eq(&account_type))
+        .filter(accounts::is_memo.eq(false))
+        .load::<Account>(&mut conn)?
+        .into_iter()
+        .map(|account| (account.id.clone(), account))
+        .collect();
+    let scale = report_scale(
+        &config,
+        ensure_single_currency(type_accounts.values().map(|a| a.currency.as_str()))?,
+    );
+
+    let mut entry_query = entries::table
+        .inner_join(transactions::table.on(transactions::id.eq(entries::transaction_id)))
+        .filter(entries::account_id.eq_any(type_accounts.keys()))
+        .filter(transactions::voided.eq(false))
+        .filter(transactions::posting_date.le(range.to_bound()))
+        .select((
+            entries::id,
+            entries::account_id,
+            transactions::posting_date,
+            entries::debit_amount,
+            entries::credit_amount,
+            entries::amount_flagged,
+        ))
+        .order((transactions::posting_date.asc(), entries::id.asc()))
+        .into_boxed();
+    if !query.include_drafts {
+        entry_query =
+            entry_query.filter(transactions::status.eq(TransactionStatus::Posted.as_str()));
+    }
+    let rows: Vec<(String, String, String, i64, i64, bool)> = entry_query.load(&mut conn)?;
+
+    let mut balance: Decimal = type_accounts
+        .values()
+        .map(|a| {
+            money::parse_stored(&a.opening_balance, || {
+                format!("opening balance of account {}", a.code)
+            })
+        })
+        .sum::<Result<Decimal, AppError>>()?;
+    let movement_starts: HashMap<&str, String> = type_accounts
+        .values()
+        .filter_map(|a| movement_start(a).map(|start| (a.id.as_str(), start)))
+        .collect();
+
+    let mut rows = rows.into_iter().peekable();
+    let mut points = Vec::with_capacity(point_dates.len());
+
+    for date in point_dates {
+        let bound = Utc
+            .from_utc_datetime(&date.and_hms_nano_opt(23, 59, 59, 999_999_999).unwrap())
+            .min(range.to)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true);
+
+        while let Some((entry_id, account_id, posting_date, debit, credit, amount_flagged)) =
+            rows.next_if(|(_, _, posting_date, _, _, _)| *posting_date <= bound)
+        {
+            // Entries up to an account's opening date are already in its opening balance
+            if movement_starts
+                .get(account_id.as_str())
+                .is_some_and(|start| posting_date < *start)
+            {
+                continue;
+            }
+            check_entry_readable(&entry_id, amount_flagged)?;
+            balance += signed_balance(
+                &type_accounts[&account_id],
+                money::from_storage_units(debit),
+                money::from_storage_units(credit),
+            )?;
+        }
+
+        points.push(BalancePoint {
+            date: date.format("%Y-%m-%d").to_string(),
+            balance: Amount::new(balance, scale),
+        });
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(BalanceSeries {
+        account_type,
+        interval: query.interval,
+        points,
+    })))
+}
+
+/// Each account type's total balance as of `as_of`, with its `top` largest
+/// accounts by absolute balance. Memo accounts are left out.
+pub async fn get_account_type_summary(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    query: web::Query<AccountTypeSummaryQuery>,
+) -> Result<HttpResponse, AppError> {
+    let top = query.top.unwrap_or(DEFAULT_SUMMARY_TOP);
+    if !(1..=MAX_SUMMARY_TOP).contains(&top) {
+        return Err(AppError::BadRequest(format!(
+            "top must be between 1 and {}",
+            MAX_SUMMARY_TOP
+        )));
+    }
+    let range = ReportRange::parse(None, query.as_of.as_deref())?;
+
+    let mut conn = pool.get()?;
+
+    let balance_query = BalanceQuery {
+        account_id: None,
+        account_type: None,
+        from_date: None,
+        to_date: Some(range.to_bound()),
+        include_drafts: query.include_drafts,
+    };
+    let balances = load_balances(&mut conn, &balance_query)?;
+    let scale = report_scale(
+        &config,
+        ensure_single_currency(
+            balances
+                .iter()
+                .filter(|b| !b.is_memo)
+                .map(|b| b.currency.as_str()),
+        )?,
+    );
+
+    let mut summaries = Vec::with_capacity(ACCOUNT_TYPES.len());
+    for account_type in ACCOUNT_TYPES {
+        let mut type_balances: Vec<&AccountBalance> = balances
+            .iter()
+            .filter(|b| b.account_type == account_type && !b.is_memo)
+            .collect();
+        type_balances.sort_by(|a, b| {
+            b.balance
+                .value
+                .abs()
+                .cmp(&a.balance.value.abs())
+                .then_with(|| a.account_code.cmp(&b.account_code))
+        });
+
+        summaries.push(AccountTypeSummary {
+            account_type: account_type.to_string(),
+            total_balance: Amount::new(type_balances.iter().map(|b| b.balance.value).sum(), scale),
+            account_count: type_balances.len(),
+            link: format!(
+                "{}/balance?account_type={}",
+                config.api_base_path, account_type
+            ),
+            top_accounts: type_balances
+                .into_iter()
+                .take(top)
+                .map(|b| AccountContribution {
+                    account_id: b.account_id.clone(),
+                    account_code: b.account_code.clone(),
+                    account_name: b.account_name.clone(),
+                    balance: b.balance,
+                    link: format!("{}/balance/{}", config.api_base_path, b.account_id),
+                })
+                .collect(),
+        });
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(summaries)))
+}
+
+/// Posted balances of the asset, liability and equity accounts at the end of
+/// two periods, per account and per section, with the change between them.
+pub async fn get_balance_sheet_comparison(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    query: web::Query<BalanceSheetCompareQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut conn = pool.get()?;
+
+    let period_a = find_period(&mut conn, &query.period_a)?;
+    let period_b = find_period(&mut conn, &query.period_b)?;
+    let balances_a = period_end_balances(&mut conn, &period_a)?;
+    let mut balances_b = period_end_balances(&mut conn, &period_b)?;
+    let scale = report_scale(
+        &config,
+        ensure_single_currency(
+            balances_a
+                .iter()
+                .chain(&balances_b)
+                .filter(|b| BALANCE_SHEET_TYPES.contains(&b.account_type.as_str()) && !b.is_memo)
+                .map(|b| b.currency.as_str()),
+        )?,
+    );
+
+    let balances_a: HashMap<String, Decimal> = balances_a
+        .into_iter()
+        .map(|b| (b.account_id, b.balance.value))
+        .collect();
+    balances_b.sort_by(|a, b| a.account_code.cmp(&b.account_code));
+
+    let sections = BALANCE_SHEET_TYPES
+        .iter()
+        .map(|account_type| {
+            let accounts: Vec<AccountBalanceDelta> = balances_b
+                .iter()
+                .filter(|b| b.account_type == *account_type && !b.is_memo)
+                .map(|b| {
+                    let balance_a = balances_a
+                        .get(&b.account_id)
+                        .copied()
+                        .unwrap_or(Decimal::ZERO);
+                    AccountBalanceDelta {
+                        account_id: b.account_id.clone(),
+                        account_code: b.account_code.clone(),
+                        account_name: b.account_name.clone(),
+                        balance_a: Amount::new(balance_a, scale),
+                        balance_b: b.balance,
+                        delta: Amount::new(b.balance.value - balance_a, scale),
+                    }
+                })
+                .collect();
+
+            let balance_a: Decimal = accounts.iter().map(|a| a.balance_a.value).sum();
+            let balance_b: Decimal = accounts.iter().map(|a| a.balance_b.value).sum();
+            BalanceSheetSectionDelta {
+                account_type: account_type.to_string(),
+                balance_a: Amount::new(balance_a, scale),
+                balance_b: Amount::new(balance_b, scale),
+                delta: Amount::new(balance_b - balance_a, scale),
+                accounts,
+            }
+        })
+        .collect();
+
+    Ok(
+        HttpResponse::Ok().json(ApiResponse::success(BalanceSheetComparison {
+            period_a,
+            period_b,
+            sections,
+        })),
+    )
+}
+
+/// Totals are only meaningful within one currency and no conversion exists,
+/// so a report adding balances together refuses accounts in several. Returns
+/// the one currency, if there were any accounts at all.
+fn ensure_single_currency<'a>(
+    currencies: impl IntoIterator<Item = &'a str>,
+) -> Result<Option<&'a str>, AppError> {
+    let mut currencies = currencies.into_iter();
+    match currencies.next() {
+        Some(first) if currencies.any(|c| c != first) => Err(AppError::BadRequest(
+            "mixed currencies; specify base".to_string(),
+        )),
+        first => Ok(first),
+    }
+}
+
+/// Decimal places for a summed report: its currency's, or the ledger
+/// currency's when no account took part.
+fn report_scale(config: &AppConfig, currency: Option<&str>) -> u32 {
+    currency.map_or_else(|| config.amount_scale(), money::currency_scale)
+}
+
+/// The last day of each interval from `start`, with the final one cut short at
+/// `end`. Weeks run seven days from `start`; months follow the calendar.
+fn interval_ends(
+    start: NaiveDate,
+    end: NaiveDate,
+    interval: SeriesInterval,
+) -> Result<Vec<NaiveDate>, AppError> {
+    let mut ends = Vec::new();
+    let mut period_start = start;
+
+    while period_start <= end {
+        if ends.len() == MAX_SERIES_POINTS {
+            return Err(AppError::BadRequest(format!(
+                "The range has more than {} intervals; narrow it or use a longer interval",
+                MAX_SERIES_POINTS
+            )));
+        }
+
+        let next_start = match interval {
+            SeriesInterval::Day => period_start.succ_opt(),
+            SeriesInterval::Week => period_start.checked_add_days(Days::new(7)),
+            SeriesInterval::Month => period_start
+                .with_day(1)
+                .and_then(|first| first.checked_add_months(Months::new(1))),
+        };
+        let period_end = next_start
+            .and_then(|next| next.pred_opt())
+            .map_or(end, |last| last.min(end));
+
+        ends.push(period_end);
+        match next_start {
+            Some(next) => period_start = next,
+            None => break,
+        }
+    }
+
+    Ok(ends)
+}
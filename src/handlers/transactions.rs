@@ -1,196 +1,1389 @@
+use std::collections::{BTreeMap, HashMap};
+
 use actix_web::{web, HttpResponse, Result, Scope};
-use chrono::Utc;
+use diesel::dsl::sql;
 use diesel::prelude::*;
+use diesel::sql_types::{Bool, Text};
+use diesel::sqlite::Sqlite;
+use log::warn;
 use rust_decimal::Decimal;
+use serde_json::json;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::audit;
+use crate::cache::TrialBalanceCache;
+use crate::clock::Clock;
+use crate::config::AppConfig;
 use crate::database::DbPool;
-use crate::errors::AppError;
+use crate::errors::{validation_messages, AppError};
+use crate::extractors::{Approval, IdempotencyKey, ReportRange};
+use crate::handlers::entries::entry_with_account;
+use crate::handlers::ledgers::check_transaction_quota;
+use crate::handlers::periods::{ensure_period_open, period_covering, warn_if_snapshotted};
+use crate::handlers::{directed, page_bounds, BoxedOrder};
+use crate::idempotency;
+use crate::ids;
 use crate::models::{
-    Account, ApiResponse, CreateTransactionRequest, Entry, EntryWithAccount, NewEntry,
-    NewTransaction, Transaction, TransactionWithEntries,
-};
-use crate::schema::{
-    accounts::{self, dsl::*},
-    entries::{self, dsl::*},
-    transactions::{self, dsl::*},
+    Account, AmountFormatQuery, ApiResponse, AuditLogEntry, BatchItemResult, BatchItemStatus,
+    BatchTransactionRequest, CreateEntryRequest, CreateTransactionQuery, CreateTransactionRequest,
+    Entry, EntryWithAccount, NewEntry, NewTransaction, ReplaceEntriesRequest, ResponseMeta,
+    SortOrder, Transaction, TransactionAuditTrail, TransactionSearchQuery, TransactionSortKey,
+    TransactionStatus, TransactionSummary, TransactionVerification, TransactionWithEntries,
+    TransactionsByAccountsRequest, TransactionsQuery, VerifiedAccount, DEFAULT_LEDGER_ID,
 };
+use crate::money::{self, Amount};
+use crate::schema::{accounts, audit_log, entries, idempotency_keys, transactions};
 
 pub fn config() -> Scope {
     web::scope("/transactions")
         .route("", web::post().to(create_transaction))
         .route("", web::get().to(get_all_transactions))
+        .route("/batch", web::post().to(create_transactions_batch))
+        .route("/by-accounts", web::post().to(get_transactions_by_accounts))
+        .route("/search", web::get().to(search_transactions))
         .route("/{id}", web::get().to(get_transaction))
         .route("/{id}", web::delete().to(delete_transaction))
+        .route("/{id}/verify", web::get().to(verify_transaction))
+        .route("/{id}/entries", web::put().to(replace_entries))
+        .route("/{id}/post", web::post().to(post_transaction))
+        .route("/{id}/void", web::post().to(void_transaction))
+        .route("/{id}/reverse", web::post().to(reverse_transaction))
+        .route(
+            "/{id}/audit-trail",
+            web::get().to(get_transaction_audit_trail),
+        )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_transaction(
     pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    approval: Approval,
+    idempotency_key: IdempotencyKey,
+    query: web::Query<CreateTransactionQuery>,
     transaction_data: web::Json<CreateTransactionRequest>,
 ) -> Result<HttpResponse, AppError> {
-    transaction_data
-        .validate()
-        .map_err(|e| AppError::ValidationError(format!("Validation failed: {:?}", e)))?;
+    let errors = collect_transaction_errors(&transaction_data);
+    if !errors.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::validation_errors(errors)));
+    }
+    check_amount_cap(&config, &transaction_data.entries, approval)?;
+
+    let mut conn = pool.get()?;
+
+    // A retry with the same key gets back what the first request created
+    let ledger_id = transaction_data
+        .ledger_id
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LEDGER_ID.to_string());
+    if let Some(ref key) = idempotency_key.0 {
+        let existing_id = idempotency::find(&mut conn, &config, clock.get_ref(), &ledger_id, key)?;
+
+        if let Some(existing_id) = existing_id {
+            let existing = get_transaction_with_entries_by_id(&mut conn, &existing_id)?;
+            return Ok(HttpResponse::Ok().json(ApiResponse::success(existing)));
+        }
+    }
+
+    // Reruns of an import find what they created last time; a transaction
+    // without a date is dated now and so can never match
+    if let (true, Some(transaction_date)) =
+        (query.skip_existing, &transaction_data.transaction_date)
+    {
+        let existing_id: Option<String> = transactions::table
+            .filter(transactions::reference.eq(&transaction_data.reference))
+            .filter(transactions::transaction_date.eq(transaction_date))
+            .select(transactions::id)
+            .first(&mut conn)
+            .optional()?;
+
+        if let Some(existing_id) = existing_id {
+            let existing = get_transaction_with_entries_by_id(&mut conn, &existing_id)?;
+            return Ok(HttpResponse::Ok().json(ApiResponse::success(existing)));
+        }
+    }
+
+    let transaction_id = conn.transaction::<_, AppError, _>(|conn| {
+        let transaction_id = insert_transaction(conn, &config, clock.get_ref(), &transaction_data)?;
+        if let Some(ref key) = idempotency_key.0 {
+            idempotency::record(conn, clock.get_ref(), &ledger_id, key, &transaction_id)?;
+        }
+        Ok(transaction_id)
+    })?;
+    cache.invalidate();
+
+    let created_transaction = get_transaction_with_entries_by_id(&mut conn, &transaction_id)?;
+
+    Ok(HttpResponse::Created()
+        .insert_header((
+            "Location",
+            format!(
+                "{}/transactions/{}",
+                config.api_base_path, created_transaction.id
+            ),
+        ))
+        .json(ApiResponse::success(created_transaction)))
+}
+
+/// Posts a batch of transactions. In atomic mode every item must succeed or nothing is written;
+/// otherwise each item is posted on its own and the per-item outcome is reported.
+pub async fn create_transactions_batch(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    approval: Approval,
+    batch_data: web::Json<BatchTransactionRequest>,
+) -> Result<HttpResponse, AppError> {
+    let batch_data = batch_data.into_inner();
+
+    if batch_data.transactions.is_empty() {
+        return Err(AppError::ValidationError(
+            "Batch must contain at least one transaction".to_string(),
+        ));
+    }
+
+    let mut results: Vec<BatchItemResult> = batch_data
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(index, item)| BatchItemResult {
+            index,
+            status: BatchItemStatus::Pending,
+            transaction_id: None,
+            errors: collect_transaction_errors(item),
+        })
+        .collect();
+
+    let mut conn = pool.get()?;
+
+    if batch_data.atomic {
+        let has_invalid = results.iter().any(|r| !r.errors.is_empty());
 
-    // Validate double entry - debits must equal credits
-    let mut total_debits = Decimal::ZERO;
-    let mut total_credits = Decimal::ZERO;
+        let outcome = if has_invalid {
+            Err(AppError::ValidationError("Batch rejected".to_string()))
+        } else {
+            conn.transaction::<_, AppError, _>(|conn| {
+                for (item, result) in batch_data.transactions.iter().zip(results.iter_mut()) {
+                    let inserted = check_amount_cap(&config, &item.entries, approval)
+                        .and_then(|()| insert_transaction(conn, &config, clock.get_ref(), item));
+                    match inserted {
+                        Ok(created_id) => result.transaction_id = Some(created_id),
+                        Err(e) => {
+                            result.errors.push(e.to_string());
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(())
+            })
+        };
 
-    for entry in &transaction_data.entries {
-        if let Some(debit) = entry.debit_amount {
-            total_debits += debit;
+        for result in results.iter_mut() {
+            result.status = if outcome.is_ok() {
+                BatchItemStatus::Created
+            } else if result.errors.is_empty() {
+                BatchItemStatus::Skipped
+            } else {
+                BatchItemStatus::Failed
+            };
+            if outcome.is_err() {
+                result.transaction_id = None;
+            }
         }
-        if let Some(credit) = entry.credit_amount {
-            total_credits += credit;
+
+        if outcome.is_err() {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse {
+                success: false,
+                data: Some(results),
+                message: Some("Batch rejected; no transactions were created".to_string()),
+                errors: None,
+                meta: None,
+            }));
         }
+
+        cache.invalidate();
+
+        return Ok(HttpResponse::Created().json(ApiResponse::success(results)));
+    }
+
+    for (item, result) in batch_data.transactions.iter().zip(results.iter_mut()) {
+        if !result.errors.is_empty() {
+            result.status = BatchItemStatus::Failed;
+            continue;
+        }
+
+        match conn.transaction::<_, AppError, _>(|conn| {
+            check_amount_cap(&config, &item.entries, approval)?;
+            insert_transaction(conn, &config, clock.get_ref(), item)
+        }) {
+            Ok(created_id) => {
+                result.status = BatchItemStatus::Created;
+                result.transaction_id = Some(created_id);
+            }
+            Err(e) => {
+                result.status = BatchItemStatus::Failed;
+                result.errors.push(e.to_string());
+            }
+        }
+    }
+
+    if results.iter().any(|r| r.status == BatchItemStatus::Created) {
+        cache.invalidate();
     }
 
+    Ok(HttpResponse::Ok().json(ApiResponse::success(results)))
+}
+
+/// Validates double entry - debits must equal credits
+fn check_double_entry(entries: &[CreateEntryRequest]) -> Result<(), AppError> {
+    let total_debits = checked_total(entries.iter().filter_map(|e| e.debit_amount))?;
+    let total_credits = checked_total(entries.iter().filter_map(|e| e.credit_amount))?;
+
     if total_debits != total_credits {
         return Err(AppError::ValidationError(
             "Total debits must equal total credits".to_string(),
         ));
     }
 
-    if transaction_data.entries.is_empty() {
+    if entries.is_empty() {
         return Err(AppError::ValidationError(
             "Transaction must have at least one entry".to_string(),
         ));
     }
 
-    let mut conn = pool.get()?;
+    Ok(())
+}
 
-    conn.transaction::<_, AppError, _>(|conn| {
-        let new_transaction_id = Uuid::new_v4().to_string();
-        let now = Utc::now().to_rfc3339();
-
-        let new_transaction = NewTransaction {
-            id: new_transaction_id.clone(),
-            reference: transaction_data.reference.clone(),
-            description: transaction_data.description.clone(),
-            transaction_date: transaction_data
-                .transaction_date
-                .clone()
-                .unwrap_or_else(|| now.clone()),
-            created_at: now.clone(),
-            updated_at: now.clone(),
-        };
+/// Rejects a transaction whose debits exceed `MAX_TRANSACTION_AMOUNT` unless the
+/// request carries an approval.
+fn check_amount_cap(
+    config: &AppConfig,
+    entries: &[CreateEntryRequest],
+    approval: Approval,
+) -> Result<(), AppError> {
+    let Some(cap) = config.max_transaction_amount else {
+        return Ok(());
+    };
 
-        diesel::insert_into(transactions::table)
-            .values(&new_transaction)
-            .execute(conn)?;
+    let total = checked_total(entries.iter().filter_map(|e| e.debit_amount))?;
+    if total > cap && !approval.0 {
+        return Err(AppError::Conflict(
+            "transaction exceeds limit; approval required".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Sums entry amounts, rejecting a total too large to represent.
+fn checked_total(amounts: impl IntoIterator<Item = Decimal>) -> Result<Decimal, AppError> {
+    amounts
+        .into_iter()
+        .try_fold(Decimal::ZERO, |total, amount| total.checked_add(amount))
+        .ok_or_else(|| {
+            AppError::ValidationError("Entry amounts are too large to total".to_string())
+        })
+}
+
+/// Runs every request-level rule and returns all failures instead of stopping at the first.
+/// Debits and credits are only compared once every entry is valid on its own.
+pub(crate) fn collect_transaction_errors(
+    transaction_data: &CreateTransactionRequest,
+) -> Vec<String> {
+    let mut errors = match transaction_data.validate() {
+        Ok(()) => Vec::new(),
+        Err(e) => validation_messages(&e),
+    };
+    let entry_errors = entry_errors(&transaction_data.entries);
+    let entries_valid = entry_errors.is_empty();
+    errors.extend(entry_errors);
+    if transaction_data
+        .metadata
+        .as_ref()
+        .is_some_and(|metadata| !metadata.is_object())
+    {
+        errors.push("metadata: must be a JSON object".to_string());
+    }
+
+    if entries_valid {
+        if let Err(AppError::ValidationError(msg)) = check_double_entry(&transaction_data.entries) {
+            errors.push(msg);
+        }
+    }
+
+    errors
+}
+
+/// Checks each entry on its own and names the failing field by its path, e.g.
+/// `entries[2].debit_amount: must not be negative`.
+fn entry_errors(entries: &[CreateEntryRequest]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let path = format!("entries[{}]", index);
+
+        if let Err(e) = entry.validate() {
+            errors.extend(
+                validation_messages(&e)
+                    .into_iter()
+                    .map(|msg| format!("{}.{}", path, msg)),
+            );
+        }
+        if entry.account_id.trim().is_empty() {
+            errors.push(format!("{}.account_id: must not be empty", path));
+        }
+        if entry
+            .currency
+            .as_ref()
+            .is_some_and(|currency| !money::is_currency_code(currency))
+        {
+            errors.push(format!(
+                "{}.currency: must be a three-letter ISO 4217 code",
+                path
+            ));
+        }
+        for (field, amount) in [
+            ("debit_amount", entry.debit_amount),
+            ("credit_amount", entry.credit_amount),
+        ] {
+            if amount.is_some_and(|a| a < Decimal::ZERO) {
+                errors.push(format!("{}.{}: must not be negative", path, field));
+            }
+            if let Some(Err(AppError::ValidationError(msg))) = amount.map(money::to_storage_units) {
+                errors.push(format!("{}.{}: {}", path, field, msg));
+            }
+        }
+    }
 
-        // Create entries
-        for entry_data in &transaction_data.entries {
-            let entry_id = Uuid::new_v4().to_string();
+    errors
+}
+
+/// Inserts a validated transaction and its entries, returning the new transaction id.
+/// Must be called inside a database transaction.
+pub(crate) fn insert_transaction(
+    conn: &mut diesel::SqliteConnection,
+    config: &AppConfig,
+    clock: &dyn Clock,
+    transaction_data: &CreateTransactionRequest,
+) -> Result<String, AppError> {
+    let ledger_id = transaction_data
+        .ledger_id
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LEDGER_ID.to_string());
+
+    check_entry_accounts(conn, config, &ledger_id, &transaction_data.entries)?;
+    let currencies = entry_currencies(conn, &transaction_data.entries)?;
+    check_currency_balance(&transaction_data.entries, &currencies)?;
+    check_transaction_quota(conn, &ledger_id)?;
+
+    let new_transaction_id = ids::new_id(conn, config, ids::TRANSACTION_PREFIX)?;
+    let now = clock.now_rfc3339();
+    let transaction_date = transaction_data
+        .transaction_date
+        .clone()
+        .unwrap_or_else(|| now.clone());
+    // Period locks and per-period references follow the posting date, not the
+    // document date
+    let posting_date = transaction_data
+        .posting_date
+        .clone()
+        .unwrap_or_else(|| transaction_date.clone());
+
+    let period_id =
+        check_reference_available(conn, config, &transaction_data.reference, &posting_date)?;
+    ensure_period_open(conn, &posting_date)?;
+    warn_if_snapshotted(conn, &posting_date)?;
 
-            let new_entry = NewEntry {
-                id: entry_id,
-                transaction_id: new_transaction_id.clone(),
+    let new_transaction = NewTransaction {
+        id: new_transaction_id.clone(),
+        reference: transaction_data.reference.clone(),
+        description: transaction_data.description.clone(),
+        transaction_date,
+        created_at: now.clone(),
+        updated_at: now.clone(),
+        ledger_id,
+        reverses_transaction_id: None,
+        status: transaction_data
+            .status
+            .unwrap_or(TransactionStatus::Posted)
+            .as_str()
+            .to_string(),
+        period_id,
+        posting_date,
+        metadata: transaction_data
+            .metadata
+            .as_ref()
+            .map(|metadata| metadata.to_string()),
+    };
+
+    diesel::insert_into(transactions::table)
+        .values(&new_transaction)
+        .execute(conn)?;
+
+    // Entries carry the transaction's own timestamp so ordering by created_at
+    // never splits a transaction
+    insert_entries(
+        conn,
+        &new_transaction_id,
+        &transaction_data.entries,
+        &currencies,
+        &new_transaction.created_at,
+    )?;
+
+    audit::record(
+        conn,
+        clock,
+        audit::ENTITY_TRANSACTION,
+        &new_transaction_id,
+        audit::ACTION_CREATED,
+        Some(json!({ "reference": new_transaction.reference })),
+    )?;
+
+    Ok(new_transaction_id)
+}
+
+/// Checks that every entry posts to an existing, active account of the given
+/// ledger and, in leaf-only mode, that none of them posts to a parent account.
+fn check_entry_accounts(
+    conn: &mut diesel::SqliteConnection,
+    config: &AppConfig,
+    ledger_id: &str,
+    entries: &[CreateEntryRequest],
+) -> Result<(), AppError> {
+    let entry_account_ids: Vec<&String> = entries.iter().map(|entry| &entry.account_id).collect();
+
+    // SQLite does not enforce the foreign key, so check it here
+    let active_ids: Vec<String> = accounts::table
+        .filter(accounts::id.eq_any(&entry_account_ids))
+        .filter(accounts::is_active.eq(true))
+        .select(accounts::id)
+        .load(conn)?;
+    if let Some(unknown) = entry_account_ids.iter().find(|id| !active_ids.contains(id)) {
+        return Err(AppError::BadRequest(format!(
+            "Unknown or inactive account: {}",
+            unknown
+        )));
+    }
+
+    // Every entry must post to an account of the transaction's own ledger
+    let out_of_scope: i64 = accounts::table
+        .filter(accounts::id.eq_any(&entry_account_ids))
+        .filter(accounts::ledger_id.ne(ledger_id))
+        .count()
+        .get_result(conn)?;
+
+    if out_of_scope > 0 {
+        return Err(AppError::BadRequest("account not in ledger".to_string()));
+    }
+
+    let locked: i64 = accounts::table
+        .filter(accounts::id.eq_any(&entry_account_ids))
+        .filter(accounts::postable.eq(false))
+        .count()
+        .get_result(conn)?;
+
+    if locked > 0 {
+        return Err(AppError::Conflict(
+            "account is locked for posting".to_string(),
+        ));
+    }
+
+    if config.post_leaf_only {
+        // Posting to a parent as well as its children would double count on rollups
+        let parent_account: Option<String> = accounts::table
+            .filter(accounts::parent_id.eq_any(&entry_account_ids))
+            .select(accounts::parent_id.assume_not_null())
+            .first(conn)
+            .optional()?;
+
+        if let Some(parent_id) = parent_account {
+            return Err(AppError::BadRequest(format!(
+                "Account {} has child accounts; post to a leaf account instead",
+                parent_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves the currency of each entry, in order: the one given on the entry,
+/// which must match its account's, or else the account's own.
+fn entry_currencies(
+    conn: &mut diesel::SqliteConnection,
+    entries: &[CreateEntryRequest],
+) -> Result<Vec<String>, AppError> {
+    let entry_account_ids: Vec<&String> = entries.iter().map(|entry| &entry.account_id).collect();
+    let account_currencies: HashMap<String, (String, String)> = accounts::table
+        .filter(accounts::id.eq_any(&entry_account_ids))
+        .select((accounts::id, accounts::code, accounts::currency))
+        .load::<(String, String, String)>(conn)?
+        .into_iter()
+        .map(|(id, code, currency)| (id, (code, currency)))
+        .collect();
+
+    entries
+        .iter()
+        .map(|entry| {
+            let (code, account_currency) =
+                account_currencies.get(&entry.account_id).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "Unknown or inactive account: {}",
+                        entry.account_id
+                    ))
+                })?;
+            match entry.currency {
+                Some(ref currency) if !currency.eq_ignore_ascii_case(account_currency) => {
+                    Err(AppError::BadRequest(format!(
+                        "Entry currency {} does not match account {} currency {}",
+                        currency.to_ascii_uppercase(),
+                        code,
+                        account_currency
+                    )))
+                }
+                _ => Ok(account_currency.clone()),
+            }
+        })
+        .collect()
+}
+
+/// Debits must equal credits within each currency, not just across the whole
+/// transaction, and no amount may be finer than its currency's minor units.
+fn check_currency_balance(
+    entries: &[CreateEntryRequest],
+    currencies: &[String],
+) -> Result<(), AppError> {
+    for (index, (entry, currency)) in entries.iter().zip(currencies).enumerate() {
+        for (field, amount) in [
+            ("debit_amount", entry.debit_amount),
+            ("credit_amount", entry.credit_amount),
+        ] {
+            if let Some(amount) = amount {
+                money::check_minor_units(amount, currency).map_err(|e| match e {
+                    AppError::ValidationError(message) => AppError::ValidationError(format!(
+                        "entries[{}].{}: {}",
+                        index, field, message
+                    )),
+                    other => other,
+                })?;
+            }
+        }
+    }
+
+    let mut totals: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();
+    for (entry, currency) in entries.iter().zip(currencies) {
+        let total = totals.entry(currency).or_default();
+        total.0 += entry.debit_amount.unwrap_or(Decimal::ZERO);
+        total.1 += entry.credit_amount.unwrap_or(Decimal::ZERO);
+    }
+
+    let unbalanced: Vec<&str> = totals
+        .iter()
+        .filter(|(_, (debits, credits))| debits != credits)
+        .map(|(currency, _)| *currency)
+        .collect();
+    if !unbalanced.is_empty() {
+        return Err(AppError::ValidationError(format!(
+            "Total debits must equal total credits in each currency; unbalanced: {}",
+            unbalanced.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Inserts every entry of a transaction in one statement, all stamped with the
+/// single `created_at` captured by the caller.
+fn insert_entries(
+    conn: &mut diesel::SqliteConnection,
+    transaction_id: &str,
+    entries: &[CreateEntryRequest],
+    currencies: &[String],
+    created_at: &str,
+) -> Result<(), AppError> {
+    let new_entries: Vec<NewEntry> = entries
+        .iter()
+        .zip(currencies)
+        .map(|(entry_data, currency)| {
+            Ok(NewEntry {
+                id: Uuid::new_v4().to_string(),
+                transaction_id: transaction_id.to_string(),
                 account_id: entry_data.account_id.clone(),
-                debit_amount: entry_data.debit_amount.unwrap_or(Decimal::ZERO).to_string(),
-                credit_amount: entry_data
-                    .credit_amount
-                    .unwrap_or(Decimal::ZERO)
-                    .to_string(),
+                debit_amount: money::to_storage_units(
+                    entry_data.debit_amount.unwrap_or(Decimal::ZERO),
+                )?,
+                credit_amount: money::to_storage_units(
+                    entry_data.credit_amount.unwrap_or(Decimal::ZERO),
+                )?,
                 description: entry_data.description.clone(),
-                created_at: now.clone(),
-            };
+                created_at: created_at.to_string(),
+                currency: currency.clone(),
+            })
+        })
+        .collect::<Result<_, AppError>>()?;
 
-            diesel::insert_into(entries::table)
-                .values(&new_entry)
-                .execute(conn)?;
-        }
+    diesel::insert_into(entries::table)
+        .values(&new_entries)
+        .execute(conn)?;
 
-        let transaction: Transaction = transactions::table.find(&new_transaction_id).first(conn)?;
+    Ok(())
+}
 
-        Ok(transaction)
+/// Rejects a reference already used in its uniqueness scope and returns the
+/// period to record it under (`None` when references are global).
+fn check_reference_available(
+    conn: &mut diesel::SqliteConnection,
+    config: &AppConfig,
+    reference: &str,
+    posting_date: &str,
+) -> Result<Option<String>, AppError> {
+    if !config.references_unique_per_period() {
+        let taken: i64 = transactions::table
+            .filter(transactions::reference.eq(reference))
+            .filter(transactions::period_id.is_null())
+            .count()
+            .get_result(conn)?;
+        if taken > 0 {
+            return Err(AppError::Conflict(format!(
+                "Reference {} already exists",
+                reference
+            )));
+        }
+        return Ok(None);
+    }
+
+    let period = period_covering(conn, posting_date)?.ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "No period covers posting date {}; references are unique per period",
+            posting_date
+        ))
     })?;
 
-    let created_transaction = get_transaction_with_entries(&mut conn, &transaction_data.reference)?;
+    let taken: i64 = transactions::table
+        .filter(transactions::reference.eq(reference))
+        .filter(transactions::period_id.eq(&period.id))
+        .count()
+        .get_result(conn)?;
+    if taken > 0 {
+        return Err(AppError::Conflict(format!(
+            "Reference {} already exists in period {}",
+            reference, period.name
+        )));
+    }
 
-    Ok(HttpResponse::Created().json(ApiResponse::success(created_transaction)))
+    Ok(Some(period.id))
 }
 
-pub async fn get_all_transactions(pool: web::Data<DbPool>) -> Result<HttpResponse, AppError> {
+/// Lists transactions newest first. With `limit` or `offset` one page is
+/// returned with the total in `meta`; otherwise the list is capped at
+/// `max_list_results` rows and a truncated list is flagged in `meta` and
+/// logged. `metadata.<key>=<value>` parameters keep only transactions whose
+/// metadata has that exact value.
+pub async fn get_all_transactions(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    query: web::Query<TransactionsQuery>,
+    params: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
     let mut conn = pool.get()?;
+    let cap = config.max_list_results;
+
+    let filtered = || filter_by_metadata(transactions::table.into_boxed(), &params);
+    let transactions_query = match query.sort_by {
+        Some(sort_by) => filtered()?
+            .order(transaction_order(sort_by, query.order))
+            .then_order_by(transactions::id.asc()),
+        None => filtered()?.order(transactions::created_at.desc()),
+    };
+
+    if query.limit.is_some() || query.offset.is_some() {
+        let (limit, offset) = page_bounds(query.limit, query.offset)?;
+        let total: i64 = filtered()?.count().get_result(&mut conn)?;
+        let page: Vec<Transaction> = transactions_query
+            .limit(limit)
+            .offset(offset)
+            .load(&mut conn)?;
+
+        return Ok(HttpResponse::Ok().json(ApiResponse::success_with_meta(
+            summarize_transactions(&mut conn, page)?,
+            ResponseMeta {
+                total,
+                limit: Some(limit),
+                offset: Some(offset),
+                truncated: false,
+            },
+        )));
+    }
+
+    // Fetch one extra row to learn whether the cap cut anything off
+    let mut results: Vec<Transaction> = transactions_query.limit(cap + 1).load(&mut conn)?;
+
+    let truncated = results.len() as i64 > cap;
+    results.truncate(cap as usize);
+
+    let summaries = summarize_transactions(&mut conn, results)?;
 
-    let results: Vec<Transaction> = transactions::table
-        .order(transactions::created_at.desc())
+    if !truncated {
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(summaries)));
+    }
+
+    let total: i64 = filtered()?.count().get_result(&mut conn)?;
+    warn!(
+        "Transaction list truncated to {} of {} rows; use paging or narrower queries",
+        cap, total
+    );
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_with_meta(
+        summaries,
+        ResponseMeta {
+            total,
+            limit: Some(cap),
+            offset: None,
+            truncated: true,
+        },
+    )))
+}
+
+/// Narrows `query` to transactions whose metadata matches every
+/// `metadata.<key>` filter. Numbers match their decimal text.
+fn filter_by_metadata<'a>(
+    mut query: transactions::BoxedQuery<'a, Sqlite>,
+    params: &HashMap<String, String>,
+) -> Result<transactions::BoxedQuery<'a, Sqlite>, AppError> {
+    let metadata_filters = params
+        .iter()
+        .filter_map(|(param, value)| param.strip_prefix("metadata.").map(|key| (key, value)));
+    for (key, value) in metadata_filters {
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(AppError::BadRequest(format!(
+                "metadata filter key '{}' may only contain letters, digits, '_' and '-'",
+                key
+            )));
+        }
+        query = query.filter(
+            sql::<Bool>("CAST(json_extract(metadata, ")
+                .bind::<Text, _>(format!("$.\"{}\"", key))
+                .sql(") AS TEXT) = ")
+                .bind::<Text, _>(value.clone()),
+        );
+    }
+    Ok(query)
+}
+
+/// Lists the transactions touching any of the given accounts in date order.
+/// A transaction with entries against several of them is returned once.
+pub async fn get_transactions_by_accounts(
+    pool: web::Data<DbPool>,
+    request: web::Json<TransactionsByAccountsRequest>,
+) -> Result<HttpResponse, AppError> {
+    request
+        .validate()
+        .map_err(|e| AppError::ValidationError(validation_messages(&e).join("; ")))?;
+    let (limit, offset) = page_bounds(request.limit, request.offset)?;
+    let range = ReportRange::parse(request.from.as_deref(), request.to.as_deref())?;
+
+    let mut conn = pool.get()?;
+
+    let mut count_query = transactions::table
+        .inner_join(entries::table.on(entries::transaction_id.eq(transactions::id)))
+        .filter(entries::account_id.eq_any(&request.account_ids))
+        .select(diesel::dsl::count_distinct(transactions::id))
+        .into_boxed();
+    let mut transaction_query = transactions::table
+        .inner_join(entries::table.on(entries::transaction_id.eq(transactions::id)))
+        .filter(entries::account_id.eq_any(&request.account_ids))
+        .select(transactions::all_columns)
+        .distinct()
+        .into_boxed();
+
+    if request.from.is_some() {
+        count_query = count_query.filter(transactions::transaction_date.ge(range.from_bound()));
+        transaction_query =
+            transaction_query.filter(transactions::transaction_date.ge(range.from_bound()));
+    }
+    if request.to.is_some() {
+        count_query = count_query.filter(transactions::transaction_date.le(range.to_bound()));
+        transaction_query =
+            transaction_query.filter(transactions::transaction_date.le(range.to_bound()));
+    }
+
+    let total: i64 = count_query.get_result(&mut conn)?;
+
+    let results: Vec<Transaction> = transaction_query
+        .order((
+            transactions::transaction_date.asc(),
+            transactions::created_at.asc(),
+            transactions::id.asc(),
+        ))
+        .limit(limit)
+        .offset(offset)
         .load(&mut conn)?;
 
-    Ok(HttpResponse::Ok().json(ApiResponse::success(results)))
+    let summaries = summarize_transactions(&mut conn, results)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_with_meta(
+        summaries,
+        ResponseMeta {
+            total,
+            limit: Some(limit),
+            offset: Some(offset),
+            truncated: false,
+        },
+    )))
+}
+
+/// Lists transactions whose reference or description contains `q`, newest
+/// first. Matching ignores ASCII case; `%` and `_` in `q` match literally.
+pub async fn search_transactions(
+    pool: web::Data<DbPool>,
+    query: web::Query<TransactionSearchQuery>,
+) -> Result<HttpResponse, AppError> {
+    let needle = query.q.trim();
+    if needle.is_empty() {
+        return Err(AppError::BadRequest("q must not be empty".to_string()));
+    }
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+    let range = ReportRange::parse(query.from.as_deref(), query.to.as_deref())?;
+    let mut conn = pool.get()?;
+
+    let pattern = format!(
+        "%{}%",
+        needle
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+    let matching = || {
+        let mut matching = transactions::table
+            .filter(
+                transactions::reference
+                    .like(pattern.clone())
+                    .escape('\\')
+                    .or(transactions::description.like(pattern.clone()).escape('\\')),
+            )
+            .into_boxed();
+        if query.from.is_some() {
+            matching = matching.filter(transactions::transaction_date.ge(range.from_bound()));
+        }
+        if query.to.is_some() {
+            matching = matching.filter(transactions::transaction_date.le(range.to_bound()));
+        }
+        matching
+    };
+
+    let total: i64 = matching().count().get_result(&mut conn)?;
+    let page: Vec<Transaction> = matching()
+        .order((transactions::created_at.desc(), transactions::id.asc()))
+        .limit(limit)
+        .offset(offset)
+        .load(&mut conn)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_with_meta(
+        summarize_transactions(&mut conn, page)?,
+        ResponseMeta {
+            total,
+            limit: Some(limit),
+            offset: Some(offset),
+            truncated: false,
+        },
+    )))
+}
+
+fn transaction_order(
+    sort_by: TransactionSortKey,
+    order: SortOrder,
+) -> BoxedOrder<transactions::table> {
+    match sort_by {
+        TransactionSortKey::Reference => directed(transactions::reference, order),
+        TransactionSortKey::TransactionDate => directed(transactions::transaction_date, order),
+        TransactionSortKey::CreatedAt => directed(transactions::created_at, order),
+        TransactionSortKey::UpdatedAt => directed(transactions::updated_at, order),
+    }
+}
+
+/// Attaches entry counts and debit totals using one query over the entries
+/// of every listed transaction rather than one query per row. A total renders
+/// at the finest scale among its entries' currencies.
+fn summarize_transactions(
+    conn: &mut diesel::SqliteConnection,
+    transaction_rows: Vec<Transaction>,
+) -> Result<Vec<TransactionSummary>, AppError> {
+    let ids: Vec<&String> = transaction_rows.iter().map(|t| &t.id).collect();
+
+    let amounts: Vec<(String, i64, String)> = entries::table
+        .filter(entries::transaction_id.eq_any(&ids))
+        .select((
+            entries::transaction_id,
+            entries::debit_amount,
+            entries::currency,
+        ))
+        .load(conn)?;
+
+    let mut totals: HashMap<String, (i64, Amount)> = HashMap::new();
+    for (transaction_id, debit_amount, currency) in amounts {
+        let (count, total) = totals.entry(transaction_id).or_insert((0, Amount::zero(0)));
+        *count += 1;
+        total.value += money::from_storage_units(debit_amount);
+        total.scale = total.scale.max(money::currency_scale(&currency));
+    }
+
+    Ok(transaction_rows
+        .into_iter()
+        .map(|transaction| {
+            let (entry_count, total_amount) = totals
+                .remove(&transaction.id)
+                .unwrap_or((0, Amount::zero(0)));
+            TransactionSummary {
+                transaction,
+                entry_count,
+                total_amount,
+            }
+        })
+        .collect())
 }
 
 pub async fn get_transaction(
     pool: web::Data<DbPool>,
     path: web::Path<String>,
+    query: web::Query<AmountFormatQuery>,
 ) -> Result<HttpResponse, AppError> {
     let trans_id = path.into_inner();
     let mut conn = pool.get()?;
 
-    let transaction = get_transaction_with_entries_by_id(&mut conn, &trans_id)?;
+    let transaction = get_transaction_with_entries_by_id(&mut conn, &trans_id)?
+        .with_amount_format(query.amount_format);
 
     Ok(HttpResponse::Ok().json(ApiResponse::success(transaction)))
 }
 
-pub async fn delete_transaction(
+/// Recomputes the totals of a single transaction and checks that its accounts still exist and are active.
+pub async fn verify_transaction(
     pool: web::Data<DbPool>,
     path: web::Path<String>,
 ) -> Result<HttpResponse, AppError> {
     let trans_id = path.into_inner();
     let mut conn = pool.get()?;
 
-    let deleted_rows = diesel::delete(transactions::table.filter(transactions::id.eq(&trans_id)))
-        .execute(&mut conn)?;
+    let transaction: Transaction = transactions::table.find(&trans_id).first(&mut conn)?;
 
-    if deleted_rows == 0 {
-        return Err(AppError::NotFound("Transaction not found".to_string()));
-    }
+    let transaction_entries: Vec<Entry> = entries::table
+        .filter(entries::transaction_id.eq(&transaction.id))
+        .load(&mut conn)?;
 
-    Ok(HttpResponse::NoContent().json(ApiResponse::success("Transaction deleted successfully")))
-}
+    let mut debit_total = Decimal::ZERO;
+    let mut credit_total = Decimal::ZERO;
+    let mut scale = 0;
+    let mut account_ids: Vec<String> = Vec::new();
 
-fn get_transaction_with_entries(
-    conn: &mut diesel::SqliteConnection,
-    ref_id: &str,
-) -> Result<TransactionWithEntries, AppError> {
-    let transaction: Transaction = transactions::table
-        .filter(transactions::reference.eq(ref_id))
-        .first(conn)?;
+    for entry in &transaction_entries {
+        debit_total += money::from_storage_units(entry.debit_amount);
+        credit_total += money::from_storage_units(entry.credit_amount);
+        scale = scale.max(money::currency_scale(&entry.currency));
+        if !account_ids.contains(&entry.account_id) {
+            account_ids.push(entry.account_id.clone());
+        }
+    }
 
-    let transaction_entries: Vec<(Entry, Account)> = entries::table
-        .inner_join(accounts::table.on(accounts::id.eq(entries::account_id)))
-        .filter(entries::transaction_id.eq(&transaction.id))
-        .load(conn)?;
+    let referenced_accounts: Vec<Account> = accounts::table
+        .filter(accounts::id.eq_any(&account_ids))
+        .load(&mut conn)?;
 
-    let entries_with_accounts: Vec<EntryWithAccount> = transaction_entries
+    let verified_accounts: Vec<VerifiedAccount> = account_ids
         .into_iter()
-        .map(|(entry, account)| EntryWithAccount {
-            id: entry.id,
-            transaction_id: entry.transaction_id,
-            account_id: entry.account_id,
-            account_code: account.code,
-            account_name: account.name,
-            debit_amount: entry.debit_amount.parse().unwrap_or(Decimal::ZERO),
-            credit_amount: entry.credit_amount.parse().unwrap_or(Decimal::ZERO),
-            description: entry.description,
-            created_at: entry.created_at,
+        .map(|account_id| {
+            let account = referenced_accounts.iter().find(|a| a.id == account_id);
+            VerifiedAccount {
+                exists: account.is_some(),
+                is_active: account.map(|a| a.is_active).unwrap_or(false),
+                account_id,
+            }
         })
         .collect();
 
-    Ok(TransactionWithEntries {
-        id: transaction.id,
-        reference: transaction.reference,
-        description: transaction.description,
-        transaction_date: transaction.transaction_date,
-        created_at: transaction.created_at,
-        updated_at: transaction.updated_at,
-        entries: entries_with_accounts,
-    })
+    let verification = TransactionVerification {
+        transaction_id: transaction.id,
+        entry_count: transaction_entries.len(),
+        debit_total: Amount::new(debit_total, scale),
+        credit_total: Amount::new(credit_total, scale),
+        is_balanced: debit_total == credit_total,
+        accounts_valid: verified_accounts.iter().all(|a| a.exists && a.is_active),
+        accounts: verified_accounts,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(verification)))
+}
+
+/// Stitches together the reversal links of a transaction and its audit log rows.
+pub async fn get_transaction_audit_trail(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let trans_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let transaction: Transaction = transactions::table.find(&trans_id).first(&mut conn)?;
+
+    let reverses: Option<Transaction> = match transaction.reverses_transaction_id {
+        Some(ref original_id) => transactions::table
+            .find(original_id)
+            .first(&mut conn)
+            .optional()?,
+        None => None,
+    };
+
+    let reversed_by: Vec<Transaction> = transactions::table
+        .filter(transactions::reverses_transaction_id.eq(&trans_id))
+        .order(transactions::created_at.asc())
+        .load(&mut conn)?;
+
+    let audit_entries: Vec<AuditLogEntry> = audit_log::table
+        .filter(audit_log::entity_type.eq(audit::ENTITY_TRANSACTION))
+        .filter(audit_log::entity_id.eq(&trans_id))
+        .order(audit_log::created_at.asc())
+        .load(&mut conn)?;
+
+    let trail = TransactionAuditTrail {
+        transaction,
+        reverses,
+        reversed_by,
+        audit_entries,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(trail)))
+}
+
+/// Moves a draft transaction to `posted` so its entries start counting in balances.
+pub async fn post_transaction(
+    pool: web::Data<DbPool>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let trans_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let transaction: Transaction = transactions::table.find(&trans_id).first(conn)?;
+
+        if transaction.status != TransactionStatus::Draft.as_str() {
+            return Err(AppError::Conflict(
+                "Only draft transactions can be posted".to_string(),
+            ));
+        }
+        ensure_period_open(conn, &transaction.posting_date)?;
+        warn_if_snapshotted(conn, &transaction.posting_date)?;
+
+        diesel::update(transactions::table.find(&trans_id))
+            .set((
+                transactions::status.eq(TransactionStatus::Posted.as_str()),
+                transactions::updated_at.eq(clock.now_rfc3339()),
+            ))
+            .execute(conn)?;
+
+        audit::record(
+            conn,
+            clock.get_ref(),
+            audit::ENTITY_TRANSACTION,
+            &trans_id,
+            audit::ACTION_POSTED,
+            None,
+        )
+    })?;
+
+    cache.invalidate();
+
+    let transaction = get_transaction_with_entries_by_id(&mut conn, &trans_id)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(transaction)))
+}
+
+/// Replaces every entry of a draft transaction in one database transaction.
+/// Posted transactions are immutable and must be voided or reversed instead.
+pub async fn replace_entries(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+    entries_data: web::Json<ReplaceEntriesRequest>,
+) -> Result<HttpResponse, AppError> {
+    let trans_id = path.into_inner();
+
+    let errors = entry_errors(&entries_data.entries);
+    if !errors.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::validation_errors(errors)));
+    }
+    check_double_entry(&entries_data.entries)?;
+
+    let mut conn = pool.get()?;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let transaction: Transaction = transactions::table.find(&trans_id).first(conn)?;
+
+        if transaction.status != TransactionStatus::Draft.as_str() {
+            return Err(AppError::Conflict(
+                "Only draft transactions can have their entries replaced".to_string(),
+            ));
+        }
+        ensure_period_open(conn, &transaction.posting_date)?;
+
+        check_entry_accounts(conn, &config, &transaction.ledger_id, &entries_data.entries)?;
+        let currencies = entry_currencies(conn, &entries_data.entries)?;
+        check_currency_balance(&entries_data.entries, &currencies)?;
+
+        diesel::delete(entries::table.filter(entries::transaction_id.eq(&trans_id)))
+            .execute(conn)?;
+
+        let now = clock.now_rfc3339();
+        insert_entries(conn, &trans_id, &entries_data.entries, &currencies, &now)?;
+
+        diesel::update(transactions::table.find(&trans_id))
+            .set(transactions::updated_at.eq(&now))
+            .execute(conn)?;
+
+        audit::record(
+            conn,
+            clock.get_ref(),
+            audit::ENTITY_TRANSACTION,
+            &trans_id,
+            audit::ACTION_UPDATED,
+            Some(json!({ "entries_replaced": entries_data.entries.len() })),
+        )
+    })?;
+
+    // Drafts count towards balances requested with include_drafts
+    cache.invalidate();
+
+    let transaction = get_transaction_with_entries_by_id(&mut conn, &trans_id)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(transaction)))
+}
+
+/// Marks a transaction as void so it drops out of balances and reports while
+/// staying on record. Transactions in a closed period cannot be voided.
+pub async fn void_transaction(
+    pool: web::Data<DbPool>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let trans_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let transaction: Transaction = transactions::table.find(&trans_id).first(conn)?;
+
+        if transaction.voided {
+            return Err(AppError::Conflict(
+                "Transaction is already void".to_string(),
+            ));
+        }
+
+        ensure_period_open(conn, &transaction.posting_date)?;
+        warn_if_snapshotted(conn, &transaction.posting_date)?;
+
+        diesel::update(transactions::table.find(&trans_id))
+            .set((
+                transactions::voided.eq(true),
+                transactions::updated_at.eq(clock.now_rfc3339()),
+            ))
+            .execute(conn)?;
+
+        audit::record(
+            conn,
+            clock.get_ref(),
+            audit::ENTITY_TRANSACTION,
+            &trans_id,
+            audit::ACTION_VOIDED,
+            None,
+        )
+    })?;
+
+    cache.invalidate();
+
+    let transaction = get_transaction_with_entries_by_id(&mut conn, &trans_id)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(transaction)))
 }
 
-fn get_transaction_with_entries_by_id(
+/// Posts a new transaction that swaps the debits and credits of a posted one,
+/// linked back to it through `reverses_transaction_id`. Unlike voiding, both
+/// stay in the books, dated when each happened.
+pub async fn reverse_transaction(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let trans_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    let reversal_id = conn.transaction::<_, AppError, _>(|conn| {
+        let original: Transaction = transactions::table.find(&trans_id).first(conn)?;
+
+        if original.status != TransactionStatus::Posted.as_str() || original.voided {
+            return Err(AppError::Conflict(
+                "Only posted transactions that are not void can be reversed".to_string(),
+            ));
+        }
+        let reversed_by: Option<String> = transactions::table
+            .filter(transactions::reverses_transaction_id.eq(&trans_id))
+            .select(transactions::id)
+            .first(conn)
+            .optional()?;
+        if let Some(reversed_by) = reversed_by {
+            return Err(AppError::Conflict(format!(
+                "Transaction is already reversed by {}",
+                reversed_by
+            )));
+        }
+
+        let original_entries: Vec<Entry> = entries::table
+            .filter(entries::transaction_id.eq(&trans_id))
+            .load(conn)?;
+        let amount = |units: i64| (units != 0).then(|| money::from_storage_units(units));
+        let reversal = CreateTransactionRequest {
+            reference: format!("REV-{}", original.reference),
+            description: format!(
+                "Reversal of {}: {}",
+                original.reference, original.description
+            ),
+            transaction_date: None,
+            posting_date: None,
+            metadata: None,
+            entries: original_entries
+                .iter()
+                .map(|entry| CreateEntryRequest {
+                    account_id: entry.account_id.clone(),
+                    debit_amount: amount(entry.credit_amount),
+                    credit_amount: amount(entry.debit_amount),
+                    description: entry.description.clone(),
+                    currency: Some(entry.currency.clone()),
+                })
+                .collect(),
+            ledger_id: Some(original.ledger_id.clone()),
+            status: Some(TransactionStatus::Posted),
+        };
+        let errors = collect_transaction_errors(&reversal);
+        if !errors.is_empty() {
+            return Err(AppError::ValidationError(errors.join("; ")));
+        }
+
+        let reversal_id = insert_transaction(conn, &config, clock.get_ref(), &reversal)?;
+        diesel::update(transactions::table.find(&reversal_id))
+            .set(transactions::reverses_transaction_id.eq(&trans_id))
+            .execute(conn)?;
+
+        let reversal_entries: Vec<Entry> = entries::table
+            .filter(entries::transaction_id.eq(&reversal_id))
+            .load(conn)?;
+        check_mirrors(&original_entries, &reversal_entries)?;
+
+        audit::record(
+            conn,
+            clock.get_ref(),
+            audit::ENTITY_TRANSACTION,
+            &trans_id,
+            audit::ACTION_REVERSED,
+            Some(json!({ "reversal_id": reversal_id })),
+        )?;
+
+        Ok(reversal_id)
+    })?;
+
+    cache.invalidate();
+
+    let reversal = get_transaction_with_entries_by_id(&mut conn, &reversal_id)?;
+
+    Ok(HttpResponse::Created()
+        .insert_header((
+            "Location",
+            format!("{}/transactions/{}", config.api_base_path, reversal.id),
+        ))
+        .json(ApiResponse::success(reversal)))
+}
+
+/// Confirms that a reversal posts to exactly the accounts of the original with
+/// each amount on the other side. A mismatch is a bug in building the reversal,
+/// and committing it would silently misstate balances.
+fn check_mirrors(original: &[Entry], reversal: &[Entry]) -> Result<(), AppError> {
+    let postings = |entries: &[Entry], swap: bool| {
+        let mut postings: Vec<(String, String, Decimal, Decimal)> = entries
+            .iter()
+            .map(|entry| {
+                let debit: Decimal = money::from_storage_units(entry.debit_amount);
+                let credit: Decimal = money::from_storage_units(entry.credit_amount);
+                let (account_id, currency) = (entry.account_id.clone(), entry.currency.clone());
+                if swap {
+                    (account_id, currency, credit, debit)
+                } else {
+                    (account_id, currency, debit, credit)
+                }
+            })
+            .collect();
+        postings.sort();
+        postings
+    };
+
+    if postings(original, true) != postings(reversal, false) {
+        return Err(AppError::InternalServerError(
+            "Reversal entries do not mirror the original transaction".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn delete_transaction(
+    pool: web::Data<DbPool>,
+    cache: web::Data<TrialBalanceCache>,
+    clock: web::Data<dyn Clock>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let trans_id = path.into_inner();
+    let mut conn = pool.get()?;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let transaction: Transaction = transactions::table
+            .find(&trans_id)
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))?;
+        ensure_period_open(conn, &transaction.posting_date)?;
+
+        diesel::delete(transactions::table.find(&trans_id)).execute(conn)?;
+
+        // A retry of the original request may create it again
+        diesel::delete(
+            idempotency_keys::table.filter(idempotency_keys::transaction_id.eq(&trans_id)),
+        )
+        .execute(conn)?;
+
+        audit::record(
+            conn,
+            clock.get_ref(),
+            audit::ENTITY_TRANSACTION,
+            &trans_id,
+            audit::ACTION_DELETED,
+            None,
+        )
+    })?;
+
+    cache.invalidate();
+
+    Ok(HttpResponse::NoContent().json(ApiResponse::success("Transaction deleted successfully")))
+}
+
+pub(crate) fn get_transaction_with_entries_by_id(
     conn: &mut diesel::SqliteConnection,
     trans_id: &str,
 ) -> Result<TransactionWithEntries, AppError> {
@@ -201,28 +1394,35 @@ fn get_transaction_with_entries_by_id(
         .filter(entries::transaction_id.eq(trans_id))
         .load(conn)?;
 
-    let entries_with_accounts: Vec<EntryWithAccount> = transaction_entries
+    let mut entries_with_accounts: Vec<EntryWithAccount> = transaction_entries
         .into_iter()
-        .map(|(entry, account)| EntryWithAccount {
-            id: entry.id,
-            transaction_id: entry.transaction_id,
-            account_id: entry.account_id,
-            account_code: account.code,
-            account_name: account.name,
-            debit_amount: entry.debit_amount.parse().unwrap_or(Decimal::ZERO),
-            credit_amount: entry.credit_amount.parse().unwrap_or(Decimal::ZERO),
-            description: entry.description,
-            created_at: entry.created_at,
-        })
+        .map(|(entry, account)| entry_with_account(entry, account, &transaction.description))
         .collect();
 
+    sort_entries(&mut entries_with_accounts);
+
     Ok(TransactionWithEntries {
         id: transaction.id,
         reference: transaction.reference,
         description: transaction.description,
         transaction_date: transaction.transaction_date,
+        posting_date: transaction.posting_date,
         created_at: transaction.created_at,
         updated_at: transaction.updated_at,
+        ledger_id: transaction.ledger_id,
+        reverses_transaction_id: transaction.reverses_transaction_id,
+        status: transaction.status,
+        voided: transaction.voided,
+        metadata: transaction.metadata,
         entries: entries_with_accounts,
     })
 }
+
+/// Orders entries by creation time, falling back to id so responses are stable between requests.
+fn sort_entries(entries_with_accounts: &mut [EntryWithAccount]) {
+    entries_with_accounts.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
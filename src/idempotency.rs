@@ -0,0 +1,67 @@
+//! Retry-safe transaction creation.
+//!
+//! A client that sends `Idempotency-Key` with `POST /transactions` can retry
+//! the request after a network failure: the first request records which
+//! transaction it created under the key, and later ones with the same key in
+//! the same ledger get that transaction back instead of posting it again.
+//! Keys expire after `LEDGER_IDEMPOTENCY_KEY_TTL_SECS` and can then be reused.
+
+use chrono::Duration;
+use diesel::prelude::*;
+
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::errors::AppError;
+use crate::models::IdempotencyKeyRecord;
+use crate::schema::idempotency_keys;
+
+/// The transaction previously created under `key` in `ledger_id`, if the key
+/// has not expired. Expired keys of every ledger are dropped on the way.
+pub fn find(
+    conn: &mut SqliteConnection,
+    config: &AppConfig,
+    clock: &dyn Clock,
+    ledger_id: &str,
+    key: &str,
+) -> Result<Option<String>, AppError> {
+    let cutoff = i64::try_from(config.idempotency_key_ttl_secs)
+        .ok()
+        .and_then(Duration::try_seconds)
+        .and_then(|ttl| clock.now().checked_sub_signed(ttl));
+    if let Some(cutoff) = cutoff {
+        diesel::delete(
+            idempotency_keys::table.filter(idempotency_keys::created_at.lt(cutoff.to_rfc3339())),
+        )
+        .execute(conn)?;
+    }
+
+    let transaction_id = idempotency_keys::table
+        .find((ledger_id, key))
+        .select(idempotency_keys::transaction_id)
+        .first(conn)
+        .optional()?;
+
+    Ok(transaction_id)
+}
+
+/// Records that `key` created `transaction_id`. Call it in the database
+/// transaction that inserted it, so a concurrent request with the same key
+/// fails on the primary key and rolls back its own insert.
+pub fn record(
+    conn: &mut SqliteConnection,
+    clock: &dyn Clock,
+    ledger_id: &str,
+    key: &str,
+    transaction_id: &str,
+) -> Result<(), AppError> {
+    diesel::insert_into(idempotency_keys::table)
+        .values(&IdempotencyKeyRecord {
+            ledger_id: ledger_id.to_string(),
+            idempotency_key: key.to_string(),
+            transaction_id: transaction_id.to_string(),
+            created_at: clock.now_rfc3339(),
+        })
+        .execute(conn)?;
+
+    Ok(())
+}
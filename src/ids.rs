@@ -0,0 +1,45 @@
+//! Ids for new accounts and transactions.
+//!
+//! Random UUIDs by default. With `LEDGER_ID_FORMAT=sequential` ids are a type
+//! prefix plus a zero-padded counter kept in the `sequences` table, e.g.
+//! `acc_000123` or `txn_000045`.
+
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::schema::sequences;
+
+pub const ACCOUNT_PREFIX: &str = "acc";
+pub const TRANSACTION_PREFIX: &str = "txn";
+
+/// A fresh id for a row of the kind named by `prefix`.
+pub fn new_id(
+    conn: &mut SqliteConnection,
+    config: &AppConfig,
+    prefix: &str,
+) -> QueryResult<String> {
+    if !config.sequential_ids() {
+        return Ok(Uuid::new_v4().to_string());
+    }
+
+    let value = next_value(conn, prefix)?;
+    Ok(format!("{}_{:06}", prefix, value))
+}
+
+/// Bumps the named counter and returns its new value, starting at 1.
+fn next_value(conn: &mut SqliteConnection, name: &str) -> QueryResult<i64> {
+    conn.transaction(|conn| {
+        diesel::insert_into(sequences::table)
+            .values((sequences::name.eq(name), sequences::value.eq(1)))
+            .on_conflict(sequences::name)
+            .do_update()
+            .set(sequences::value.eq(sequences::value + 1))
+            .execute(conn)?;
+
+        sequences::table
+            .find(name)
+            .select(sequences::value)
+            .first(conn)
+    })
+}
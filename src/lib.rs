@@ -1,8 +1,20 @@
 // Re-export modules for testing
+pub mod audit;
+pub mod bank_feed;
+pub mod cache;
+pub mod camt053;
+pub mod clock;
+pub mod code_ranges;
 pub mod config;
+pub mod csv_import;
 pub mod database;
 pub mod errors;
+pub mod extractors;
 pub mod handlers;
+pub mod idempotency;
+pub mod ids;
 pub mod middleware;
 pub mod models;
-pub mod schema;
\ No newline at end of file
+pub mod money;
+pub mod schema;
+pub mod seed;
@@ -1,50 +1,103 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use dotenvy::dotenv;
+use double_rust_ledger::{
+    cache::{AccountTreeCache, TrialBalanceCache},
+    clock::{Clock, SystemClock},
+    config::AppConfig,
+    database, handlers, middleware, seed,
+};
 use log::{error, info};
-use std::env;
 use tokio::signal;
-mod config;
-mod database;
-mod errors;
-mod handlers;
-mod middleware;
-mod models;
-mod schema;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
     env_logger::init();
 
-    let database_url = env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:ledger.db".to_string());
+    let app_config = match AppConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                e.to_string(),
+            ));
+        }
+    };
 
     info!("Starting Double Entry Ledger API server...");
-    info!("Database URL: {}", database_url);
+    info!("Database URL: {}", app_config.database_url);
 
     // Initialize database connection
-    let db_pool = database::create_pool(&database_url).expect("Failed to create database pool");
+    let db_pool = database::create_pool(&app_config.database_url, app_config.pool_size)
+        .expect("Failed to create database pool");
+
+    // Run migrations, refusing to start against a schema newer than this build
+    // and waiting while another instance holds the migration lock
+    let lock_timeout = Duration::from_secs(app_config.migration_lock_timeout_secs);
+    if let Err(e) = database::run_migrations_with_lock_timeout(&db_pool, lock_timeout) {
+        error!("{}", e);
+        return Err(std::io::Error::other(e.to_string()));
+    }
+
+    let mut conn = db_pool.get().expect("Failed to get database connection");
+    let backfilled = seed::backfill_currency(&mut conn, &app_config.currency)
+        .expect("Failed to backfill currencies");
+    if backfilled > 0 {
+        info!(
+            "Gave {} accounts and entries the ledger currency {}",
+            backfilled, app_config.currency
+        );
+    }
 
-    // Run migrations
-    database::run_migrations(&db_pool).expect("Failed to run migrations");
+    if app_config.system_accounts {
+        let created = seed::ensure_system_accounts(&mut conn, &app_config.currency, &SystemClock)
+            .expect("Failed to create system accounts");
+        if created > 0 {
+            info!("Created {} system accounts", created);
+        }
+    }
+    drop(conn);
 
-    let bind_address = env::var("BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+    let bind_address = app_config.bind_address.clone();
     info!("Server running at http://{}", bind_address);
 
+    // Shared across workers so every worker sees the same invalidations
+    let trial_balance_cache = web::Data::new(TrialBalanceCache::default());
+    let account_tree_cache = web::Data::new(AccountTreeCache::default());
+    let clock: web::Data<dyn Clock> = web::Data::from(Arc::new(SystemClock) as Arc<dyn Clock>);
+    let api_base_path = app_config.api_base_path.clone();
+    let serialize_writes = middleware::SerializeWrites::new(app_config.serialize_writes);
+    let concurrency_limit = middleware::ConcurrencyLimit::new(app_config.max_concurrent_requests)
+        .exempt(handlers::PROBE_PATHS);
+
     // Create HttpServer
     let server = HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::new(app_config.clone()))
+            .app_data(handlers::json_config(&app_config))
+            .app_data(trial_balance_cache.clone())
+            .app_data(account_tree_cache.clone())
+            .app_data(clock.clone())
+            .wrap(serialize_writes.clone())
             .wrap(middleware::PanicRecovery)
-            .wrap(middleware::RequestTimeout::new(30)) // 30 second timeout
+            .wrap(
+                // 30 second timeout, except for long-running exports and reports
+                middleware::RequestTimeout::new(30).exempt(app_config.timeout_exempt_prefixes()),
+            )
+            .wrap(middleware::SlowRequestLog::new(app_config.slow_request_ms))
+            .wrap(concurrency_limit.clone())
+            .wrap(
+                middleware::ApiKeyAuth::new(app_config.api_keys.clone())
+                    .exempt(handlers::PROBE_PATHS),
+            )
             .wrap(Logger::default())
             .wrap(actix_cors::Cors::permissive())
-            .service(
-                web::scope("/api/v1")
-                    .service(handlers::accounts::config())
-                    .service(handlers::transactions::config())
-                    .service(handlers::balance::config()),
-            )
-            .service(web::resource("/health").route(web::get().to(handlers::health::health_check)))
+            .configure(|cfg| handlers::configure_at(cfg, &api_base_path))
     })
     .bind(&bind_address)?
     .run();
@@ -1,14 +1,18 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpResponse, Result,
+    http::{header, Method},
+    Error, Result,
 };
 use futures_util::future::LocalBoxFuture;
 use log::{error, warn};
 use std::future::{ready, Ready};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore};
 use tokio::time::timeout;
 
-use crate::models::ApiResponse;
+use crate::database::with_db_timer;
+use crate::errors::AppError;
 
 pub struct PanicRecovery;
 
@@ -62,14 +66,27 @@ where
 
 pub struct RequestTimeout {
     timeout: Duration,
+    exempt_prefixes: Arc<Vec<String>>,
 }
 
 impl RequestTimeout {
     pub fn new(timeout_secs: u64) -> Self {
         Self {
             timeout: Duration::from_secs(timeout_secs),
+            exempt_prefixes: Arc::new(Vec::new()),
         }
     }
+
+    /// Lets requests under any of these path prefixes run without a time limit,
+    /// for exports and reports that legitimately take long.
+    pub fn exempt<I, P>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.exempt_prefixes = Arc::new(prefixes.into_iter().map(Into::into).collect());
+        self
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for RequestTimeout
@@ -88,6 +105,7 @@ where
         ready(Ok(RequestTimeoutMiddleware {
             service,
             timeout: self.timeout,
+            exempt_prefixes: self.exempt_prefixes.clone(),
         }))
     }
 }
@@ -95,6 +113,7 @@ where
 pub struct RequestTimeoutMiddleware<S> {
     service: S,
     timeout: Duration,
+    exempt_prefixes: Arc<Vec<String>>,
 }
 
 impl<S, B> Service<ServiceRequest> for RequestTimeoutMiddleware<S>
@@ -110,9 +129,17 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
+        let exempt = self
+            .exempt_prefixes
+            .iter()
+            .any(|prefix| path_is_under(req.path(), prefix));
         let fut = self.service.call(req);
         let timeout_duration = self.timeout;
 
+        if exempt {
+            return Box::pin(fut);
+        }
+
         Box::pin(async move {
             match timeout(timeout_duration, fut).await {
                 Ok(response) => response,
@@ -123,4 +150,390 @@ where
             }
         })
     }
-}
\ No newline at end of file
+}
+
+/// Requires an `Authorization: Bearer <key>` header carrying one of the
+/// configured keys and answers `401 Unauthorized` otherwise. With no keys
+/// configured every request is let through.
+pub struct ApiKeyAuth {
+    keys: Arc<Vec<String>>,
+    exempt_prefixes: Arc<Vec<String>>,
+}
+
+impl ApiKeyAuth {
+    pub fn new<I, K>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        Self {
+            keys: Arc::new(keys.into_iter().map(Into::into).collect()),
+            exempt_prefixes: Arc::new(Vec::new()),
+        }
+    }
+
+    /// Lets requests under any of these path prefixes through without a key,
+    /// so health checks keep working.
+    pub fn exempt<I, P>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.exempt_prefixes = Arc::new(prefixes.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ApiKeyAuthMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyAuthMiddleware {
+            service,
+            keys: self.keys.clone(),
+            exempt_prefixes: self.exempt_prefixes.clone(),
+        }))
+    }
+}
+
+pub struct ApiKeyAuthMiddleware<S> {
+    service: S,
+    keys: Arc<Vec<String>>,
+    exempt_prefixes: Arc<Vec<String>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let exempt = self
+            .exempt_prefixes
+            .iter()
+            .any(|prefix| path_is_under(req.path(), prefix));
+        if self.keys.is_empty() || exempt {
+            return Box::pin(self.service.call(req));
+        }
+
+        let presented = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(bearer_token);
+        let rejection = match presented {
+            None => Some("missing API key"),
+            Some(key) if !self.keys.iter().any(|known| keys_match(known, key)) => {
+                Some("invalid API key")
+            }
+            Some(_) => None,
+        };
+        if let Some(reason) = rejection {
+            warn!("Rejecting {} {}: {}", req.method(), req.path(), reason);
+            return Box::pin(ready(
+                Err(AppError::Unauthorized(reason.to_string()).into()),
+            ));
+        }
+
+        Box::pin(self.service.call(req))
+    }
+}
+
+/// The credentials of a `Bearer` authorization value; the scheme is case-insensitive.
+fn bearer_token(value: &str) -> Option<&str> {
+    let (scheme, token) = value.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Compares keys in time independent of where they first differ, so response
+/// timing does not reveal how much of a guess was right.
+fn keys_match(known: &str, presented: &str) -> bool {
+    known.len() == presented.len()
+        && known
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Whether `path` is `prefix` itself or below it, matching whole segments only.
+fn path_is_under(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || prefix.is_empty(),
+        None => false,
+    }
+}
+
+/// Logs a warning with the method, path and timings of every request that takes
+/// longer than the threshold. The time its queries spent in the database is
+/// reported separately from the total.
+pub struct SlowRequestLog {
+    threshold: Duration,
+}
+
+impl SlowRequestLog {
+    pub fn new(threshold_ms: u64) -> Self {
+        Self {
+            threshold: Duration::from_millis(threshold_ms),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SlowRequestLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = SlowRequestLogMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SlowRequestLogMiddleware {
+            service,
+            threshold: self.threshold,
+        }))
+    }
+}
+
+pub struct SlowRequestLogMiddleware<S> {
+    service: S,
+    threshold: Duration,
+}
+
+impl<S, B> Service<ServiceRequest> for SlowRequestLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let method = req.method().clone();
+        let path = req.path().to_string();
+        let threshold = self.threshold;
+        let started = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let (response, db_time) = with_db_timer(fut).await;
+            let elapsed = started.elapsed();
+
+            if elapsed > threshold {
+                warn!(
+                    "Slow request: {} {} took {} ms (db {} ms, threshold {} ms)",
+                    method,
+                    path,
+                    elapsed.as_millis(),
+                    db_time.as_millis(),
+                    threshold.as_millis()
+                );
+            }
+
+            response
+        })
+    }
+}
+
+/// Runs requests that may write one at a time while reads stay concurrent.
+///
+/// SQLite allows a single writer; concurrent writers race for the database lock
+/// and the losers fail with "database is locked". Queueing writes here trades
+/// write throughput for never hitting that error. Create one instance and clone
+/// it into every worker so they all share the same lock.
+#[derive(Clone)]
+pub struct SerializeWrites {
+    lock: Option<Arc<Mutex<()>>>,
+}
+
+impl SerializeWrites {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            lock: enabled.then(|| Arc::new(Mutex::new(()))),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SerializeWrites
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = SerializeWritesMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SerializeWritesMiddleware {
+            service,
+            lock: self.lock.clone(),
+        }))
+    }
+}
+
+pub struct SerializeWritesMiddleware<S> {
+    service: S,
+    lock: Option<Arc<Mutex<()>>>,
+}
+
+impl<S, B> Service<ServiceRequest> for SerializeWritesMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_read = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+        let lock = self.lock.clone().filter(|_| !is_read);
+        // The handler does not run until this future is polled, so holding the
+        // guard across the await covers all of its database work
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let _guard = match lock {
+                Some(lock) => Some(lock.lock_owned().await),
+                None => None,
+            };
+            fut.await
+        })
+    }
+}
+
+/// Admits at most a fixed number of requests at a time and turns the rest away
+/// with `503 Service Unavailable` and `Retry-After`, rather than letting them
+/// queue for a database connection. Like `SerializeWrites`, create one instance
+/// and clone it into every worker so the limit is shared.
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    permits: Option<Arc<Semaphore>>,
+    exempt_prefixes: Arc<Vec<String>>,
+}
+
+impl ConcurrencyLimit {
+    /// `None` admits every request.
+    pub fn new(max_concurrent: Option<usize>) -> Self {
+        Self {
+            permits: max_concurrent.map(|max| Arc::new(Semaphore::new(max))),
+            exempt_prefixes: Arc::new(Vec::new()),
+        }
+    }
+
+    /// Lets requests under any of these path prefixes through without taking a
+    /// permit, so health checks keep answering under load.
+    pub fn exempt<I, P>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.exempt_prefixes = Arc::new(prefixes.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ConcurrencyLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ConcurrencyLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ConcurrencyLimitMiddleware {
+            service,
+            permits: self.permits.clone(),
+            exempt_prefixes: self.exempt_prefixes.clone(),
+        }))
+    }
+}
+
+pub struct ConcurrencyLimitMiddleware<S> {
+    service: S,
+    permits: Option<Arc<Semaphore>>,
+    exempt_prefixes: Arc<Vec<String>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ConcurrencyLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let exempt = self
+            .exempt_prefixes
+            .iter()
+            .any(|prefix| path_is_under(req.path(), prefix));
+        let permits = match &self.permits {
+            Some(permits) if !exempt => permits.clone(),
+            _ => return Box::pin(self.service.call(req)),
+        };
+
+        let permit = match permits.try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                warn!(
+                    "Rejecting {} {}: too many requests in flight",
+                    req.method(),
+                    req.path()
+                );
+                return Box::pin(ready(Err(AppError::ServiceUnavailable(
+                    "too many concurrent requests".to_string(),
+                )
+                .into())));
+            }
+        };
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let response = fut.await;
+            drop(permit);
+            response
+        })
+    }
+}
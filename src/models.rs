@@ -1,8 +1,23 @@
 use crate::schema::*;
 use diesel::prelude::*;
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
-use validator::Validate;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::money::Amount;
+use validator::{Validate, ValidationError};
+
+/// Ledger used when a request does not name one explicitly.
+pub const DEFAULT_LEDGER_ID: &str = "default";
+
+/// Equity account that absorbs opening balances; created at startup.
+pub const OPENING_BALANCES_ACCOUNT_ID: &str = "system-opening-balances";
+
+/// Holding account for uncategorized postings such as imported lines; created at startup.
+pub const SUSPENSE_ACCOUNT_ID: &str = "system-suspense";
+
+/// Accounts the ledger relies on; they can be neither deleted nor deactivated,
+/// locked, made memo or retyped.
+pub const SYSTEM_ACCOUNT_IDS: [&str; 2] = [OPENING_BALANCES_ACCOUNT_ID, SUSPENSE_ACCOUNT_ID];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
 #[diesel(table_name = accounts)]
@@ -15,6 +30,14 @@ pub struct Account {
     pub is_active: bool,
     pub created_at: String,
     pub updated_at: String,
+    pub is_memo: bool,
+    pub ledger_id: String,
+    pub opening_balance: String,
+    pub opening_date: Option<String>,
+    /// False while the account is locked: it keeps reporting but takes no new entries.
+    pub postable: bool,
+    /// ISO 4217 code every entry on the account is denominated in.
+    pub currency: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -31,6 +54,25 @@ pub enum AccountType {
     Expense,
 }
 
+impl AccountType {
+    /// The side on which entries increase an account of this type.
+    pub fn normal_side(&self) -> NormalSide {
+        match self {
+            AccountType::Asset | AccountType::Expense => NormalSide::Debit,
+            AccountType::Liability | AccountType::Equity | AccountType::Revenue => {
+                NormalSide::Credit
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NormalSide {
+    Debit,
+    Credit,
+}
+
 impl From<String> for AccountType {
     fn from(s: String) -> Self {
         match s.as_str() {
@@ -58,23 +100,149 @@ impl From<AccountType> for String {
 
 #[derive(Debug, Validate, Deserialize)]
 pub struct CreateAccountRequest {
-    #[validate(length(min = 1, max = 20))]
+    #[validate(length(min = 1, max = 20), custom = "validate_account_code")]
     pub code: String,
     #[validate(length(min = 1, max = 255))]
     pub name: String,
     pub account_type: AccountType,
     pub parent_id: Option<String>,
+    /// Memo (statistical) accounts track totals but stay out of the accounting equation.
+    #[serde(default)]
+    pub is_memo: bool,
+    pub ledger_id: Option<String>,
+    /// Balance carried in from a previous system, signed by the account's normal side.
+    pub opening_balance: Option<Decimal>,
+    /// `YYYY-MM-DD`; only entries dated after this day add to the opening balance.
+    pub opening_date: Option<String>,
+    /// ISO 4217 code; defaults to the ledger currency.
+    pub currency: Option<String>,
+}
+
+/// Codes are matched against `LEDGER_ACCOUNT_CODE_PATTERN` when the account
+/// is written; whitespace is never allowed, whatever the pattern.
+fn validate_account_code(code: &str) -> Result<(), ValidationError> {
+    if code.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        let mut error = ValidationError::new("account_code");
+        error.message = Some("must not contain whitespace or control characters".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Dates are stored as given and compared as text by period locks and report
+/// ranges, so only RFC 3339 timestamps and `YYYY-MM-DD` dates are accepted.
+fn validate_transaction_date(date: &str) -> Result<(), ValidationError> {
+    let valid = chrono::DateTime::parse_from_rfc3339(date).is_ok()
+        || chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok();
+    if !valid {
+        let mut error = ValidationError::new("date");
+        error.message = Some("must be an RFC 3339 timestamp or a YYYY-MM-DD date".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Columns `GET /accounts` can be sorted by with `sort_by`.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountSortKey {
+    Code,
+    Name,
+    AccountType,
+    CreatedAt,
+    UpdatedAt,
 }
 
+/// Columns `GET /transactions` can be sorted by with `sort_by`.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionSortKey {
+    Reference,
+    TransactionDate,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountsQuery {
+    pub modified_since: Option<String>,
+    pub sort_by: Option<AccountSortKey>,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Either of `limit` and `offset` switches the list to pages.
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteAccountQuery {
+    /// Removes the row instead of deactivating it; only allowed without entries.
+    #[serde(default)]
+    pub hard: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionsQuery {
+    pub sort_by: Option<TransactionSortKey>,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Either of `limit` and `offset` switches the list to pages.
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTransactionQuery {
+    /// Returns an existing transaction with the same reference and date instead
+    /// of creating another.
+    #[serde(default)]
+    pub skip_existing: bool,
+}
+
+/// Body of `POST /transactions/by-accounts`: every transaction with at least
+/// one entry against any of `account_ids`, paged and optionally date-bounded.
 #[derive(Debug, Validate, Deserialize)]
+pub struct TransactionsByAccountsRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub account_ids: Vec<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Query of `GET /transactions/search`: `q` is matched case-insensitively
+/// against the reference and description.
+#[derive(Debug, Deserialize)]
+pub struct TransactionSearchQuery {
+    pub q: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Default, Validate, Deserialize)]
 pub struct UpdateAccountRequest {
-    #[validate(length(min = 1, max = 20))]
+    #[validate(length(min = 1, max = 20), custom = "validate_account_code")]
     pub code: Option<String>,
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
     pub account_type: Option<AccountType>,
     pub parent_id: Option<String>,
     pub is_active: Option<bool>,
+    pub is_memo: Option<bool>,
+    pub opening_balance: Option<Decimal>,
+    pub opening_date: Option<String>,
+    pub postable: Option<bool>,
 }
 
 #[derive(Debug, Insertable)]
@@ -88,6 +256,11 @@ pub struct NewAccount {
     pub is_active: bool,
     pub created_at: String,
     pub updated_at: String,
+    pub is_memo: bool,
+    pub ledger_id: String,
+    pub opening_balance: String,
+    pub opening_date: Option<String>,
+    pub currency: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
@@ -99,6 +272,18 @@ pub struct Transaction {
     pub transaction_date: String,
     pub created_at: String,
     pub updated_at: String,
+    pub ledger_id: String,
+    pub reverses_transaction_id: Option<String>,
+    pub status: String,
+    pub voided: bool,
+    /// Period the reference is unique within; `None` when references are global.
+    pub period_id: Option<String>,
+    /// Date the transaction counts from in balances, reports and period locks;
+    /// `transaction_date` stays the document date.
+    pub posting_date: String,
+    /// JSON object of integration-specific keys, stored as text.
+    #[serde(serialize_with = "serialize_json_text")]
+    pub metadata: Option<String>,
 }
 
 #[derive(Debug, Validate, Deserialize)]
@@ -107,10 +292,132 @@ pub struct CreateTransactionRequest {
     pub reference: String,
     #[validate(length(min = 1, max = 500))]
     pub description: String,
+    #[validate(custom = "validate_transaction_date")]
     pub transaction_date: Option<String>,
+    /// Defaults to the transaction date.
+    #[validate(custom = "validate_transaction_date")]
+    pub posting_date: Option<String>,
+    /// Integration-specific keys such as the source system; must be an object.
+    pub metadata: Option<serde_json::Value>,
+    pub entries: Vec<CreateEntryRequest>,
+    pub ledger_id: Option<String>,
+    /// Defaults to `posted`; drafts stay out of balances and reports until posted.
+    pub status: Option<TransactionStatus>,
+}
+
+/// New entries for a draft transaction; they replace all existing ones.
+#[derive(Debug, Validate, Deserialize)]
+pub struct ReplaceEntriesRequest {
+    #[validate]
     pub entries: Vec<CreateEntryRequest>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    Draft,
+    Posted,
+}
+
+impl TransactionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStatus::Draft => "draft",
+            TransactionStatus::Posted => "posted",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchTransactionRequest {
+    pub transactions: Vec<CreateTransactionRequest>,
+    #[serde(default)]
+    pub atomic: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Pending,
+    Created,
+    Updated,
+    Unchanged,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub status: BatchItemStatus,
+    pub transaction_id: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// One item of a bulk account update: the account id plus the same fields
+/// accepted by `PUT /accounts/{id}`.
+#[derive(Debug, Deserialize)]
+pub struct AccountUpdateItem {
+    pub id: String,
+    #[serde(flatten)]
+    pub update: UpdateAccountRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkAccountUpdateRequest {
+    pub updates: Vec<AccountUpdateItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountUpdateResult {
+    pub id: String,
+    pub status: BatchItemStatus,
+    pub errors: Vec<String>,
+}
+
+/// The full chart of accounts from an external system of record, keyed by code.
+#[derive(Debug, Deserialize)]
+pub struct AccountSyncRequest {
+    pub accounts: Vec<CreateAccountRequest>,
+    /// Deactivates active accounts whose code is not in `accounts`.
+    #[serde(default)]
+    pub deactivate_missing: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AccountSyncSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub deactivated: usize,
+}
+
+/// A chart of accounts to check before importing it. Records have no ids yet,
+/// so here `parent_id` always holds the parent's code: another record in the
+/// set, or an active account of the record's ledger. Account ids are not
+/// accepted.
+#[derive(Debug, Deserialize)]
+pub struct AccountValidationRequest {
+    pub accounts: Vec<CreateAccountRequest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountValidationResult {
+    pub index: usize,
+    pub code: String,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountValidationReport {
+    pub valid: bool,
+    pub accounts: Vec<AccountValidationResult>,
+    /// Codes used by more than one record of the same ledger in the set.
+    pub duplicate_codes: Vec<String>,
+    /// Each cycle of parent links, as the codes of the records on it.
+    pub cycles: Vec<Vec<String>>,
+}
+
 #[derive(Debug, Insertable)]
 #[diesel(table_name = transactions)]
 pub struct NewTransaction {
@@ -120,6 +427,12 @@ pub struct NewTransaction {
     pub transaction_date: String,
     pub created_at: String,
     pub updated_at: String,
+    pub ledger_id: String,
+    pub reverses_transaction_id: Option<String>,
+    pub status: String,
+    pub period_id: Option<String>,
+    pub posting_date: String,
+    pub metadata: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable)]
@@ -128,10 +441,17 @@ pub struct Entry {
     pub id: String,
     pub transaction_id: String,
     pub account_id: String,
-    pub debit_amount: String,
-    pub credit_amount: String,
+    /// In storage units; see `money::from_storage_units`.
+    pub debit_amount: i64,
+    pub credit_amount: i64,
     pub description: Option<String>,
     pub created_at: String,
+    /// Set when a stored text amount could not be converted to storage units
+    /// and was replaced with zero; see `GET /admin/corrupt-amounts`.
+    pub amount_flagged: bool,
+    /// Matched against a bank or card statement.
+    pub reconciled: bool,
+    pub currency: String,
 }
 
 #[derive(Debug, Validate, Deserialize)]
@@ -141,6 +461,8 @@ pub struct CreateEntryRequest {
     pub credit_amount: Option<Decimal>,
     #[validate(length(max = 255))]
     pub description: Option<String>,
+    /// Must match the account's currency; taken from the account when omitted.
+    pub currency: Option<String>,
 }
 
 #[derive(Debug, Insertable)]
@@ -149,21 +471,194 @@ pub struct NewEntry {
     pub id: String,
     pub transaction_id: String,
     pub account_id: String,
-    pub debit_amount: String,
-    pub credit_amount: String,
+    pub debit_amount: i64,
+    pub credit_amount: i64,
     pub description: Option<String>,
     pub created_at: String,
+    pub currency: String,
+}
+
+/// The transaction created by the first request carrying an `Idempotency-Key`.
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[diesel(table_name = idempotency_keys)]
+pub struct IdempotencyKeyRecord {
+    pub ledger_id: String,
+    pub idempotency_key: String,
+    pub transaction_id: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = ledger_quotas)]
+pub struct LedgerQuota {
+    pub ledger_id: String,
+    pub max_accounts: Option<i64>,
+    pub max_transactions: Option<i64>,
+    pub updated_at: String,
+}
+
+/// Limits left as `None` are unlimited.
+#[derive(Debug, Validate, Deserialize)]
+pub struct UpdateQuotaRequest {
+    #[validate(range(min = 0))]
+    pub max_accounts: Option<i64>,
+    #[validate(range(min = 0))]
+    pub max_transactions: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = periods)]
+pub struct Period {
+    pub id: String,
+    pub name: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub closed_at: Option<String>,
+    pub created_at: String,
+}
+
+/// One account's balance frozen at the end of a period.
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[diesel(table_name = period_snapshots)]
+pub struct PeriodSnapshotRow {
+    pub period_id: String,
+    pub account_id: String,
+    pub account_code: String,
+    pub account_name: String,
+    pub balance: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotBalance {
+    pub account_id: String,
+    pub account_code: String,
+    pub account_name: String,
+    /// Balance recorded when the snapshot was taken.
+    pub balance: Amount,
+    /// Balance as of the same date computed from today's entries.
+    pub live_balance: Amount,
+    pub drift: Amount,
+}
+
+/// Balances frozen at a period's end, compared against the live ledger.
+/// `has_drift` is set once back-dated changes have moved any balance.
+#[derive(Debug, Serialize)]
+pub struct PeriodSnapshot {
+    pub period_id: String,
+    pub period_name: String,
+    pub as_of: String,
+    pub created_at: String,
+    pub has_drift: bool,
+    pub balances: Vec<SnapshotBalance>,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct CreatePeriodRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    /// Inclusive `YYYY-MM-DD` bounds.
+    pub start_date: String,
+    pub end_date: String,
 }
 
 #[derive(Debug, Serialize)]
+pub struct QuotaUsage {
+    pub ledger_id: String,
+    pub max_accounts: Option<i64>,
+    pub max_transactions: Option<i64>,
+    pub account_count: i64,
+    pub transaction_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeedSummary {
+    pub accounts_created: usize,
+    pub transactions_created: usize,
+    pub account_count: i64,
+    pub transaction_count: i64,
+}
+
+/// An entry amount that was zeroed when text amounts were converted, with the
+/// text it held before.
+#[derive(Debug, Serialize, Queryable)]
+pub struct CorruptAmount {
+    pub entry_id: String,
+    pub transaction_id: String,
+    pub account_id: String,
+    pub field: String,
+    pub raw_value: String,
+}
+
+/// Amounts carry the scale of the account's currency.
+#[derive(Debug, Clone, Serialize)]
 pub struct AccountBalance {
     pub account_id: String,
     pub account_code: String,
     pub account_name: String,
     pub account_type: String,
-    pub debit_total: Decimal,
-    pub credit_total: Decimal,
-    pub balance: Decimal,
+    pub currency: String,
+    pub opening_balance: Amount,
+    pub debit_total: Amount,
+    pub credit_total: Amount,
+    pub balance: Amount,
+    pub is_memo: bool,
+    pub normal_side: NormalSide,
+    /// False when the balance sits on the opposite side, e.g. an overdrawn asset.
+    pub balance_is_normal: bool,
+}
+
+/// A single account's balance split by entry reconciliation. The opening
+/// balance counts as reconciled, so the two parts add up to `balance`.
+#[derive(Debug, Serialize)]
+pub struct AccountBalanceDetail {
+    #[serde(flatten)]
+    pub balance: AccountBalance,
+    pub reconciled_balance: Amount,
+    pub unreconciled_balance: Amount,
+}
+
+/// An account's balance right after one transaction in posting order.
+/// `account_involved` is false when the transaction has no entry on the account.
+#[derive(Debug, Serialize)]
+pub struct BalanceAtTransaction {
+    pub transaction_id: String,
+    pub transaction_reference: String,
+    pub transaction_date: String,
+    pub account_involved: bool,
+    pub balance: AccountBalance,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrialBalance {
+    pub accounts: Vec<AccountBalance>,
+    /// Summed over every account; `None` when the accounts use several
+    /// currencies, since such a sum means nothing.
+    pub total_debits: Option<Amount>,
+    pub total_credits: Option<Amount>,
+    /// One row per currency, in code order.
+    pub totals: Vec<CurrencyTotals>,
+    /// True when every currency balances on its own.
+    pub is_balanced: bool,
+}
+
+/// Trial balance totals of the accounts kept in one currency.
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrencyTotals {
+    pub currency: String,
+    pub total_debits: Amount,
+    pub total_credits: Amount,
+    pub is_balanced: bool,
+}
+
+/// A transaction as listed, with aggregates of its entries.
+#[derive(Debug, Serialize)]
+pub struct TransactionSummary {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    pub entry_count: i64,
+    /// Sum of the debit side, which equals the credit side for balanced transactions.
+    pub total_amount: Amount,
 }
 
 #[derive(Debug, Serialize)]
@@ -172,11 +667,86 @@ pub struct TransactionWithEntries {
     pub reference: String,
     pub description: String,
     pub transaction_date: String,
+    pub posting_date: String,
     pub created_at: String,
     pub updated_at: String,
+    pub ledger_id: String,
+    pub reverses_transaction_id: Option<String>,
+    pub status: String,
+    pub voided: bool,
+    #[serde(serialize_with = "serialize_json_text")]
+    pub metadata: Option<String>,
     pub entries: Vec<EntryWithAccount>,
 }
 
+impl TransactionWithEntries {
+    pub fn with_amount_format(mut self, format: AmountFormat) -> Self {
+        self.entries = self
+            .entries
+            .into_iter()
+            .map(|entry| entry.with_amount_format(format))
+            .collect();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct AuditLogEntry {
+    pub id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub details: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub entity_type: Option<String>,
+    pub action: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// One page of a larger result set; `total` counts every matching row.
+#[derive(Debug, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionAuditTrail {
+    pub transaction: Transaction,
+    pub reverses: Option<Transaction>,
+    pub reversed_by: Vec<Transaction>,
+    pub audit_entries: Vec<AuditLogEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifiedAccount {
+    pub account_id: String,
+    pub exists: bool,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionVerification {
+    pub transaction_id: String,
+    pub entry_count: usize,
+    pub debit_total: Amount,
+    pub credit_total: Amount,
+    pub is_balanced: bool,
+    pub accounts_valid: bool,
+    pub accounts: Vec<VerifiedAccount>,
+}
+
+/// Amounts carry the scale of the entry's currency.
 #[derive(Debug, Serialize)]
 pub struct EntryWithAccount {
     pub id: String,
@@ -184,18 +754,343 @@ pub struct EntryWithAccount {
     pub account_id: String,
     pub account_code: String,
     pub account_name: String,
-    pub debit_amount: Decimal,
-    pub credit_amount: Decimal,
+    #[serde(flatten)]
+    pub amounts: EntryAmounts,
+    pub currency: String,
     pub description: Option<String>,
+    /// True when `description` was taken from the parent transaction.
+    pub description_inherited: bool,
     pub created_at: String,
 }
 
+impl EntryWithAccount {
+    pub fn with_amount_format(mut self, format: AmountFormat) -> Self {
+        if let (
+            AmountFormat::Signed,
+            EntryAmounts::Columns {
+                debit_amount,
+                credit_amount,
+            },
+        ) = (format, &self.amounts)
+        {
+            self.amounts = EntryAmounts::Signed {
+                amount: Amount::new(debit_amount.value - credit_amount.value, debit_amount.scale),
+            };
+        }
+        self
+    }
+}
+
+/// How entry amounts are written out, chosen with `?amount_format=`.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountFormat {
+    /// Separate `debit_amount` and `credit_amount` columns.
+    #[default]
+    Columns,
+    /// One `amount`: debits positive, credits negative.
+    Signed,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum EntryAmounts {
+    Columns {
+        debit_amount: Amount,
+        credit_amount: Amount,
+    },
+    Signed {
+        amount: Amount,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AmountFormatQuery {
+    #[serde(default)]
+    pub amount_format: AmountFormat,
+}
+
+/// A single entry with the context of its parent transaction.
+#[derive(Debug, Serialize)]
+pub struct EntryDetail {
+    #[serde(flatten)]
+    pub entry: EntryWithAccount,
+    pub transaction_reference: String,
+    pub transaction_date: String,
+}
+
+/// Falls back to the transaction description when an entry has none of its own.
+/// Returns the description to show and whether it was inherited.
+pub fn resolve_entry_description(
+    entry_description: Option<String>,
+    transaction_description: &str,
+) -> (Option<String>, bool) {
+    match entry_description {
+        Some(description) => (Some(description), false),
+        None => (Some(transaction_description.to_string()), true),
+    }
+}
+
+/// `serialize_with` target for JSON stored in a text column, so it is returned
+/// as JSON rather than as an escaped string.
+fn serialize_json_text<S: Serializer>(
+    text: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let value: Option<serde_json::Value> = text
+        .as_deref()
+        .and_then(|text| serde_json::from_str(text).ok());
+    value.serialize(serializer)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BalanceQuery {
     pub account_id: Option<String>,
     pub account_type: Option<String>,
     pub from_date: Option<String>,
+    #[serde(alias = "as_of")]
     pub to_date: Option<String>,
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChartQuery {
+    pub as_of: Option<String>,
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountTypeSummaryQuery {
+    pub as_of: Option<String>,
+    /// How many of each type's largest accounts to list.
+    pub top: Option<usize>,
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+/// The total balance of one account type with its largest accounts. `link`
+/// fields point at the balance endpoints for drilling down.
+#[derive(Debug, Serialize)]
+pub struct AccountTypeSummary {
+    pub account_type: String,
+    pub total_balance: Amount,
+    pub account_count: usize,
+    pub link: String,
+    pub top_accounts: Vec<AccountContribution>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountContribution {
+    pub account_id: String,
+    pub account_code: String,
+    pub account_name: String,
+    pub balance: Amount,
+    pub link: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceSheetCompareQuery {
+    pub period_a: String,
+    pub period_b: String,
+}
+
+/// Balance sheet accounts at the end of two periods. Every `delta` is the
+/// period B figure minus the period A one.
+#[derive(Debug, Serialize)]
+pub struct BalanceSheetComparison {
+    pub period_a: Period,
+    pub period_b: Period,
+    pub sections: Vec<BalanceSheetSectionDelta>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceSheetSectionDelta {
+    pub account_type: String,
+    pub balance_a: Amount,
+    pub balance_b: Amount,
+    pub delta: Amount,
+    pub accounts: Vec<AccountBalanceDelta>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountBalanceDelta {
+    pub account_id: String,
+    pub account_code: String,
+    pub account_name: String,
+    pub balance_a: Amount,
+    pub balance_b: Amount,
+    pub delta: Amount,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesInterval {
+    Day,
+    Week,
+    #[default]
+    Month,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceSeriesQuery {
+    pub account_type: AccountType,
+    #[serde(default)]
+    pub interval: SeriesInterval,
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+/// Combined balance of every non-memo account of one type at the end of each
+/// interval.
+#[derive(Debug, Serialize)]
+pub struct BalanceSeries {
+    pub account_type: String,
+    pub interval: SeriesInterval,
+    pub points: Vec<BalancePoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalancePoint {
+    pub date: String,
+    pub balance: Amount,
+}
+
+/// One account of the chart of accounts with its sub-accounts. `balance` is the
+/// account's own; `rolled_up_balance` adds every descendant's, on this account's
+/// normal side.
+#[derive(Debug, Serialize)]
+pub struct ChartNode {
+    pub account_id: String,
+    pub account_code: String,
+    pub account_name: String,
+    pub account_type: String,
+    pub is_active: bool,
+    pub is_memo: bool,
+    pub balance: Amount,
+    pub rolled_up_balance: Amount,
+    pub children: Vec<ChartNode>,
+}
+
+/// One account in the cached account hierarchy, without balances.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountTreeNode {
+    pub account_id: String,
+    pub account_code: String,
+    pub account_name: String,
+    pub account_type: String,
+    pub is_active: bool,
+    pub is_memo: bool,
+    pub children: Vec<AccountTreeNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationQuery {
+    pub as_of: Option<String>,
+    pub statement_balance: Decimal,
+}
+
+/// An entry not yet matched to a statement; `amount` is signed like the account balance.
+#[derive(Debug, Serialize)]
+pub struct UnreconciledEntry {
+    pub entry_id: String,
+    pub transaction_id: String,
+    pub transaction_reference: String,
+    pub transaction_date: String,
+    pub description: Option<String>,
+    pub amount: Amount,
+}
+
+/// Compares a statement balance with the reconciled part of an account.
+/// `difference` is the statement balance minus `reconciled_balance`; when the
+/// ledger is complete it equals the net of `unreconciled_entries`.
+#[derive(Debug, Serialize)]
+pub struct Reconciliation {
+    pub account_id: String,
+    pub as_of: Option<String>,
+    pub ledger_balance: Amount,
+    pub reconciled_balance: Amount,
+    pub statement_balance: Amount,
+    pub difference: Amount,
+    pub unreconciled_total: Amount,
+    pub unreconciled_entries: Vec<UnreconciledEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntriesQuery {
+    pub account_id: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    #[serde(default)]
+    pub amount_format: AmountFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrialBalanceQuery {
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LedgerQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountLedgerQuery {
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerEntry {
+    pub entry_id: String,
+    pub transaction_id: String,
+    pub transaction_reference: String,
+    pub transaction_description: String,
+    pub transaction_date: String,
+    pub posting_date: String,
+    pub debit_amount: Amount,
+    pub credit_amount: Amount,
+    pub description: Option<String>,
+    pub running_balance: Amount,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountLedger {
+    pub account_id: String,
+    pub account_code: String,
+    pub account_name: String,
+    pub account_type: String,
+    /// Balance brought forward into the window, including the account's opening balance.
+    pub opening_balance: Amount,
+    pub closing_balance: Amount,
+    /// Signed movement of the entries in the window.
+    pub net_change: Amount,
+    pub entries: Vec<LedgerEntry>,
+}
+
+/// Outcome of `GET /ready`: ready only when every check passed.
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessCheck {
+    /// `database`, `migrations` or `pool`.
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -204,6 +1099,19 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
     pub message: Option<String>,
     pub errors: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ResponseMeta>,
+}
+
+/// List metadata carried next to `data` so its type stays a plain list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseMeta {
+    pub total: i64,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// Set when the server cut the list short of `total`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
 }
 
 impl<T> ApiResponse<T> {
@@ -213,6 +1121,14 @@ impl<T> ApiResponse<T> {
             data: Some(data),
             message: None,
             errors: None,
+            meta: None,
+        }
+    }
+
+    pub fn success_with_meta(data: T, meta: ResponseMeta) -> Self {
+        Self {
+            meta: Some(meta),
+            ..Self::success(data)
         }
     }
 
@@ -222,6 +1138,7 @@ impl<T> ApiResponse<T> {
             data: None,
             message: Some(message),
             errors: None,
+            meta: None,
         }
     }
 
@@ -231,6 +1148,7 @@ impl<T> ApiResponse<T> {
             data: None,
             message: Some("Validation failed".to_string()),
             errors: Some(errors),
+            meta: None,
         }
     }
 }
@@ -244,14 +1162,23 @@ mod tests {
     fn test_account_type_conversion() {
         // Test From<String> for AccountType
         assert_eq!(AccountType::from("asset".to_string()), AccountType::Asset);
-        assert_eq!(AccountType::from("liability".to_string()), AccountType::Liability);
+        assert_eq!(
+            AccountType::from("liability".to_string()),
+            AccountType::Liability
+        );
         assert_eq!(AccountType::from("equity".to_string()), AccountType::Equity);
-        assert_eq!(AccountType::from("revenue".to_string()), AccountType::Revenue);
-        assert_eq!(AccountType::from("expense".to_string()), AccountType::Expense);
-        
+        assert_eq!(
+            AccountType::from("revenue".to_string()),
+            AccountType::Revenue
+        );
+        assert_eq!(
+            AccountType::from("expense".to_string()),
+            AccountType::Expense
+        );
+
         // Test invalid type defaults to Asset
         assert_eq!(AccountType::from("invalid".to_string()), AccountType::Asset);
-        
+
         // Test From<AccountType> for String
         assert_eq!(String::from(AccountType::Asset), "asset");
         assert_eq!(String::from(AccountType::Liability), "liability");
@@ -267,8 +1194,13 @@ mod tests {
             name: "Cash Account".to_string(),
             account_type: AccountType::Asset,
             parent_id: None,
+            is_memo: false,
+            ledger_id: None,
+            opening_balance: None,
+            opening_date: None,
+            currency: None,
         };
-        
+
         // Should pass validation
         assert!(valid_request.validate().is_ok());
 
@@ -277,8 +1209,13 @@ mod tests {
             name: "Cash Account".to_string(),
             account_type: AccountType::Asset,
             parent_id: None,
+            is_memo: false,
+            ledger_id: None,
+            opening_balance: None,
+            opening_date: None,
+            currency: None,
         };
-        
+
         // Should fail validation
         assert!(invalid_request.validate().is_err());
     }
@@ -291,12 +1228,14 @@ mod tests {
                 debit_amount: Some(Decimal::new(10000, 2)), // 100.00
                 credit_amount: None,
                 description: Some("Test debit".to_string()),
+                currency: None,
             },
             CreateEntryRequest {
                 account_id: "acc2".to_string(),
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)), // 100.00
                 description: Some("Test credit".to_string()),
+                currency: None,
             },
         ];
 
@@ -304,9 +1243,13 @@ mod tests {
             reference: "TXN-001".to_string(),
             description: "Test transaction".to_string(),
             transaction_date: None,
+            posting_date: None,
+            metadata: None,
             entries: valid_entries,
+            ledger_id: None,
+            status: None,
         };
-        
+
         // Should pass validation
         assert!(valid_request.validate().is_ok());
 
@@ -314,13 +1257,35 @@ mod tests {
             reference: "".to_string(), // Empty reference should fail
             description: "Test transaction".to_string(),
             transaction_date: None,
+            posting_date: None,
+            metadata: None,
             entries: vec![],
+            ledger_id: None,
+            status: None,
         };
-        
+
         // Should fail validation
         assert!(invalid_request.validate().is_err());
     }
 
+    #[test]
+    fn test_transaction_dates_must_be_rfc3339_or_plain_dates() {
+        for date in [
+            "2026-10-15",
+            "2026-10-15T10:00:00Z",
+            "2026-10-15T10:00:00+02:00",
+        ] {
+            assert!(validate_transaction_date(date).is_ok(), "{} rejected", date);
+        }
+        for date in ["15/10/2026", "2026-10-15 10:00", "2026-13-01", ""] {
+            assert!(
+                validate_transaction_date(date).is_err(),
+                "{} accepted",
+                date
+            );
+        }
+    }
+
     #[test]
     fn test_api_response_builders() {
         let success_response = ApiResponse::success("test data");
@@ -341,29 +1306,71 @@ mod tests {
         ]);
         assert!(!validation_response.success);
         assert!(validation_response.data.is_none());
-        assert_eq!(validation_response.message, Some("Validation failed".to_string()));
-        assert_eq!(validation_response.errors, Some(vec![
-            "field1 is required".to_string(),
-            "field2 is invalid".to_string(),
-        ]));
+        assert_eq!(
+            validation_response.message,
+            Some("Validation failed".to_string())
+        );
+        assert_eq!(
+            validation_response.errors,
+            Some(vec![
+                "field1 is required".to_string(),
+                "field2 is invalid".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_api_response_meta_serialization() {
+        let plain = serde_json::to_value(ApiResponse::success(vec![1, 2])).unwrap();
+        assert!(plain.get("meta").is_none());
+
+        let with_meta = ApiResponse::success_with_meta(
+            vec![1, 2],
+            ResponseMeta {
+                total: 10,
+                limit: Some(2),
+                offset: Some(0),
+                truncated: false,
+            },
+        );
+        let json = serde_json::to_value(with_meta).unwrap();
+        assert_eq!(json["data"], serde_json::json!([1, 2]));
+        assert_eq!(json["meta"]["total"], 10);
+        assert_eq!(json["meta"]["limit"], 2);
+        assert_eq!(json["meta"]["offset"], 0);
+        assert!(json["meta"].get("truncated").is_none());
+    }
+
+    #[test]
+    fn test_normal_side_per_account_type() {
+        assert_eq!(AccountType::Asset.normal_side(), NormalSide::Debit);
+        assert_eq!(AccountType::Expense.normal_side(), NormalSide::Debit);
+        assert_eq!(AccountType::Liability.normal_side(), NormalSide::Credit);
+        assert_eq!(AccountType::Equity.normal_side(), NormalSide::Credit);
+        assert_eq!(AccountType::Revenue.normal_side(), NormalSide::Credit);
     }
 
     #[test]
     fn test_account_balance_calculation() {
         use rust_decimal::Decimal;
-        
+
         let balance = AccountBalance {
             account_id: "test-id".to_string(),
             account_code: "1000".to_string(),
             account_name: "Test Account".to_string(),
             account_type: "asset".to_string(),
-            debit_total: Decimal::new(15000, 2), // 150.00
-            credit_total: Decimal::new(5000, 2),  // 50.00
-            balance: Decimal::new(10000, 2),      // 100.00
+            currency: "USD".to_string(),
+            opening_balance: Amount::zero(2),
+            debit_total: Amount::new(Decimal::new(15000, 2), 2), // 150.00
+            credit_total: Amount::new(Decimal::new(5000, 2), 2), // 50.00
+            balance: Amount::new(Decimal::new(10000, 2), 2),     // 100.00
+            is_memo: false,
+            normal_side: NormalSide::Debit,
+            balance_is_normal: true,
         };
 
         // For asset accounts: balance = debits - credits
-        let expected_balance = balance.debit_total - balance.credit_total;
-        assert_eq!(balance.balance, expected_balance);
+        let expected_balance = balance.debit_total.value - balance.credit_total.value;
+        assert_eq!(balance.balance.value, expected_balance);
     }
 }
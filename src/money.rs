@@ -0,0 +1,199 @@
+//! Fixed-scale rendering of monetary amounts.
+//!
+//! Entry amounts are stored as integers at `STORAGE_SCALE` decimal places, so
+//! sums are exact but can come out as `100` or `42.5`. Responses carry them as
+//! an `Amount` holding the minor-unit scale of their currency, which renders as
+//! a padded string (`"100.00"`, `"42.50"`). Entry amounts may not have more
+//! places than their currency, so padding never has to round.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Serialize, Serializer};
+
+use crate::errors::AppError;
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+pub const DEFAULT_SCALE: u32 = 2;
+
+/// Decimal places entry amounts are stored with. The columns hold integers in
+/// units of `10^-STORAGE_SCALE`, so `12.5` is stored as `125000`.
+pub const STORAGE_SCALE: u32 = 4;
+
+/// True for a three-letter ISO 4217 code such as `USD`.
+pub fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Minor-unit digits for an ISO 4217 currency code; two unless listed.
+pub fn currency_scale(currency: &str) -> u32 {
+    match currency.to_ascii_uppercase().as_str() {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => DEFAULT_SCALE,
+    }
+}
+
+/// Converts an amount to the integer stored for it, failing rather than
+/// rounding when it has more than `STORAGE_SCALE` decimal places.
+pub fn to_storage_units(amount: Decimal) -> Result<i64, AppError> {
+    if amount.normalize().scale() > STORAGE_SCALE {
+        return Err(AppError::ValidationError(format!(
+            "{} has more than {} decimal places",
+            amount, STORAGE_SCALE
+        )));
+    }
+
+    amount
+        .checked_mul(Decimal::from(10_i64.pow(STORAGE_SCALE)))
+        .and_then(|units| units.to_i64())
+        .ok_or_else(|| AppError::ValidationError(format!("{} is too large to store", amount)))
+}
+
+/// The amount a stored integer stands for, without trailing zeros.
+pub fn from_storage_units(units: i64) -> Decimal {
+    Decimal::new(units, STORAGE_SCALE).normalize()
+}
+
+/// Reads an amount kept as text, such as an account's opening balance or a
+/// period snapshot. A value that does not parse is an error naming `what`,
+/// never a silent zero.
+pub fn parse_stored(value: &str, what: impl FnOnce() -> String) -> Result<Decimal, AppError> {
+    value.parse().map_err(|_| {
+        AppError::InternalServerError(format!(
+            "stored {} '{}' is not a valid amount",
+            what(),
+            value
+        ))
+    })
+}
+
+/// Pads `amount` with trailing zeros to `scale` places. Digits past `scale`
+/// are kept rather than rounded away, so the text always equals the value.
+pub fn format_amount(amount: Decimal, scale: u32) -> String {
+    let mut padded = amount.normalize();
+    if padded.scale() < scale {
+        padded.rescale(scale);
+    }
+    padded.to_string()
+}
+
+/// Rejects an amount with more decimal places than `currency` has minor units,
+/// e.g. `100.5` in JPY, which could not be shown as it is stored.
+pub fn check_minor_units(amount: Decimal, currency: &str) -> Result<(), AppError> {
+    let scale = currency_scale(currency);
+    if amount.normalize().scale() > scale {
+        return Err(AppError::ValidationError(format!(
+            "{} has more decimal places than {} allows ({})",
+            amount, currency, scale
+        )));
+    }
+    Ok(())
+}
+
+/// A monetary amount with the decimal places of its currency, which it is
+/// rendered with: always a string such as `"100.00"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Amount {
+    pub value: Decimal,
+    pub scale: u32,
+}
+
+impl Amount {
+    pub fn new(value: Decimal, scale: u32) -> Self {
+        Self { value, scale }
+    }
+
+    pub fn zero(scale: u32) -> Self {
+        Self::new(Decimal::ZERO, scale)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_amount(self.value, self.scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_pads_to_scale() {
+        assert_eq!(format_amount(Decimal::new(100, 0), 2), "100.00");
+        assert_eq!(format_amount(Decimal::new(425, 1), 2), "42.50");
+        assert_eq!(format_amount(Decimal::ZERO, 2), "0.00");
+        assert_eq!(format_amount(Decimal::new(-5, 0), 3), "-5.000");
+    }
+
+    #[test]
+    fn test_format_keeps_extra_digits() {
+        assert_eq!(format_amount(Decimal::new(12345, 3), 2), "12.345");
+        assert_eq!(format_amount(Decimal::new(10050, 2), 0), "100.5");
+        assert_eq!(format_amount(Decimal::new(12000, 4), 2), "1.20");
+    }
+
+    #[test]
+    fn test_minor_units_checked_per_currency() {
+        assert!(check_minor_units(Decimal::new(1005, 1), "JPY").is_err());
+        assert!(check_minor_units(Decimal::new(10005, 3), "USD").is_err());
+        assert!(check_minor_units(Decimal::new(10050, 3), "USD").is_ok());
+        assert!(check_minor_units(Decimal::new(1234, 3), "KWD").is_ok());
+    }
+
+    #[test]
+    fn test_storage_units_round_trip() {
+        let sum = Decimal::new(1, 1) + Decimal::new(2, 1);
+        assert_eq!(to_storage_units(sum).unwrap(), 3000);
+        assert_eq!(from_storage_units(3000), Decimal::new(3, 1));
+        assert_eq!(
+            to_storage_units(Decimal::new(100000, 2)).unwrap(),
+            10_000_000
+        );
+        assert_eq!(from_storage_units(1), Decimal::new(1, 4));
+        assert_eq!(from_storage_units(0), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_storage_units_reject_extra_places_and_overflow() {
+        assert!(to_storage_units(Decimal::new(1, 5)).is_err());
+        assert!(to_storage_units(Decimal::MAX).is_err());
+        assert_eq!(to_storage_units(Decimal::new(10, 5)).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_unreadable_stored_amount_is_an_error() {
+        assert_eq!(
+            parse_stored("12.50", || unreachable!()).unwrap(),
+            Decimal::new(1250, 2)
+        );
+        let err = parse_stored("1O0", || "opening balance of account 1000".to_string())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("stored opening balance of account 1000 '1O0' is not a valid amount"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_amount_renders_at_its_scale() {
+        let rendered = |value, scale| serde_json::to_value(Amount::new(value, scale)).unwrap();
+
+        assert_eq!(rendered(Decimal::new(1234, 3), 3), "1.234");
+        assert_eq!(rendered(Decimal::new(100, 0), 0), "100");
+        assert_eq!(rendered(Decimal::new(12, 1), 2), "1.20");
+        assert_eq!(rendered(Decimal::ZERO, 2), "0.00");
+    }
+
+    #[test]
+    fn test_currency_scales() {
+        assert_eq!(currency_scale("USD"), 2);
+        assert_eq!(currency_scale("jpy"), 0);
+        assert_eq!(currency_scale("KWD"), 3);
+        assert_eq!(currency_scale("XYZ"), DEFAULT_SCALE);
+    }
+}
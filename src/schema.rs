@@ -10,6 +10,23 @@ diesel::table! {
         is_active -> Bool,
         created_at -> Text,
         updated_at -> Text,
+        is_memo -> Bool,
+        ledger_id -> Text,
+        opening_balance -> Text,
+        opening_date -> Nullable<Text>,
+        postable -> Bool,
+        currency -> Text,
+    }
+}
+
+diesel::table! {
+    audit_log (id) {
+        id -> Text,
+        entity_type -> Text,
+        entity_id -> Text,
+        action -> Text,
+        details -> Nullable<Text>,
+        created_at -> Text,
     }
 }
 
@@ -18,10 +35,60 @@ diesel::table! {
         id -> Text,
         transaction_id -> Text,
         account_id -> Text,
-        debit_amount -> Text,
-        credit_amount -> Text,
+        debit_amount -> BigInt,
+        credit_amount -> BigInt,
         description -> Nullable<Text>,
         created_at -> Text,
+        amount_flagged -> Bool,
+        reconciled -> Bool,
+        currency -> Text,
+    }
+}
+
+diesel::table! {
+    idempotency_keys (ledger_id, idempotency_key) {
+        ledger_id -> Text,
+        idempotency_key -> Text,
+        transaction_id -> Text,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    ledger_quotas (ledger_id) {
+        ledger_id -> Text,
+        max_accounts -> Nullable<BigInt>,
+        max_transactions -> Nullable<BigInt>,
+        updated_at -> Text,
+    }
+}
+
+diesel::table! {
+    period_snapshots (period_id, account_id) {
+        period_id -> Text,
+        account_id -> Text,
+        account_code -> Text,
+        account_name -> Text,
+        balance -> Text,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    periods (id) {
+        id -> Text,
+        name -> Text,
+        start_date -> Text,
+        end_date -> Text,
+        closed_at -> Nullable<Text>,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    sequences (name) {
+        name -> Text,
+        value -> BigInt,
     }
 }
 
@@ -33,10 +100,39 @@ diesel::table! {
         transaction_date -> Text,
         created_at -> Text,
         updated_at -> Text,
+        ledger_id -> Text,
+        reverses_transaction_id -> Nullable<Text>,
+        status -> Text,
+        voided -> Bool,
+        period_id -> Nullable<Text>,
+        posting_date -> Text,
+        metadata -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    unreadable_amounts (entry_id, field) {
+        entry_id -> Text,
+        field -> Text,
+        raw_value -> Text,
     }
 }
 
 diesel::joinable!(entries -> accounts (account_id));
 diesel::joinable!(entries -> transactions (transaction_id));
+diesel::joinable!(period_snapshots -> periods (period_id));
+diesel::joinable!(transactions -> periods (period_id));
+diesel::joinable!(unreadable_amounts -> entries (entry_id));
 
-diesel::allow_tables_to_appear_in_same_query!(accounts, entries, transactions,);
+diesel::allow_tables_to_appear_in_same_query!(
+    accounts,
+    audit_log,
+    entries,
+    idempotency_keys,
+    ledger_quotas,
+    period_snapshots,
+    periods,
+    sequences,
+    transactions,
+    unreadable_amounts,
+);
@@ -0,0 +1,231 @@
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::audit;
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::errors::AppError;
+use crate::handlers::transactions::insert_transaction;
+use crate::ids;
+use crate::models::{
+    AccountType, CreateEntryRequest, CreateTransactionRequest, NewAccount, SeedSummary,
+    DEFAULT_LEDGER_ID, OPENING_BALANCES_ACCOUNT_ID, SUSPENSE_ACCOUNT_ID,
+};
+use crate::schema::{accounts, transactions};
+
+/// (id, code, name, type) of the accounts every deployment needs.
+const SYSTEM_ACCOUNTS: &[(&str, &str, &str, AccountType)] = &[
+    (
+        OPENING_BALANCES_ACCOUNT_ID,
+        "3999",
+        "Opening Balances",
+        AccountType::Equity,
+    ),
+    (
+        SUSPENSE_ACCOUNT_ID,
+        "9999",
+        "Suspense / Uncategorized",
+        AccountType::Asset,
+    ),
+];
+
+const DEMO_ACCOUNTS: &[(&str, &str, AccountType)] = &[
+    ("1000", "Cash", AccountType::Asset),
+    ("1100", "Bank", AccountType::Asset),
+    ("1200", "Accounts Receivable", AccountType::Asset),
+    ("2000", "Accounts Payable", AccountType::Liability),
+    ("3000", "Owner's Equity", AccountType::Equity),
+    ("4000", "Sales Revenue", AccountType::Revenue),
+    ("5000", "Rent Expense", AccountType::Expense),
+    ("5100", "Office Supplies", AccountType::Expense),
+];
+
+/// (reference, description, debit code, credit code, amount in cents)
+const DEMO_TRANSACTIONS: &[(&str, &str, &str, &str, i64)] = &[
+    ("DEMO-001", "Owner investment", "1100", "3000", 1_000_000),
+    ("DEMO-002", "Cash sale", "1000", "4000", 150_000),
+    ("DEMO-003", "Office rent", "5000", "1100", 200_000),
+    ("DEMO-004", "Supplies on credit", "5100", "2000", 35_000),
+    ("DEMO-005", "Invoice to customer", "1200", "4000", 250_000),
+];
+
+/// Creates any missing system accounts under their fixed ids, in the ledger
+/// `currency`, and returns how many were created. Existing system accounts are
+/// left untouched.
+pub fn ensure_system_accounts(
+    conn: &mut SqliteConnection,
+    currency: &str,
+    clock: &dyn Clock,
+) -> Result<usize, AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        let mut created = 0;
+
+        for (id, code, name, account_type) in SYSTEM_ACCOUNTS {
+            let exists: i64 = accounts::table.find(id).count().get_result(conn)?;
+            if exists > 0 {
+                continue;
+            }
+
+            let now = clock.now_rfc3339();
+            diesel::insert_into(accounts::table)
+                .values(&NewAccount {
+                    id: id.to_string(),
+                    code: code.to_string(),
+                    name: name.to_string(),
+                    account_type: account_type.clone().into(),
+                    parent_id: None,
+                    is_active: true,
+                    created_at: now.clone(),
+                    updated_at: now,
+                    is_memo: false,
+                    ledger_id: DEFAULT_LEDGER_ID.to_string(),
+                    opening_balance: Decimal::ZERO.to_string(),
+                    opening_date: None,
+                    currency: currency.to_string(),
+                })
+                .execute(conn)?;
+            audit::record(
+                conn,
+                clock,
+                audit::ENTITY_ACCOUNT,
+                id,
+                audit::ACTION_CREATED,
+                Some(serde_json::json!({ "code": code, "system": true })),
+            )?;
+            created += 1;
+        }
+
+        Ok(created)
+    })
+}
+
+/// Gives accounts and entries recorded before currencies were tracked the
+/// ledger `currency`; entries follow their account. Returns how many rows
+/// were updated, which is zero once every row has a currency.
+pub fn backfill_currency(conn: &mut SqliteConnection, currency: &str) -> Result<usize, AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        let accounts_updated = diesel::update(accounts::table.filter(accounts::currency.eq("")))
+            .set(accounts::currency.eq(currency))
+            .execute(conn)?;
+        let entries_updated = diesel::sql_query(
+            "UPDATE entries SET currency = COALESCE(
+                 (SELECT NULLIF(currency, '') FROM accounts WHERE accounts.id = entries.account_id),
+                 ?
+             )
+             WHERE currency = ''",
+        )
+        .bind::<diesel::sql_types::Text, _>(currency)
+        .execute(conn)?;
+
+        Ok(accounts_updated + entries_updated)
+    })
+}
+
+/// Inserts the demo chart of accounts and transactions, skipping anything whose
+/// code or reference already exists so it can be run repeatedly.
+pub fn seed_demo(
+    conn: &mut SqliteConnection,
+    config: &AppConfig,
+    clock: &dyn Clock,
+) -> Result<SeedSummary, AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        let mut accounts_created = 0;
+        let mut transactions_created = 0;
+
+        for (code, name, account_type) in DEMO_ACCOUNTS {
+            let exists: i64 = accounts::table
+                .filter(accounts::ledger_id.eq(DEFAULT_LEDGER_ID))
+                .filter(accounts::code.eq(code))
+                .count()
+                .get_result(conn)?;
+            if exists > 0 {
+                continue;
+            }
+
+            let account_id = ids::new_id(conn, config, ids::ACCOUNT_PREFIX)?;
+            let now = clock.now_rfc3339();
+            diesel::insert_into(accounts::table)
+                .values(&NewAccount {
+                    id: account_id.clone(),
+                    code: code.to_string(),
+                    name: name.to_string(),
+                    account_type: account_type.clone().into(),
+                    parent_id: None,
+                    is_active: true,
+                    created_at: now.clone(),
+                    updated_at: now,
+                    is_memo: false,
+                    ledger_id: DEFAULT_LEDGER_ID.to_string(),
+                    opening_balance: Decimal::ZERO.to_string(),
+                    opening_date: None,
+                    currency: config.currency.to_ascii_uppercase(),
+                })
+                .execute(conn)?;
+            audit::record(
+                conn,
+                clock,
+                audit::ENTITY_ACCOUNT,
+                &account_id,
+                audit::ACTION_CREATED,
+                Some(serde_json::json!({ "code": code })),
+            )?;
+            accounts_created += 1;
+        }
+
+        for (reference, description, debit_code, credit_code, cents) in DEMO_TRANSACTIONS {
+            let exists: i64 = transactions::table
+                .filter(transactions::reference.eq(reference))
+                .count()
+                .get_result(conn)?;
+            if exists > 0 {
+                continue;
+            }
+
+            let amount = Decimal::new(*cents, 2);
+            let request = CreateTransactionRequest {
+                reference: reference.to_string(),
+                description: description.to_string(),
+                transaction_date: None,
+                posting_date: None,
+                metadata: None,
+                entries: vec![
+                    CreateEntryRequest {
+                        account_id: account_id_for_code(conn, debit_code)?,
+                        debit_amount: Some(amount),
+                        credit_amount: None,
+                        description: None,
+                        currency: None,
+                    },
+                    CreateEntryRequest {
+                        account_id: account_id_for_code(conn, credit_code)?,
+                        debit_amount: None,
+                        credit_amount: Some(amount),
+                        description: None,
+                        currency: None,
+                    },
+                ],
+                ledger_id: None,
+                status: None,
+            };
+
+            insert_transaction(conn, config, clock, &request)?;
+            transactions_created += 1;
+        }
+
+        Ok(SeedSummary {
+            accounts_created,
+            transactions_created,
+            account_count: accounts::table.count().get_result(conn)?,
+            transaction_count: transactions::table.count().get_result(conn)?,
+        })
+    })
+}
+
+fn account_id_for_code(conn: &mut SqliteConnection, code: &str) -> Result<String, AppError> {
+    Ok(accounts::table
+        .filter(accounts::ledger_id.eq(DEFAULT_LEDGER_ID))
+        .filter(accounts::code.eq(code))
+        .order(accounts::is_active.desc())
+        .select(accounts::id)
+        .first(conn)?)
+}
@@ -0,0 +1,1259 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+use double_rust_ledger::code_ranges;
+use double_rust_ledger::config::AppConfig;
+use regex::Regex;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn test_code_change_allowed_by_default() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+
+    let (status, body) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", cash),
+        json!({ "code": "1001" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["code"], "1001");
+}
+
+#[actix_rt::test]
+async fn test_code_change_rejected_when_codes_are_immutable() {
+    let pool = test_pool();
+    let config = AppConfig {
+        immutable_codes: true,
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let uri = format!("/api/v1/accounts/{}", cash);
+
+    let (status, _) = put_json(&app, &uri, json!({ "code": "1001" })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Resending the current code or changing other fields is still allowed
+    let (status, body) =
+        put_json(&app, &uri, json!({ "code": "1000", "name": "Petty Cash" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["code"], "1000");
+    assert_eq!(body["data"]["name"], "Petty Cash");
+}
+
+#[actix_rt::test]
+async fn test_system_accounts_created_once() {
+    use double_rust_ledger::clock::SystemClock;
+    use double_rust_ledger::models::{OPENING_BALANCES_ACCOUNT_ID, SUSPENSE_ACCOUNT_ID};
+    use double_rust_ledger::seed::ensure_system_accounts;
+
+    let pool = test_pool();
+    let mut conn = pool.get().unwrap();
+    assert_eq!(
+        ensure_system_accounts(&mut conn, "EUR", &SystemClock).unwrap(),
+        2
+    );
+    assert_eq!(
+        ensure_system_accounts(&mut conn, "EUR", &SystemClock).unwrap(),
+        0
+    );
+    drop(conn);
+
+    let app = init_app!(pool);
+
+    let (status, body) = get_json(
+        &app,
+        &format!("/api/v1/accounts/{}", OPENING_BALANCES_ACCOUNT_ID),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["account_type"], "equity");
+    assert_eq!(body["data"]["currency"], "EUR");
+
+    let (status, body) = get_json(&app, &format!("/api/v1/accounts/{}", SUSPENSE_ACCOUNT_ID)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["code"], "9999");
+
+    let (_, body) = get_json(&app, "/api/v1/accounts").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn test_noop_update_keeps_updated_at() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let id = create_account(&app, "1000", "Cash", "asset").await;
+    let uri = format!("/api/v1/accounts/{}", id);
+    let (_, before) = get_json(&app, &uri).await;
+
+    let (status, body) = put_json(
+        &app,
+        &uri,
+        json!({ "code": "1000", "name": "Cash", "account_type": "asset", "is_active": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["updated_at"], before["data"]["updated_at"]);
+
+    let (_, audit) = get_json(&app, "/api/v1/admin/audit?action=updated").await;
+    assert_eq!(audit["data"]["total"], 0);
+
+    let (_, body) = put_json(&app, &uri, json!({ "name": "Petty Cash" })).await;
+    assert_eq!(body["data"]["name"], "Petty Cash");
+    assert_ne!(body["data"]["updated_at"], before["data"]["updated_at"]);
+
+    let (_, audit) = get_json(&app, "/api/v1/admin/audit?action=updated").await;
+    assert_eq!(audit["data"]["total"], 1);
+}
+
+#[actix_rt::test]
+async fn test_modified_since_returns_only_later_changes() {
+    use chrono::{SecondsFormat, Utc};
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    create_account(&app, "2000", "Payables", "liability").await;
+
+    let cutoff = Utc::now().to_rfc3339_opts(SecondsFormat::Nanos, true);
+
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", cash),
+        json!({ "name": "Petty Cash" }),
+    )
+    .await;
+
+    let (status, body) =
+        get_json(&app, &format!("/api/v1/accounts?modified_since={}", cutoff)).await;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec![revenue.as_str(), cash.as_str()]);
+
+    let (status, _) = get_json(&app, "/api/v1/accounts?modified_since=yesterday").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_routes_resolve_under_custom_base_path() {
+    let pool = test_pool();
+    let config = AppConfig {
+        api_base_path: "/ledger/api".to_string(),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let resp = actix_web::test::call_service(
+        &app,
+        actix_web::test::TestRequest::post()
+            .uri("/ledger/api/accounts")
+            .set_json(json!({ "code": "1000", "name": "Cash", "account_type": "asset" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let location = resp
+        .headers()
+        .get("location")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(location.starts_with("/ledger/api/accounts/"));
+
+    let (status, body) = get_json(&app, &location).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["code"], "1000");
+
+    let (status, _) = get_json(&app, "/api/v1/accounts").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = get_json(&app, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_non_json_body_rejected_with_415() {
+    use actix_web::test::TestRequest;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let req = TestRequest::post()
+        .uri("/api/v1/accounts")
+        .insert_header(("content-type", "application/x-www-form-urlencoded"))
+        .set_payload("code=1000&name=Cash&account_type=asset")
+        .to_request();
+    let (status, body) = send(&app, req).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["message"], "expected application/json");
+
+    let req = TestRequest::post()
+        .uri("/api/v1/accounts")
+        .set_payload(r#"{"code":"1000","name":"Cash","account_type":"asset"}"#)
+        .to_request();
+    let (status, _) = send(&app, req).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[actix_rt::test]
+async fn test_content_type_not_checked_when_enforcement_disabled() {
+    use actix_web::test::TestRequest;
+
+    let pool = test_pool();
+    let config = AppConfig {
+        require_json_content_type: false,
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let req = TestRequest::post()
+        .uri("/api/v1/accounts")
+        .set_payload(r#"{"code":"1000","name":"Cash","account_type":"asset"}"#)
+        .to_request();
+    let (status, _) = send(&app, req).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let req = TestRequest::post()
+        .uri("/api/v1/accounts")
+        .insert_header(("content-type", "text/plain"))
+        .set_payload(r#"{"code":"1100","name":"Bank","account_type":"asset"}"#)
+        .to_request();
+    let (status, _) = send(&app, req).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[actix_rt::test]
+async fn test_bulk_update_applies_every_item() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts/bulk-update",
+        json!({
+            "updates": [
+                { "id": cash, "name": "Petty Cash" },
+                { "id": bank, "name": "Bank" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"][0]["status"], "updated");
+    assert_eq!(body["data"][1]["status"], "unchanged");
+
+    let (_, account) = get_json(&app, &format!("/api/v1/accounts/{}", cash)).await;
+    assert_eq!(account["data"]["name"], "Petty Cash");
+}
+
+#[actix_rt::test]
+async fn test_bulk_update_rolls_back_on_type_change_guard() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    post_transfer(&app, "BULK-1", &cash, &revenue, "10.00").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts/bulk-update",
+        json!({
+            "updates": [
+                { "id": bank, "name": "Main Bank" },
+                { "id": cash, "account_type": "expense" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let results = body["data"].as_array().unwrap();
+    assert_eq!(results[0]["id"], bank);
+    assert_eq!(results[0]["status"], "skipped");
+    assert_eq!(results[1]["id"], cash);
+    assert_eq!(results[1]["status"], "failed");
+    assert!(results[1]["errors"][0]
+        .as_str()
+        .unwrap()
+        .contains("its account type cannot be changed"));
+
+    let (_, account) = get_json(&app, &format!("/api/v1/accounts/{}", bank)).await;
+    assert_eq!(account["data"]["name"], "Bank");
+    let (_, account) = get_json(&app, &format!("/api/v1/accounts/{}", cash)).await;
+    assert_eq!(account["data"]["account_type"], "asset");
+}
+
+#[actix_rt::test]
+async fn test_type_change_rejected_once_account_has_entries() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    post_transfer(&app, "RETYPE-1", &cash, &revenue, "10.00").await;
+
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", cash),
+        json!({ "account_type": "expense" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", bank),
+        json!({ "account_type": "expense" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_codes_unique_among_active_accounts_only() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let body = json!({ "code": "1000", "name": "Cash", "account_type": "asset" });
+    let mut archived = Vec::new();
+    for _ in 0..2 {
+        let id = create_account_with(&app, body.clone()).await;
+        let (status, _) = put_json(
+            &app,
+            &format!("/api/v1/accounts/{}", id),
+            json!({ "is_active": false }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        archived.push(id);
+    }
+
+    // Both archived accounts keep code 1000 and the code is free for a new one
+    create_account_with(&app, body.clone()).await;
+
+    let (status, _) = post_json(&app, "/api/v1/accounts", body).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", archived[0]),
+        json!({ "is_active": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", archived[0]),
+        json!({ "code": "1001", "is_active": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["code"], "1001");
+}
+
+#[actix_rt::test]
+async fn test_codes_unique_within_each_ledger() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    create_account(&app, "1000", "Cash", "asset").await;
+    let acme_cash = create_account_with(
+        &app,
+        json!({ "code": "1000", "name": "Acme Cash", "account_type": "asset", "ledger_id": "acme" }),
+    )
+    .await;
+
+    let (status, body) = get_json(&app, &format!("/api/v1/accounts/{}", acme_cash)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["ledger_id"], "acme");
+
+    let (status, _) = post_json(
+        &app,
+        "/api/v1/accounts",
+        json!({ "code": "1000", "name": "Acme Till", "account_type": "asset", "ledger_id": "acme" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[actix_rt::test]
+async fn test_changing_code_to_one_in_use_conflicts() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts",
+        json!({ "code": "1000", "name": "Petty cash", "account_type": "asset" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["message"],
+        "Account code 1000 is already used by an active account"
+    );
+
+    let uri = format!("/api/v1/accounts/{}", bank);
+    let (status, body) = put_json(&app, &uri, json!({ "code": "1000" })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["message"],
+        "Account code 1000 is already used by an active account"
+    );
+
+    // Keeping its own code is not a clash
+    let (status, _) = put_json(&app, &uri, json!({ "code": "1100", "name": "Main bank" })).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_accounts_sort_by_each_key() {
+    use chrono::{Duration, TimeZone, Utc};
+    use double_rust_ledger::clock::FixedClock;
+    use std::sync::Arc;
+
+    let pool = test_pool();
+    let clock = Arc::new(FixedClock::new(
+        Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap(),
+    ));
+    let app = init_app!(pool, Default::default(), clock.clone());
+
+    let mut ids = Vec::new();
+    for (code, name, account_type) in [
+        ("3000", "Bravo", "equity"),
+        ("1000", "Charlie", "asset"),
+        ("2000", "Alpha", "liability"),
+    ] {
+        ids.push(create_account(&app, code, name, account_type).await);
+        clock.advance(Duration::minutes(1));
+    }
+    let (a, b, c) = (&ids[0], &ids[1], &ids[2]);
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", a),
+        json!({ "name": "Bravo 2" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for (sort_by, expected) in [
+        ("code", [b, c, a]),
+        ("name", [c, a, b]),
+        ("account_type", [b, a, c]),
+        ("created_at", [a, b, c]),
+        ("updated_at", [b, c, a]),
+    ] {
+        let (status, body) = get_json(&app, &format!("/api/v1/accounts?sort_by={}", sort_by)).await;
+        assert_eq!(status, StatusCode::OK);
+        let listed: Vec<&str> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|account| account["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(listed, expected, "sort_by={}", sort_by);
+
+        let (_, body) = get_json(
+            &app,
+            &format!("/api/v1/accounts?sort_by={}&order=desc", sort_by),
+        )
+        .await;
+        assert_eq!(body["data"][0]["id"], expected[2].as_str());
+    }
+
+    let (status, _) = get_json(&app, "/api/v1/accounts?sort_by=balance").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_json(&app, "/api/v1/accounts?sort_by=code&order=sideways").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_chart_rolls_balances_up_to_parents() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let current = create_account(&app, "1000", "Current Assets", "asset").await;
+    let with_parent = |code: &str, name: &str, account_type: &str, parent: &str| json!({ "code": code, "name": name, "account_type": account_type, "parent_id": parent });
+    let cash = create_account_with(&app, with_parent("1100", "Cash", "asset", &current)).await;
+    let bank = create_account_with(&app, with_parent("1200", "Bank", "asset", &current)).await;
+    let savings = create_account_with(&app, with_parent("1210", "Savings", "asset", &bank)).await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for (reference, debit, amount, date) in [
+        ("SALE-1", &cash, "100.00", "2024-01-10"),
+        ("SALE-2", &savings, "50.00", "2024-01-20"),
+        ("SALE-3", &bank, "25.00", "2024-02-05"),
+    ] {
+        let mut body = transfer_body(reference, debit, &revenue, amount);
+        body["transaction_date"] = json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, body) = get_json(&app, "/api/v1/accounts/chart").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let roots = body["data"].as_array().unwrap();
+    assert_eq!(roots.len(), 2);
+    let current_node = &roots[0];
+    assert_eq!(current_node["account_code"], "1000");
+    assert_eq!(current_node["balance"], "0.00");
+    assert_eq!(current_node["rolled_up_balance"], "175.00");
+
+    let bank_node = &current_node["children"][1];
+    assert_eq!(bank_node["account_code"], "1200");
+    assert_eq!(bank_node["balance"], "25.00");
+    assert_eq!(bank_node["rolled_up_balance"], "75.00");
+    assert_eq!(bank_node["children"][0]["rolled_up_balance"], "50.00");
+    assert_eq!(roots[1]["rolled_up_balance"], "175.00");
+
+    let (_, body) = get_json(&app, "/api/v1/accounts/chart?as_of=2024-01-31").await;
+    assert_eq!(body["data"][0]["rolled_up_balance"], "150.00");
+    assert_eq!(body["data"][0]["children"][1]["rolled_up_balance"], "50.00");
+}
+
+#[actix_rt::test]
+async fn test_code_format_enforced() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    for code in ["1000", "1000.01", "A-100", "cash_1"] {
+        let (status, body) = post_json(
+            &app,
+            "/api/v1/accounts",
+            json!({ "code": code, "name": "Valid", "account_type": "asset" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{}: {}", code, body);
+    }
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts",
+        json!({ "code": "10 00", "name": "Spaced", "account_type": "asset" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Validation failed");
+    assert_eq!(
+        body["errors"],
+        json!([
+            "code: must not contain whitespace or control characters",
+            "code: '10 00' does not match the required format ^[A-Za-z0-9]+([._-][A-Za-z0-9]+)*$"
+        ])
+    );
+
+    for code in ["1000!", ".1000", "1000.", "10/00"] {
+        let (status, body) = post_json(
+            &app,
+            "/api/v1/accounts",
+            json!({ "code": code, "name": "Invalid", "account_type": "asset" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", code);
+        assert!(
+            body["errors"][0]
+                .as_str()
+                .unwrap()
+                .contains("does not match the required format"),
+            "{}: {}",
+            code,
+            body
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_validation_errors_name_the_field() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts",
+        json!({ "code": "", "name": "Cash", "account_type": "asset" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Validation failed");
+    let errors = body["errors"].as_array().unwrap();
+    assert!(!errors.is_empty());
+    assert!(
+        errors
+            .iter()
+            .all(|e| e.as_str().unwrap().starts_with("code: ")),
+        "{}",
+        body
+    );
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let (status, body) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", cash),
+        json!({ "code": "", "name": "" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let errors: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e.as_str().unwrap())
+        .collect();
+    assert!(
+        errors.iter().any(|e| e.starts_with("code: ")),
+        "{:?}",
+        errors
+    );
+    assert!(
+        errors.iter().any(|e| e.starts_with("name: ")),
+        "{:?}",
+        errors
+    );
+}
+
+#[actix_rt::test]
+async fn test_code_format_is_configurable() {
+    let pool = test_pool();
+    let config = AppConfig {
+        account_code_pattern: Regex::new(r"^[0-9]{4}(\.[0-9]{2})?$").unwrap(),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    create_account(&app, "1000.01", "Petty Cash", "asset").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts",
+        json!({ "code": "A-100", "name": "Lettered", "account_type": "asset" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["errors"],
+        json!([r"code: 'A-100' does not match the required format ^[0-9]{4}(\.[0-9]{2})?$"])
+    );
+
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", cash),
+        json!({ "code": "100" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_codes_checked_against_type_ranges() {
+    let pool = test_pool();
+    let config = AppConfig {
+        account_code_ranges: code_ranges::parse(
+            "asset=1,liability=2,equity=3,revenue=4000-4999,expense=5|6",
+        )
+        .unwrap(),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    for (account_type, in_range, out_of_range) in [
+        ("asset", "1000", "2000"),
+        ("liability", "2100", "1100"),
+        ("equity", "3000", "4000"),
+        ("revenue", "4100", "40000"),
+        ("expense", "6100", "7100"),
+    ] {
+        let (status, _) = post_json(
+            &app,
+            "/api/v1/accounts",
+            json!({ "code": in_range, "name": "In range", "account_type": account_type }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{} {}", account_type, in_range);
+
+        let (status, body) = post_json(
+            &app,
+            "/api/v1/accounts",
+            json!({ "code": out_of_range, "name": "Out of range", "account_type": account_type }),
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::BAD_REQUEST,
+            "{} {}",
+            account_type,
+            out_of_range
+        );
+        assert!(body["message"]
+            .as_str()
+            .unwrap()
+            .contains("is outside the range"));
+    }
+
+    let (_, accounts) = get_json(&app, "/api/v1/accounts?sort_by=code").await;
+    let cash = accounts["data"][0]["id"].as_str().unwrap().to_string();
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", cash),
+        json!({ "code": "9000" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_sync_creates_updates_and_deactivates() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let payables = create_account(&app, "2000", "Payables", "liability").await;
+
+    let payload = json!({
+        "accounts": [
+            { "code": "1000", "name": "Cash", "account_type": "asset" },
+            { "code": "1100", "name": "Bank Account", "account_type": "asset" },
+            { "code": "4000", "name": "Sales", "account_type": "revenue" }
+        ],
+        "deactivate_missing": true
+    });
+
+    let (status, body) = post_json(&app, "/api/v1/accounts/sync", payload.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["data"],
+        json!({ "created": 1, "updated": 1, "unchanged": 1, "deactivated": 1 })
+    );
+
+    let (_, account) = get_json(&app, &format!("/api/v1/accounts/{}", bank)).await;
+    assert_eq!(account["data"]["name"], "Bank Account");
+    let (_, account) = get_json(&app, &format!("/api/v1/accounts/{}", payables)).await;
+    assert_eq!(account["data"]["is_active"], false);
+    let (_, account) = get_json(&app, &format!("/api/v1/accounts/{}", cash)).await;
+    assert_eq!(account["data"]["is_active"], true);
+
+    // Replaying the same payload changes nothing
+    let (status, body) = post_json(&app, "/api/v1/accounts/sync", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["data"],
+        json!({ "created": 0, "updated": 0, "unchanged": 3, "deactivated": 0 })
+    );
+}
+
+#[actix_rt::test]
+async fn test_sync_rejects_duplicate_codes_without_writing() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts/sync",
+        json!({
+            "accounts": [
+                { "code": "1000", "name": "Cash", "account_type": "asset" },
+                { "code": "1000", "name": "Petty Cash", "account_type": "asset" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "account 1: code 1000 appears more than once"
+    );
+
+    let (_, accounts) = get_json(&app, "/api/v1/accounts").await;
+    assert_eq!(accounts["data"].as_array().unwrap().len(), 0);
+}
+
+#[actix_rt::test]
+async fn test_validate_reports_cycles_and_duplicates_without_writing() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts/validate",
+        json!({
+            "accounts": [
+                { "code": "1100", "name": "Bank", "account_type": "asset", "parent_id": "1000" },
+                { "code": "2000", "name": "Payables", "account_type": "liability", "parent_id": "2100" },
+                { "code": "2100", "name": "Trade", "account_type": "liability", "parent_id": "2000" },
+                { "code": "4000", "name": "Sales", "account_type": "revenue" },
+                { "code": "4000", "name": "Other", "account_type": "revenue", "parent_id": "9999" },
+                { "code": "1000", "name": "Cash Again", "account_type": "asset" },
+                { "code": "1200", "name": "Till", "account_type": "asset", "parent_id": cash }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let report = &body["data"];
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["duplicate_codes"], json!(["4000"]));
+    assert_eq!(report["cycles"], json!([["2000", "2100"]]));
+
+    let errors = |index: usize| &report["accounts"][index]["errors"];
+    assert_eq!(errors(0), &json!([]));
+    assert_eq!(errors(3), &json!([]));
+    assert_eq!(errors(4), &json!(["parent 9999 not found"]));
+    assert_eq!(
+        errors(5),
+        &json!(["Account code 1000 is already used by an active account"])
+    );
+    // Parents are named by code, so an account id does not resolve
+    assert_eq!(errors(6), &json!([format!("parent {} not found", cash)]));
+
+    let (_, accounts) = get_json(&app, "/api/v1/accounts").await;
+    assert_eq!(accounts["data"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn test_validate_accepts_clean_chart() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts/validate",
+        json!({
+            "accounts": [
+                { "code": "1000", "name": "Assets", "account_type": "asset" },
+                { "code": "1100", "name": "Bank", "account_type": "asset", "parent_id": "1000" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["valid"], true);
+    assert_eq!(body["data"]["cycles"], json!([]));
+}
+
+#[actix_rt::test]
+async fn test_account_tree_cache_is_invalidated_by_account_changes() {
+    use diesel::prelude::*;
+    use double_rust_ledger::schema::accounts;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let assets = create_account(&app, "1000", "Assets", "asset").await;
+    let (status, body) = get_json(&app, "/api/v1/accounts/tree").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+    // Written behind the API's back, so the cached tree does not notice
+    let mut conn = pool.get().unwrap();
+    diesel::update(accounts::table.find(&assets))
+        .set(accounts::name.eq("Renamed directly"))
+        .execute(&mut conn)
+        .unwrap();
+    let (_, body) = get_json(&app, "/api/v1/accounts/tree").await;
+    assert_eq!(body["data"][0]["account_name"], "Assets");
+
+    let bank = create_account_with(
+        &app,
+        json!({ "code": "1100", "name": "Bank", "account_type": "asset", "parent_id": assets }),
+    )
+    .await;
+    let (_, body) = get_json(&app, "/api/v1/accounts/tree").await;
+    let root = &body["data"][0];
+    assert_eq!(root["account_name"], "Renamed directly");
+    assert_eq!(root["children"][0]["account_id"], bank);
+
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", bank),
+        json!({ "name": "Main Bank" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = get_json(&app, "/api/v1/accounts/tree").await;
+    assert_eq!(body["data"][0]["children"][0]["account_name"], "Main Bank");
+}
+
+#[actix_rt::test]
+async fn test_account_list_pages() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    for n in 0..120 {
+        let code = format!("{}", 1000 + n);
+        create_account(&app, &code, &format!("Account {}", code), "asset").await;
+    }
+
+    let (status, body) = get_json(
+        &app,
+        "/api/v1/accounts?sort_by=code&order=asc&limit=50&offset=50",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let codes: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|account| account["code"].as_str().unwrap())
+        .collect();
+    let expected: Vec<String> = (1050..1100).map(|code| code.to_string()).collect();
+    assert_eq!(codes, expected);
+    assert_eq!(
+        body["meta"],
+        json!({ "total": 120, "limit": 50, "offset": 50 })
+    );
+
+    let (_, body) = get_json(&app, "/api/v1/accounts?sort_by=code&offset=100").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 20);
+    assert_eq!(body["data"][0]["code"], "1100");
+
+    // Without paging parameters the first page is returned, still with the total
+    let (_, body) = get_json(&app, "/api/v1/accounts?sort_by=code").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 50);
+    assert_eq!(body["data"][0]["code"], "1000");
+    assert_eq!(
+        body["meta"],
+        json!({ "total": 120, "limit": 50, "offset": 0 })
+    );
+
+    let (status, _) = get_json(&app, "/api/v1/accounts?limit=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_json(&app, "/api/v1/accounts?limit=501").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_parent_must_exist_and_not_loop() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts",
+        json!({
+            "code": "1100",
+            "name": "Bank",
+            "account_type": "asset",
+            "parent_id": "no-such-account"
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Parent account does not exist");
+
+    let assets = create_account(&app, "1000", "Assets", "asset").await;
+    let bank = create_account_with(
+        &app,
+        json!({ "code": "1100", "name": "Bank", "account_type": "asset", "parent_id": assets }),
+    )
+    .await;
+
+    let (status, body) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", assets),
+        json!({ "parent_id": assets }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "An account cannot be its own parent");
+
+    // Moving an account under its own child would detach both from the root
+    let (status, body) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", assets),
+        json!({ "parent_id": bank }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Circular account hierarchy detected");
+
+    let (status, body) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", bank),
+        json!({ "parent_id": "no-such-account" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Parent account does not exist");
+}
+
+#[actix_rt::test]
+async fn test_parent_cycles_rejected() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    // a <- b <- c
+    let a = create_account(&app, "1000", "A", "asset").await;
+    let b = create_account_with(
+        &app,
+        json!({ "code": "1100", "name": "B", "account_type": "asset", "parent_id": a }),
+    )
+    .await;
+    let c = create_account_with(
+        &app,
+        json!({ "code": "1110", "name": "C", "account_type": "asset", "parent_id": b }),
+    )
+    .await;
+
+    for (child, parent) in [(&a, &b), (&a, &c), (&b, &c)] {
+        let (status, body) = put_json(
+            &app,
+            &format!("/api/v1/accounts/{}", child),
+            json!({ "parent_id": parent }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], "Circular account hierarchy detected");
+    }
+
+    let (_, body) = get_json(&app, &format!("/api/v1/accounts/{}", a)).await;
+    assert!(body["data"]["parent_id"].is_null());
+
+    // Reparenting to an unrelated branch is fine
+    let d = create_account(&app, "1200", "D", "asset").await;
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", c),
+        json!({ "parent_id": d }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_delete_deactivates_unless_hard() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let unused = create_account(&app, "5000", "Unused", "expense").await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "10.00").await;
+
+    // Soft delete keeps the account and its entries
+    let (status, _) = delete(&app, &format!("/api/v1/accounts/{}", cash)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, body) = get_json(&app, &format!("/api/v1/accounts/{}", cash)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["is_active"], false);
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["balance"], "10.00");
+
+    let (status, body) = delete(&app, &format!("/api/v1/accounts/{}?hard=true", revenue)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Account 4000 has entries and cannot be deleted; deactivate it instead"
+    );
+    let (status, _) = get_json(&app, &format!("/api/v1/accounts/{}", revenue)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = delete(&app, &format!("/api/v1/accounts/{}?hard=true", unused)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = get_json(&app, &format!("/api/v1/accounts/{}", unused)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = delete(&app, "/api/v1/accounts/missing").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn test_hard_delete_refuses_accounts_with_children() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let assets = create_account(&app, "1000", "Current Assets", "asset").await;
+    let cash = create_account_with(
+        &app,
+        json!({ "code": "1010", "name": "Cash", "account_type": "asset", "parent_id": assets }),
+    )
+    .await;
+
+    let (status, body) = delete(&app, &format!("/api/v1/accounts/{}?hard=true", assets)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["message"],
+        "Account 1000 has child accounts and cannot be deleted"
+    );
+
+    // Once the child is gone the parent can go too
+    let (status, _) = delete(&app, &format!("/api/v1/accounts/{}?hard=true", cash)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = delete(&app, &format!("/api/v1/accounts/{}?hard=true", assets)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[actix_rt::test]
+async fn test_system_accounts_cannot_be_deleted() {
+    use double_rust_ledger::clock::SystemClock;
+    use double_rust_ledger::models::SYSTEM_ACCOUNT_IDS;
+    use double_rust_ledger::seed::ensure_system_accounts;
+
+    let pool = test_pool();
+    ensure_system_accounts(&mut pool.get().unwrap(), "USD", &SystemClock).unwrap();
+    let app = init_app!(pool);
+
+    for id in SYSTEM_ACCOUNT_IDS {
+        for uri in [
+            format!("/api/v1/accounts/{}", id),
+            format!("/api/v1/accounts/{}?hard=true", id),
+        ] {
+            let (status, body) = delete(&app, &uri).await;
+            assert_eq!(status, StatusCode::CONFLICT, "{}", uri);
+            assert!(
+                body["message"]
+                    .as_str()
+                    .unwrap()
+                    .ends_with("is a system account and cannot be deleted or deactivated"),
+                "{}",
+                body
+            );
+        }
+
+        let (_, body) = get_json(&app, &format!("/api/v1/accounts/{}", id)).await;
+        assert_eq!(body["data"]["is_active"], true);
+    }
+}
+
+#[actix_rt::test]
+async fn test_system_accounts_cannot_be_disabled_by_update() {
+    use double_rust_ledger::clock::SystemClock;
+    use double_rust_ledger::models::SUSPENSE_ACCOUNT_ID;
+    use double_rust_ledger::seed::ensure_system_accounts;
+
+    let pool = test_pool();
+    ensure_system_accounts(&mut pool.get().unwrap(), "USD", &SystemClock).unwrap();
+    let app = init_app!(pool);
+
+    let uri = format!("/api/v1/accounts/{}", SUSPENSE_ACCOUNT_ID);
+    for update in [
+        json!({ "is_active": false }),
+        json!({ "postable": false }),
+        json!({ "is_memo": true }),
+        json!({ "account_type": "expense" }),
+    ] {
+        let (status, body) = put_json(&app, &uri, update.clone()).await;
+        assert_eq!(status, StatusCode::CONFLICT, "{}: {}", update, body);
+    }
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts/bulk-update",
+        json!({ "updates": [{ "id": SUSPENSE_ACCOUNT_ID, "is_active": false }] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["data"][0]["status"], "failed");
+
+    // Renaming is harmless
+    let (status, _) = put_json(&app, &uri, json!({ "name": "Unsorted" })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = get_json(&app, &uri).await;
+    assert_eq!(body["data"]["is_active"], true);
+    assert_eq!(body["data"]["postable"], true);
+}
+
+#[actix_rt::test]
+async fn test_account_ledger_running_balance() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let rent = create_account(&app, "5000", "Rent", "expense").await;
+
+    for (reference, date, debit, credit, amount) in [
+        ("SALE-1", "2024-01-05T10:00:00Z", &cash, &revenue, "100.00"),
+        ("RENT-1", "2024-02-01T10:00:00Z", &rent, &cash, "30.00"),
+        ("SALE-2", "2024-03-10T10:00:00Z", &cash, &revenue, "5.00"),
+    ] {
+        let mut body = transfer_body(reference, debit, credit, amount);
+        body["transaction_date"] = json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, body) = get_json(&app, &format!("/api/v1/accounts/{}/ledger", cash)).await;
+    assert_eq!(status, StatusCode::OK);
+    let ledger = &body["data"];
+    let rows: Vec<(&str, &str, &str)> = ledger["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| {
+            (
+                row["transaction_reference"].as_str().unwrap(),
+                row["transaction_description"].as_str().unwrap(),
+                row["running_balance"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("SALE-1", "Transfer SALE-1", "100.00"),
+            ("RENT-1", "Transfer RENT-1", "70.00"),
+            ("SALE-2", "Transfer SALE-2", "75.00"),
+        ]
+    );
+    assert_eq!(ledger["closing_balance"], "75.00");
+
+    // Credits raise a revenue account's balance
+    let (_, body) = get_json(&app, &format!("/api/v1/accounts/{}/ledger", revenue)).await;
+    assert_eq!(body["data"]["entries"][1]["running_balance"], "105.00");
+
+    // Earlier entries are brought forward into the opening balance
+    let (_, body) = get_json(
+        &app,
+        &format!(
+            "/api/v1/accounts/{}/ledger?from_date=2024-02-01&to_date=2024-02-29",
+            cash
+        ),
+    )
+    .await;
+    let ledger = &body["data"];
+    assert_eq!(ledger["opening_balance"], "100.00");
+    assert_eq!(ledger["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(ledger["entries"][0]["running_balance"], "70.00");
+    assert_eq!(ledger["closing_balance"], "70.00");
+
+    let (status, _) = get_json(&app, "/api/v1/accounts/missing/ledger").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get_json(
+        &app,
+        &format!("/api/v1/accounts/{}/ledger?from_date=soon", cash),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
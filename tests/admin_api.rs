@@ -0,0 +1,212 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+use diesel::prelude::*;
+use double_rust_ledger::schema::{entries, unreadable_amounts};
+use serde_json::json;
+
+#[actix_rt::test]
+async fn test_corrupt_amounts_are_listed_until_resolved() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let transaction = post_transfer(&app, "SALE-1", &cash, &revenue, "100.00").await;
+
+    let (_, clean) = get_json(&app, "/api/v1/admin/corrupt-amounts").await;
+    assert_eq!(clean["data"].as_array().unwrap().len(), 0);
+
+    let entry_id = transaction["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["account_id"] == cash.as_str())
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // What the amount conversion leaves behind for text it could not read
+    let mut conn = pool.get().unwrap();
+    diesel::update(entries::table.find(&entry_id))
+        .set((
+            entries::debit_amount.eq(0),
+            entries::amount_flagged.eq(true),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(unreadable_amounts::table)
+        .values((
+            unreadable_amounts::entry_id.eq(&entry_id),
+            unreadable_amounts::field.eq("debit_amount"),
+            unreadable_amounts::raw_value.eq("1O0.00"),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+    let (status, report) = get_json(&app, "/api/v1/admin/corrupt-amounts").await;
+    assert_eq!(status, StatusCode::OK);
+    let found = report["data"].as_array().unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["entry_id"], entry_id.as_str());
+    assert_eq!(found[0]["transaction_id"], transaction["id"]);
+    assert_eq!(found[0]["field"], "debit_amount");
+    assert_eq!(found[0]["raw_value"], "1O0.00");
+
+    // Totals would count the unreadable amount as zero, so they refuse instead
+    for uri in [
+        format!("/api/v1/balance/{}", cash),
+        "/api/v1/balance".to_string(),
+        "/api/v1/reports/trial-balance".to_string(),
+        format!("/api/v1/accounts/{}/ledger", cash),
+    ] {
+        let (status, body) = get_json(&app, &uri).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{}", uri);
+        assert_eq!(
+            body["message"],
+            format!(
+                "entry {} has an unreadable amount; review it through /admin/corrupt-amounts",
+                entry_id
+            )
+        );
+    }
+    // Accounts without flagged entries still report
+    let (status, _) = get_json(&app, &format!("/api/v1/balance/{}", revenue)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let resolve = format!("/api/v1/admin/corrupt-amounts/{}/resolve", entry_id);
+    let (status, _) = post_json(&app, &resolve, json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, after) = get_json(&app, "/api/v1/admin/corrupt-amounts").await;
+    assert_eq!(after["data"].as_array().unwrap().len(), 0);
+    let flagged: bool = entries::table
+        .find(&entry_id)
+        .select(entries::amount_flagged)
+        .first(&mut conn)
+        .unwrap();
+    assert!(!flagged);
+
+    let (status, _) = post_json(&app, &resolve, json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn test_seed_demo_is_idempotent() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        dev_endpoints: true,
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let (status, first) = post_json(&app, "/api/v1/admin/seed-demo", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(first["data"]["accounts_created"].as_u64().unwrap() > 0);
+    assert!(first["data"]["transactions_created"].as_u64().unwrap() > 0);
+
+    let (_, second) = post_json(&app, "/api/v1/admin/seed-demo", json!({})).await;
+    assert_eq!(second["data"]["accounts_created"], 0);
+    assert_eq!(second["data"]["transactions_created"], 0);
+    assert_eq!(
+        second["data"]["account_count"],
+        first["data"]["account_count"]
+    );
+    assert_eq!(
+        second["data"]["transaction_count"],
+        first["data"]["transaction_count"]
+    );
+
+    let (_, trial) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(trial["data"]["is_balanced"], true);
+}
+
+#[actix_rt::test]
+async fn test_seed_demo_requires_dev_endpoints() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, _) = post_json(&app, "/api/v1/admin/seed-demo", json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn test_audit_log_filtered_by_action() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let txn = post_transfer(&app, "SALE-1", &cash, &revenue, "10.00").await;
+    put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", cash),
+        json!({ "name": "Petty Cash" }),
+    )
+    .await;
+    delete(
+        &app,
+        &format!("/api/v1/transactions/{}", txn["id"].as_str().unwrap()),
+    )
+    .await;
+
+    let (status, body) = get_json(&app, "/api/v1/admin/audit?action=created").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["total"], 3);
+    let items = body["data"]["items"].as_array().unwrap();
+    assert!(items.iter().all(|i| i["action"] == "created"));
+    // Newest first: the transaction was created after both accounts
+    assert_eq!(items[0]["entity_type"], "transaction");
+
+    let (_, body) = get_json(
+        &app,
+        "/api/v1/admin/audit?entity_type=account&action=updated",
+    )
+    .await;
+    assert_eq!(body["data"]["total"], 1);
+    assert_eq!(body["data"]["items"][0]["entity_id"], cash.as_str());
+
+    let (_, body) = get_json(&app, "/api/v1/admin/audit?from=2999-01-01").await;
+    assert_eq!(body["data"]["total"], 0);
+}
+
+#[actix_rt::test]
+async fn test_audit_log_paging() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    for i in 0..5 {
+        create_account(
+            &app,
+            &format!("10{}0", i),
+            &format!("Account {}", i),
+            "asset",
+        )
+        .await;
+    }
+
+    let (_, body) = get_json(&app, "/api/v1/admin/audit?limit=2").await;
+    assert_eq!(body["data"]["total"], 5);
+    assert_eq!(body["data"]["limit"], 2);
+    let first_page: Vec<String> = body["data"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(first_page.len(), 2);
+
+    let (_, body) = get_json(&app, "/api/v1/admin/audit?limit=2&offset=4").await;
+    let last_page = body["data"]["items"].as_array().unwrap();
+    assert_eq!(last_page.len(), 1);
+    assert_eq!(body["data"]["offset"], 4);
+    assert!(!first_page.contains(&last_page[0]["id"].as_str().unwrap().to_string()));
+
+    let (status, _) = get_json(&app, "/api/v1/admin/audit?offset=-1").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,100 @@
+use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+use double_rust_ledger::handlers::PROBE_PATHS;
+use double_rust_ledger::middleware::ApiKeyAuth;
+use serde_json::Value;
+
+async fn ok() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+macro_rules! init_auth_app {
+    () => {
+        test::init_service(
+            App::new()
+                .wrap(ApiKeyAuth::new(["first-key", "second-key"]).exempt(PROBE_PATHS))
+                .route("/api/v1/accounts", web::get().to(ok))
+                .route("/health", web::get().to(ok))
+                .route("/ready", web::get().to(ok)),
+        )
+        .await
+    };
+}
+
+#[actix_rt::test]
+async fn test_valid_key_is_accepted() {
+    let app = init_auth_app!();
+
+    for key in ["first-key", "second-key"] {
+        let req = test::TestRequest::get()
+            .uri("/api/v1/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", key)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
+
+#[actix_rt::test]
+async fn test_missing_header_is_unauthorized() {
+    let app = init_auth_app!();
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/accounts")
+        .to_request();
+    let err = test::try_call_service(&app, req).await.unwrap_err();
+    let response = err.error_response();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.headers().get("WWW-Authenticate").unwrap(),
+        "Bearer"
+    );
+
+    let body = actix_web::body::to_bytes(response.into_body())
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["message"], "missing API key");
+
+    // Health and readiness probes need no key
+    for uri in ["/health", "/ready"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK, "{}", uri);
+    }
+}
+
+#[actix_rt::test]
+async fn test_wrong_key_is_unauthorized() {
+    let app = init_auth_app!();
+
+    for header in ["Bearer third-key", "Bearer first-ke", "Basic first-key"] {
+        let req = test::TestRequest::get()
+            .uri("/api/v1/accounts")
+            .insert_header(("Authorization", header))
+            .to_request();
+        let err = test::try_call_service(&app, req).await.unwrap_err();
+        assert_eq!(
+            err.error_response().status(),
+            StatusCode::UNAUTHORIZED,
+            "{} accepted",
+            header
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_no_configured_keys_leaves_the_api_open() {
+    let app = test::init_service(
+        App::new()
+            .wrap(ApiKeyAuth::new(Vec::<String>::new()))
+            .route("/api/v1/accounts", web::get().to(ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/accounts")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
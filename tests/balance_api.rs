@@ -0,0 +1,603 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn test_unknown_account_type_is_an_error() {
+    use diesel::prelude::*;
+    use double_rust_ledger::schema::accounts;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "100.00").await;
+
+    // Simulate a row written before account types were validated
+    let mut conn = pool.get().unwrap();
+    diesel::sql_query("PRAGMA ignore_check_constraints = ON")
+        .execute(&mut conn)
+        .unwrap();
+    diesel::update(accounts::table.find(&cash))
+        .set(accounts::account_type.eq("assett"))
+        .execute(&mut conn)
+        .unwrap();
+
+    let (status, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        body["message"],
+        format!("unknown account_type for account {}", cash)
+    );
+
+    let (status, _) = get_json(&app, "/api/v1/balance").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let (status, body) = get_json(&app, &format!("/api/v1/balance/{}", revenue)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["balance"], "100.00");
+}
+
+#[actix_rt::test]
+async fn test_unreadable_opening_balance_is_an_error() {
+    use diesel::prelude::*;
+    use double_rust_ledger::schema::accounts;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let mut conn = pool.get().unwrap();
+    diesel::update(accounts::table.find(&cash))
+        .set(accounts::opening_balance.eq("1O0.00"))
+        .execute(&mut conn)
+        .unwrap();
+
+    for uri in [
+        format!("/api/v1/balance/{}", cash),
+        format!("/api/v1/accounts/{}/ledger", cash),
+        "/api/v1/reports/trial-balance".to_string(),
+    ] {
+        let (status, body) = get_json(&app, &uri).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{}", uri);
+        assert_eq!(
+            body["message"],
+            "stored opening balance of account 1000 '1O0.00' is not a valid amount"
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_missing_account_balance_names_the_id() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, body) = get_json(&app, "/api/v1/balance/no-such-account").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["message"], "account no-such-account not found");
+}
+
+#[actix_rt::test]
+async fn test_draft_does_not_move_balances_until_posted() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "100.00").await;
+
+    let mut draft_body = transfer_body("SALE-2", &cash, &revenue, "25.00");
+    draft_body["status"] = serde_json::json!("draft");
+    let (status, draft) = post_json(&app, "/api/v1/transactions", draft_body).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(draft["data"]["status"], "draft");
+    let draft_id = draft["data"]["id"].as_str().unwrap().to_string();
+
+    let balance_uri = format!("/api/v1/balance/{}", cash);
+    let (_, body) = get_json(&app, &balance_uri).await;
+    assert_eq!(body["data"]["balance"], "100.00");
+
+    let (_, body) = get_json(&app, &format!("{}?include_drafts=true", balance_uri)).await;
+    assert_eq!(body["data"]["balance"], "125.00");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(body["data"]["total_debits"], "100.00");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance?include_drafts=true").await;
+    assert_eq!(body["data"]["total_debits"], "125.00");
+
+    let (status, body) = post_json(
+        &app,
+        &format!("/api/v1/transactions/{}/post", draft_id),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "posted");
+
+    let (_, body) = get_json(&app, &balance_uri).await;
+    assert_eq!(body["data"]["balance"], "125.00");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(body["data"]["total_debits"], "125.00");
+
+    let (status, _) = post_json(
+        &app,
+        &format!("/api/v1/transactions/{}/post", draft_id),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[actix_rt::test]
+async fn test_opening_balance_plus_movement_since_opening_date() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account_with(
+        &app,
+        serde_json::json!({
+            "code": "1000",
+            "name": "Cash",
+            "account_type": "asset",
+            "opening_balance": "500.00",
+            "opening_date": "2024-01-31"
+        }),
+    )
+    .await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    // Already part of the migrated opening figure
+    for (reference, date, amount) in [
+        ("OLD-1", "2024-01-15T10:00:00Z", "300.00"),
+        ("NEW-1", "2024-02-01T09:00:00Z", "40.00"),
+        ("NEW-2", "2024-02-10T09:00:00Z", "2.50"),
+    ] {
+        let mut body = transfer_body(reference, &cash, &revenue, amount);
+        body["transaction_date"] = serde_json::json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["opening_balance"], "500.00");
+    assert_eq!(body["data"]["debit_total"], "42.50");
+    assert_eq!(body["data"]["balance"], "542.50");
+
+    let (_, body) = get_json(&app, "/api/v1/balance?to_date=2024-02-05").await;
+    let cash_balance = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["account_id"] == cash.as_str())
+        .unwrap()
+        .clone();
+    assert_eq!(cash_balance["balance"], "540.00");
+
+    // Accounts without an opening date count every entry
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", revenue)).await;
+    assert_eq!(body["data"]["balance"], "342.50");
+}
+
+#[actix_rt::test]
+async fn test_balances_honour_date_range() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for (reference, date, amount) in [
+        ("JAN", "2024-01-15T10:00:00Z", "100.00"),
+        ("FEB", "2024-02-29T18:00:00Z", "20.00"),
+        ("MAR", "2024-03-01T09:00:00Z", "3.00"),
+    ] {
+        let mut body = transfer_body(reference, &cash, &revenue, amount);
+        body["transaction_date"] = serde_json::json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let cash_balance = |body: serde_json::Value| {
+        body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|b| b["account_id"] == cash.as_str())
+            .unwrap()["balance"]
+            .clone()
+    };
+
+    // Open-ended: everything from February on
+    let (status, body) = get_json(&app, "/api/v1/balance?from_date=2024-02-01").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(cash_balance(body), "23.00");
+
+    // Both bounds are inclusive and a bare end date covers the whole day
+    let (_, body) = get_json(
+        &app,
+        "/api/v1/balance?from_date=2024-01-15T10:00:00Z&to_date=2024-02-29",
+    )
+    .await;
+    assert_eq!(cash_balance(body), "120.00");
+
+    for query in ["from_date=yesterday", "to_date=2024-13-01"] {
+        let (status, _) = get_json(&app, &format!("/api/v1/balance?{}", query)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+    }
+
+    // A single account's balance takes the same bounds
+    let (status, body) = get_json(
+        &app,
+        &format!("/api/v1/balance/{}?from_date=2024-02-01", cash),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["balance"], "23.00");
+
+    let (_, body) = get_json(
+        &app,
+        &format!(
+            "/api/v1/balance/{}?from_date=2024-01-15T10:00:00Z&to_date=2024-02-29",
+            cash
+        ),
+    )
+    .await;
+    assert_eq!(body["data"]["balance"], "120.00");
+    assert_eq!(body["data"]["unreconciled_balance"], "120.00");
+
+    let (status, _) = get_json(
+        &app,
+        &format!("/api/v1/balance/{}?to_date=2024-13-01", cash),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_invalid_opening_date_rejected() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, _) = post_json(
+        &app,
+        "/api/v1/accounts",
+        serde_json::json!({
+            "code": "1000",
+            "name": "Cash",
+            "account_type": "asset",
+            "opening_date": "31/01/2024"
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_balance_csv_export() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash, Petty", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    create_account(&app, "5000", "Rent", "expense").await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "150.00").await;
+
+    let resp = actix_web::test::call_service(
+        &app,
+        actix_web::test::TestRequest::get()
+            .uri("/api/v1/balance/export.csv?account_type=asset")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp
+        .headers()
+        .get("content-type")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("text/csv"));
+    let body = actix_web::test::read_body(resp).await;
+    let csv = std::str::from_utf8(&body).unwrap();
+
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "account_code,account_name,account_type,debit_total,credit_total,balance"
+    );
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], "1000,\"Cash, Petty\",asset,150.00,0.00,150.00");
+}
+
+#[actix_rt::test]
+async fn test_amounts_render_at_fixed_scale() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let transaction = post_transfer(&app, "SALE-1", &cash, &revenue, "100").await;
+    post_transfer(&app, "SALE-2", &cash, &revenue, "2.5").await;
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["debit_total"], "102.50");
+    assert_eq!(body["data"]["credit_total"], "0.00");
+    assert_eq!(body["data"]["opening_balance"], "0.00");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(body["data"]["total_debits"], "102.50");
+
+    let entries = transaction["entries"].as_array().unwrap();
+    assert!(entries
+        .iter()
+        .all(|e| e["debit_amount"] == "100.00" || e["credit_amount"] == "100.00"));
+    assert!(entries.iter().any(|e| e["credit_amount"] == "0.00"));
+}
+
+#[actix_rt::test]
+async fn test_balance_snapshot_after_each_transaction() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut ids = Vec::new();
+    for (reference, date, debit, amount) in [
+        ("T-1", "2024-01-01", &cash, "100.00"),
+        ("T-2", "2024-01-02", &bank, "50.00"),
+        ("T-3", "2024-01-03", &cash, "25.00"),
+        ("T-4", "2024-01-03", &cash, "5.00"),
+    ] {
+        let mut body = transfer_body(reference, debit, &revenue, amount);
+        body["transaction_date"] = serde_json::json!(date);
+        let (status, created) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(created["data"]["id"].as_str().unwrap().to_string());
+    }
+
+    let snapshot = |id: &str| format!("/api/v1/accounts/{}/balance-at-transaction/{}", cash, id);
+
+    let expected = [
+        ("100.00", true),
+        ("100.00", false),
+        ("125.00", true),
+        ("130.00", true),
+    ];
+    for (id, (balance, involved)) in ids.iter().zip(expected) {
+        let (status, body) = get_json(&app, &snapshot(id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["balance"]["balance"], balance, "after {}", id);
+        assert_eq!(body["data"]["account_involved"], involved);
+    }
+
+    let (status, _) = get_json(&app, &snapshot("missing")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn test_balance_overflow_is_an_error() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    // Entry amounts are bounded by their integer storage
+    let huge = "50000000000000000000000000000";
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("BIG-1", &revenue, &revenue, huge),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["errors"][0],
+        format!("entries[0].debit_amount: {} is too large to store", huge)
+    );
+
+    // An opening balance is not, so adding entries to one can still overflow
+    let cash = create_account_with(
+        &app,
+        json!({
+            "code": "1000",
+            "name": "Cash",
+            "account_type": "asset",
+            "opening_balance": "79228162514264337593543950335"
+        }),
+    )
+    .await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "1.00").await;
+
+    let (status, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        body["message"],
+        format!("balance overflow for account {}", cash)
+    );
+
+    let (status, _) = get_json(&app, "/api/v1/balance").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[actix_rt::test]
+async fn test_balance_reports_normal_side() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    post_transfer(&app, "SALE-1", &cash, &revenue, "100.00").await;
+    // Paying out more than the bank holds leaves it overdrawn
+    post_transfer(&app, "MOVE-1", &cash, &bank, "40.00").await;
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["balance"], "140.00");
+    assert_eq!(body["data"]["normal_side"], "debit");
+    assert_eq!(body["data"]["balance_is_normal"], true);
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", bank)).await;
+    assert_eq!(body["data"]["balance"], "-40.00");
+    assert_eq!(body["data"]["normal_side"], "debit");
+    assert_eq!(body["data"]["balance_is_normal"], false);
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", revenue)).await;
+    assert_eq!(body["data"]["normal_side"], "credit");
+    assert_eq!(body["data"]["balance_is_normal"], true);
+}
+
+#[actix_rt::test]
+async fn test_account_balance_splits_reconciled_entries() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let rent = create_account(&app, "5000", "Rent", "expense").await;
+
+    post_transfer(&app, "SALE-1", &bank, &revenue, "100.00").await;
+    post_transfer(&app, "SALE-2", &bank, &revenue, "75.00").await;
+    post_transfer(&app, "RENT-1", &rent, &bank, "30.00").await;
+
+    let (_, entries) = get_json(&app, &format!("/api/v1/entries?account_id={}", bank)).await;
+    let entries = entries["data"].as_array().unwrap();
+    // Clear the first sale and the rent payment; the second sale is pending
+    for entry in [&entries[0], &entries[2]] {
+        let (status, _) = post_json(
+            &app,
+            &format!(
+                "/api/v1/entries/{}/reconcile",
+                entry["id"].as_str().unwrap()
+            ),
+            serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = get_json(&app, &format!("/api/v1/balance/{}", bank)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["balance"], "145.00");
+    assert_eq!(body["data"]["reconciled_balance"], "70.00");
+    assert_eq!(body["data"]["unreconciled_balance"], "75.00");
+}
+
+#[actix_rt::test]
+async fn test_balances_for_many_accounts() {
+    use diesel::prelude::*;
+    use double_rust_ledger::models::{NewAccount, NewEntry, NewTransaction};
+    use double_rust_ledger::schema::{accounts, entries, transactions};
+    use rust_decimal::Decimal;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    // 100 asset and 100 revenue accounts; transaction n moves (n % 7 + 1) units
+    // from revenue account n % 100 to asset account n % 100
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut conn = pool.get().unwrap();
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        for (prefix, account_type) in [("a", "asset"), ("r", "revenue")] {
+            let new_accounts: Vec<NewAccount> = (0..100)
+                .map(|i| NewAccount {
+                    id: format!("{}{}", prefix, i),
+                    code: format!("{}{:03}", prefix, i),
+                    name: format!("{} {}", account_type, i),
+                    account_type: account_type.to_string(),
+                    parent_id: None,
+                    is_active: true,
+                    created_at: now.clone(),
+                    updated_at: now.clone(),
+                    is_memo: false,
+                    ledger_id: "default".to_string(),
+                    opening_balance: "0".to_string(),
+                    opening_date: None,
+                    currency: "USD".to_string(),
+                })
+                .collect();
+            diesel::insert_into(accounts::table)
+                .values(&new_accounts)
+                .execute(conn)?;
+        }
+
+        for n in 0..1000 {
+            let transaction_id = format!("t{}", n);
+            diesel::insert_into(transactions::table)
+                .values(&NewTransaction {
+                    id: transaction_id.clone(),
+                    reference: format!("BULK-{}", n),
+                    description: "Bulk".to_string(),
+                    transaction_date: now.clone(),
+                    created_at: now.clone(),
+                    updated_at: now.clone(),
+                    ledger_id: "default".to_string(),
+                    reverses_transaction_id: None,
+                    status: "posted".to_string(),
+                    period_id: None,
+                    posting_date: now.clone(),
+                    metadata: None,
+                })
+                .execute(conn)?;
+
+            let amount = (n % 7 + 1) * 10_000;
+            let entry = |side: &str, account_id: String, debit: i64, credit: i64| NewEntry {
+                id: format!("{}-{}", transaction_id, side),
+                transaction_id: transaction_id.clone(),
+                account_id,
+                debit_amount: debit,
+                credit_amount: credit,
+                description: None,
+                created_at: now.clone(),
+                currency: "USD".to_string(),
+            };
+            diesel::insert_into(entries::table)
+                .values(&vec![
+                    entry("dr", format!("a{}", n % 100), amount, 0),
+                    entry("cr", format!("r{}", n % 100), 0, amount),
+                ])
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    let (status, body) = get_json(&app, "/api/v1/balance").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let balances = body["data"].as_array().unwrap();
+    let mut checked = 0;
+    for i in 0..100 {
+        let expected: Decimal = (0..1000)
+            .filter(|n| n % 100 == i)
+            .map(|n| Decimal::from(n % 7 + 1))
+            .sum();
+        let expected = format!("{:.2}", expected);
+
+        for (prefix, debit_total, credit_total) in [
+            ("a", expected.as_str(), "0.00"),
+            ("r", "0.00", expected.as_str()),
+        ] {
+            let id = format!("{}{}", prefix, i);
+            let balance = balances
+                .iter()
+                .find(|b| b["account_id"] == id.as_str())
+                .unwrap();
+            assert_eq!(balance["debit_total"], debit_total, "{}", id);
+            assert_eq!(balance["credit_total"], credit_total, "{}", id);
+            assert_eq!(balance["balance"], expected.as_str(), "{}", id);
+            checked += 1;
+        }
+    }
+    assert_eq!(checked, 200);
+
+    let (_, body) = get_json(&app, "/api/v1/balance?account_type=revenue").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 100);
+}
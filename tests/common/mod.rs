@@ -0,0 +1,190 @@
+// Shared helpers for the HTTP-level integration tests
+#![allow(dead_code)]
+
+use actix_http::Request;
+use actix_web::{
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    http::StatusCode,
+    test,
+};
+use double_rust_ledger::database::{self, DbPool};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// Builds the application service with the same routes as the server binary.
+#[macro_export]
+macro_rules! init_app {
+    ($pool:expr) => {
+        init_app!($pool, double_rust_ledger::config::AppConfig::default())
+    };
+    ($pool:expr, $config:expr) => {
+        init_app!(
+            $pool,
+            $config,
+            std::sync::Arc::new(double_rust_ledger::clock::SystemClock)
+        )
+    };
+    ($pool:expr, $config:expr, $clock:expr) => {{
+        let config: double_rust_ledger::config::AppConfig = $config;
+        let clock: std::sync::Arc<dyn double_rust_ledger::clock::Clock> = $clock;
+        let serialize_writes =
+            double_rust_ledger::middleware::SerializeWrites::new(config.serialize_writes);
+        let api_base_path = config.api_base_path.clone();
+        let json_config = double_rust_ledger::handlers::json_config(&config);
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data(actix_web::web::Data::new($pool.clone()))
+                .app_data(actix_web::web::Data::new(config))
+                .app_data(json_config)
+                .app_data(actix_web::web::Data::new(
+                    double_rust_ledger::cache::TrialBalanceCache::default(),
+                ))
+                .app_data(actix_web::web::Data::new(
+                    double_rust_ledger::cache::AccountTreeCache::default(),
+                ))
+                .app_data(actix_web::web::Data::from(clock))
+                .wrap(serialize_writes)
+                .configure(|cfg| double_rust_ledger::handlers::configure_at(cfg, &api_base_path)),
+        )
+        .await
+    }};
+}
+
+/// Creates a migrated database in a throwaway file so every pooled connection sees the same data.
+pub fn test_pool() -> DbPool {
+    let path = std::env::temp_dir().join(format!("ledger-test-{}.db", Uuid::new_v4()));
+    let pool =
+        database::create_pool(path.to_str().unwrap(), 15).expect("Failed to create test database");
+    database::run_migrations(&pool).expect("Failed to run migrations");
+    pool
+}
+
+pub async fn send<S, B>(app: &S, req: Request) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let resp = test::call_service(app, req).await;
+    let status = resp.status();
+    let body = test::read_body(resp).await;
+    let value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, value)
+}
+
+pub async fn get_json<S, B>(app: &S, uri: &str) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    send(app, test::TestRequest::get().uri(uri).to_request()).await
+}
+
+pub async fn post_json<S, B>(app: &S, uri: &str, body: Value) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    send(
+        app,
+        test::TestRequest::post()
+            .uri(uri)
+            .set_json(body)
+            .to_request(),
+    )
+    .await
+}
+
+pub async fn put_json<S, B>(app: &S, uri: &str, body: Value) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    send(
+        app,
+        test::TestRequest::put()
+            .uri(uri)
+            .set_json(body)
+            .to_request(),
+    )
+    .await
+}
+
+pub async fn delete<S, B>(app: &S, uri: &str) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    send(app, test::TestRequest::delete().uri(uri).to_request()).await
+}
+
+/// Creates an account through the API and returns its id.
+pub async fn create_account<S, B>(app: &S, code: &str, name: &str, account_type: &str) -> String
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    create_account_with(
+        app,
+        json!({ "code": code, "name": name, "account_type": account_type }),
+    )
+    .await
+}
+
+pub async fn create_account_with<S, B>(app: &S, body: Value) -> String
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (status, body) = post_json(app, "/api/v1/accounts", body).await;
+    assert_eq!(
+        status,
+        StatusCode::CREATED,
+        "account creation failed: {}",
+        body
+    );
+    body["data"]["id"].as_str().unwrap().to_string()
+}
+
+/// Posts a two-entry transaction debiting one account and crediting another.
+pub async fn post_transfer<S, B>(
+    app: &S,
+    reference: &str,
+    debit_account: &str,
+    credit_account: &str,
+    amount: &str,
+) -> Value
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (status, body) = post_json(
+        app,
+        "/api/v1/transactions",
+        transfer_body(reference, debit_account, credit_account, amount),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::CREATED,
+        "transaction creation failed: {}",
+        body
+    );
+    body["data"].clone()
+}
+
+pub fn transfer_body(
+    reference: &str,
+    debit_account: &str,
+    credit_account: &str,
+    amount: &str,
+) -> Value {
+    json!({
+        "reference": reference,
+        "description": format!("Transfer {}", reference),
+        "entries": [
+            { "account_id": debit_account, "debit_amount": amount, "credit_amount": null },
+            { "account_id": credit_account, "debit_amount": null, "credit_amount": amount }
+        ]
+    })
+}
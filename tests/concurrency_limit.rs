@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+use double_rust_ledger::middleware::ConcurrencyLimit;
+use tokio::sync::Notify;
+
+/// Holds its permit until the test says to finish.
+async fn blocked(release: web::Data<Arc<Notify>>) -> HttpResponse {
+    release.notified().await;
+    HttpResponse::Ok().finish()
+}
+
+async fn health() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+#[actix_rt::test]
+async fn test_requests_over_the_limit_get_503() {
+    let release = Arc::new(Notify::new());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(release.clone()))
+            .wrap(ConcurrencyLimit::new(Some(2)).exempt(["/health"]))
+            .route("/work", web::get().to(blocked))
+            .route("/health", web::get().to(health)),
+    )
+    .await;
+
+    let in_flight = || test::call_service(&app, test::TestRequest::get().uri("/work").to_request());
+    let overflow = async {
+        let err = test::try_call_service(&app, test::TestRequest::get().uri("/work").to_request())
+            .await
+            .unwrap_err();
+        let response = err.error_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "1");
+
+        let health =
+            test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
+        assert_eq!(health.status(), StatusCode::OK);
+
+        release.notify_waiters();
+    };
+
+    // The first two futures are polled first and take both permits
+    let (first, second, ()) = futures_util::join!(in_flight(), in_flight(), overflow);
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::OK);
+
+    // Permits are returned once the requests finish
+    let release_again = release.clone();
+    let (after, ()) = futures_util::join!(in_flight(), async move {
+        release_again.notify_waiters();
+    });
+    assert_eq!(after.status(), StatusCode::OK);
+}
@@ -0,0 +1,263 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+
+async fn post_dated_transfer<S, B>(
+    app: &S,
+    reference: &str,
+    date: &str,
+    debit_account: &str,
+    credit_account: &str,
+    amount: &str,
+) where
+    S: actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse<B>,
+        Error = actix_web::Error,
+    >,
+    B: actix_web::body::MessageBody,
+{
+    let mut body = transfer_body(reference, debit_account, credit_account, amount);
+    body["transaction_date"] = serde_json::json!(date);
+    let (status, body) = post_json(app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+}
+
+#[actix_rt::test]
+async fn test_entries_filtered_by_account_and_date() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    post_dated_transfer(&app, "E-1", "2024-01-05", &cash, &revenue, "10.00").await;
+    post_dated_transfer(&app, "E-2", "2024-02-05", &cash, &revenue, "20.00").await;
+    post_dated_transfer(&app, "E-3", "2024-03-05", &bank, &revenue, "30.00").await;
+
+    let (status, body) = get_json(&app, &format!("/api/v1/entries?account_id={}", cash)).await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = body["data"].as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|r| r["account_code"] == "1000"));
+    assert_eq!(rows[0]["debit_amount"], "10.00");
+    assert_eq!(rows[1]["debit_amount"], "20.00");
+
+    let (_, body) = get_json(&app, "/api/v1/entries?from=2024-02-01&to=2024-02-28").await;
+    let rows = body["data"].as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(rows
+        .iter()
+        .all(|r| r["debit_amount"] == "20.00" || r["credit_amount"] == "20.00"));
+
+    let (_, body) = get_json(
+        &app,
+        &format!("/api/v1/entries?account_id={}&from=2024-02-01", revenue),
+    )
+    .await;
+    let rows = body["data"].as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["credit_amount"], "20.00");
+    assert_eq!(rows[1]["credit_amount"], "30.00");
+}
+
+#[actix_rt::test]
+async fn test_get_single_entry_with_transaction_context() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_dated_transfer(&app, "E-1", "2024-01-05", &cash, &revenue, "10.00").await;
+
+    let (_, body) = get_json(&app, &format!("/api/v1/entries?account_id={}", cash)).await;
+    let entry_id = body["data"][0]["id"].as_str().unwrap();
+
+    let (status, body) = get_json(&app, &format!("/api/v1/entries/{}", entry_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let entry = &body["data"];
+    assert_eq!(entry["id"], entry_id);
+    assert_eq!(entry["account_code"], "1000");
+    assert_eq!(entry["debit_amount"], "10.00");
+    assert_eq!(entry["description"], "Transfer E-1");
+    assert_eq!(entry["description_inherited"], true);
+    assert_eq!(entry["transaction_reference"], "E-1");
+    assert_eq!(entry["transaction_date"], "2024-01-05");
+
+    let (status, body) = get_json(&app, "/api/v1/entries/missing").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["message"], "entry missing not found");
+}
+
+#[actix_rt::test]
+async fn test_signed_amount_format() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let transaction = post_transfer(&app, "S-1", &cash, &revenue, "12.50").await;
+
+    let (status, body) = get_json(&app, "/api/v1/entries?amount_format=signed").await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = body["data"].as_array().unwrap();
+    let debit = rows.iter().find(|r| r["account_id"] == cash).unwrap();
+    let credit = rows.iter().find(|r| r["account_id"] == revenue).unwrap();
+    assert_eq!(debit["amount"], "12.50");
+    assert_eq!(credit["amount"], "-12.50");
+    assert!(debit.get("debit_amount").is_none());
+    assert!(credit.get("credit_amount").is_none());
+
+    let entry_uri = format!(
+        "/api/v1/entries/{}?amount_format=signed",
+        credit["id"].as_str().unwrap()
+    );
+    let (_, body) = get_json(&app, &entry_uri).await;
+    assert_eq!(body["data"]["amount"], "-12.50");
+    assert_eq!(body["data"]["transaction_reference"], "S-1");
+
+    let transaction_uri = format!(
+        "/api/v1/transactions/{}",
+        transaction["id"].as_str().unwrap()
+    );
+    let (_, body) = get_json(&app, &format!("{}?amount_format=signed", transaction_uri)).await;
+    let mut amounts: Vec<&str> = body["data"]["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["amount"].as_str().unwrap())
+        .collect();
+    amounts.sort();
+    assert_eq!(amounts, ["-12.50", "12.50"]);
+
+    // The two-column form stays the default
+    let (_, body) = get_json(&app, &transaction_uri).await;
+    let entries = body["data"]["entries"].as_array().unwrap();
+    assert!(entries.iter().any(|e| e["credit_amount"] == "12.50"));
+    assert!(entries.iter().all(|e| e.get("amount").is_none()));
+
+    let (status, _) = get_json(&app, "/api/v1/entries?amount_format=net").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_entries_pagination() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for (i, date) in ["2024-01-01", "2024-01-02", "2024-01-03"]
+        .iter()
+        .enumerate()
+    {
+        let reference = format!("P-{}", i);
+        let amount = format!("{}.00", i + 1);
+        post_dated_transfer(&app, &reference, date, &cash, &revenue, &amount).await;
+    }
+
+    let uri = format!("/api/v1/entries?account_id={}&limit=2", cash);
+    let (_, body) = get_json(&app, &uri).await;
+    let first_page = body["data"].as_array().unwrap();
+    assert_eq!(first_page.len(), 2);
+    assert_eq!(body["meta"]["total"], 3);
+    assert_eq!(body["meta"]["limit"], 2);
+    assert_eq!(first_page[0]["debit_amount"], "1.00");
+    assert_eq!(first_page[1]["debit_amount"], "2.00");
+
+    let (_, body) = get_json(&app, &format!("{}&offset=2", uri)).await;
+    let second_page = body["data"].as_array().unwrap();
+    assert_eq!(second_page.len(), 1);
+    assert_eq!(second_page[0]["debit_amount"], "3.00");
+
+    let (status, _) = get_json(&app, "/api/v1/entries?limit=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_reconciling_listed_entries_clears_the_difference() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut bank_entry_ids = Vec::new();
+    for (reference, amount) in [("DEP-1", "100.00"), ("DEP-2", "50.00"), ("DEP-3", "25.00")] {
+        let transaction = post_transfer(&app, reference, &bank, &revenue, amount).await;
+        let entry = transaction["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["account_id"] == bank.as_str())
+            .unwrap()
+            .clone();
+        bank_entry_ids.push(entry["id"].as_str().unwrap().to_string());
+    }
+
+    let (status, _) = post_json(
+        &app,
+        &format!("/api/v1/entries/{}/reconcile", bank_entry_ids[0]),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!(
+        "/api/v1/accounts/{}/reconciliation?statement_balance=175.00",
+        bank
+    );
+    let (status, body) = get_json(&app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let report = &body["data"];
+    assert_eq!(report["ledger_balance"], "175.00");
+    assert_eq!(report["reconciled_balance"], "100.00");
+    assert_eq!(report["difference"], "75.00");
+    assert_eq!(report["unreconciled_total"], "75.00");
+
+    let listed: Vec<String> = report["unreconciled_entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["entry_id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(listed, bank_entry_ids[1..].to_vec());
+
+    for entry_id in &listed {
+        let (status, _) = post_json(
+            &app,
+            &format!("/api/v1/entries/{}/reconcile", entry_id),
+            serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, body) = get_json(&app, &uri).await;
+    assert_eq!(body["data"]["difference"], "0.00");
+    assert_eq!(body["data"]["unreconciled_entries"], serde_json::json!([]));
+}
+
+#[actix_rt::test]
+async fn test_reconciliation_requires_known_account() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, _) = get_json(
+        &app,
+        "/api/v1/accounts/missing/reconciliation?statement_balance=0",
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = post_json(
+        &app,
+        "/api/v1/entries/missing/reconcile",
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
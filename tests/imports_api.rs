@@ -0,0 +1,233 @@
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use common::*;
+use serde_json::json;
+
+const STATEMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Ntry>
+        <NtryRef>CAMT-IN-1</NtryRef>
+        <Amt Ccy="EUR">300.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <BookgDt><Dt>2023-12-01</Dt></BookgDt>
+        <AddtlNtryInf>Incoming transfer</AddtlNtryInf>
+      </Ntry>
+      <Ntry>
+        <NtryRef>CAMT-OUT-1</NtryRef>
+        <Amt Ccy="EUR">45.50</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <BookgDt><Dt>2023-12-03</Dt></BookgDt>
+        <AddtlNtryInf>Bank charges</AddtlNtryInf>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+#[actix_rt::test]
+async fn test_camt053_import_creates_balanced_transactions() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let suspense = create_account(&app, "9999", "Uncategorized", "liability").await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/v1/import/camt053?account_id={}&contra_account_id={}",
+            bank, suspense
+        ))
+        .insert_header(("Content-Type", "application/xml"))
+        .set_payload(STATEMENT)
+        .to_request();
+    let (status, body) = send(&app, req).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    let imported = body["data"].as_array().unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0]["reference"], "CAMT-IN-1");
+    assert_eq!(imported[0]["transaction_date"], "2023-12-01");
+
+    let (_, bank_balance) = get_json(&app, &format!("/api/v1/balance/{}", bank)).await;
+    assert_eq!(bank_balance["data"]["balance"], "254.50");
+
+    let (_, trial) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(trial["data"]["is_balanced"], true);
+}
+
+async fn post_csv<S, B>(app: &S, csv: &str) -> (StatusCode, serde_json::Value)
+where
+    S: actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse<B>,
+        Error = actix_web::Error,
+    >,
+    B: actix_web::body::MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/api/v1/import/csv")
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload(csv.to_string())
+        .to_request();
+    send(app, req).await
+}
+
+#[actix_rt::test]
+async fn test_csv_import_resolves_codes_and_ids() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    create_account(&app, "4000", "Sales", "revenue").await;
+
+    let csv = format!(
+        "reference,date,description,account,debit,credit
+CSV-1,2024-01-05,Cash sale,{},100.00,
+CSV-1,2024-01-05,Cash sale,4000,,100.00
+",
+        cash
+    );
+    let (status, body) = post_csv(&app, &csv).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    assert_eq!(body["data"][0]["reference"], "CSV-1");
+    assert_eq!(body["data"][0]["entries"].as_array().unwrap().len(), 2);
+
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(balance["data"]["balance"], "100.00");
+}
+
+#[actix_rt::test]
+async fn test_csv_import_rejects_unknown_account_code() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    create_account(&app, "1000", "Cash", "asset").await;
+    create_account(&app, "4000", "Sales", "revenue").await;
+
+    let csv = "reference,date,description,account,debit,credit
+CSV-1,2024-01-05,Cash sale,1000,100.00,
+CSV-1,2024-01-05,Cash sale,4000,,100.00
+CSV-2,2024-01-06,Refund,4000,20.00,
+CSV-2,2024-01-06,Refund,1999,,20.00
+";
+    let (status, body) = post_csv(&app, csv).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "line 5: unknown account '1999'");
+
+    // Nothing from the file was posted, not even the valid transaction
+    let (_, transactions) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(transactions["data"].as_array().unwrap().len(), 0);
+}
+
+#[actix_rt::test]
+async fn test_bank_import_maps_signed_amounts() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let sales = create_account(&app, "4000", "Sales", "revenue").await;
+    let fees = create_account(&app, "6100", "Bank Fees", "expense").await;
+
+    let (status, body) = post_json(
+        &app,
+        &format!("/api/v1/import/bank?account_id={}", bank),
+        json!({
+            "transactions": [
+                { "date": "2024-03-01", "amount": "250.00", "description": "Invoice 17", "counterparty": "ACME Ltd" },
+                { "date": "2024-03-02", "amount": "-4.50", "description": "Account fee" }
+            ],
+            "default_contra_account_id": sales,
+            "rules": [{ "pattern": "fee", "contra_account_id": fees }]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    let imported = body["data"].as_array().unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0]["reference"], "BANK-1100-1");
+    assert_eq!(imported[0]["transaction_date"], "2024-03-01");
+    assert_eq!(imported[0]["entries"][0]["description"], "ACME Ltd");
+
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", bank)).await;
+    assert_eq!(balance["data"]["balance"], "245.50");
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", sales)).await;
+    assert_eq!(balance["data"]["balance"], "250.00");
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", fees)).await;
+    assert_eq!(balance["data"]["balance"], "4.50");
+}
+
+#[actix_rt::test]
+async fn test_bank_import_rejects_unknown_contra_account() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let fees = create_account(&app, "6100", "Bank Fees", "expense").await;
+
+    let (status, body) = post_json(
+        &app,
+        &format!("/api/v1/import/bank?account_id={}", bank),
+        json!({
+            "transactions": [
+                { "date": "2024-03-02", "amount": "-4.50", "description": "Account fee" },
+                { "date": "2024-03-03", "amount": "80.00", "description": "Refund" }
+            ],
+            "default_contra_account_id": "missing",
+            "rules": [{ "pattern": "FEE", "contra_account_id": fees }]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("transaction 1: unknown contra account 'missing'"));
+    assert!(!message.contains("transaction 0"));
+
+    let (_, transactions) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(transactions["data"].as_array().unwrap().len(), 0);
+}
+
+#[actix_rt::test]
+async fn test_bank_import_routes_by_sign_to_configured_contras() {
+    let pool = test_pool();
+    let app = init_app!(pool.clone());
+
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let sales = create_account(&app, "4000", "Sales", "revenue").await;
+    let expenses = create_account(&app, "6000", "General Expenses", "expense").await;
+    let fees = create_account(&app, "6100", "Bank Fees", "expense").await;
+
+    let config = double_rust_ledger::config::AppConfig {
+        import_income_account_id: Some(sales.clone()),
+        import_expense_account_id: Some(expenses.clone()),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let (status, body) = post_json(
+        &app,
+        &format!("/api/v1/import/bank?account_id={}", bank),
+        json!({
+            "transactions": [
+                { "date": "2024-03-01", "amount": "120.00", "description": "Card settlement" },
+                { "date": "2024-03-02", "amount": "-35.00", "description": "Office chair" },
+                { "date": "2024-03-03", "amount": "-2.00", "description": "Monthly fee" }
+            ],
+            "rules": [{ "pattern": "fee", "contra_account_id": fees }]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    for (account, expected) in [
+        (&bank, "83.00"),
+        (&sales, "120.00"),
+        (&expenses, "35.00"),
+        (&fees, "2.00"),
+    ] {
+        let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", account)).await;
+        assert_eq!(balance["data"]["balance"], expected, "{}", account);
+    }
+}
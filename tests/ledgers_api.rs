@@ -0,0 +1,70 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn test_account_quota_blocks_creation() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, _) = put_json(
+        &app,
+        "/api/v1/ledgers/acme/quota",
+        json!({ "max_accounts": 2, "max_transactions": null }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for code in ["1000", "1100"] {
+        create_account_with(
+            &app,
+            json!({ "code": code, "name": "Acme", "account_type": "asset", "ledger_id": "acme" }),
+        )
+        .await;
+    }
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/accounts",
+        json!({ "code": "1200", "name": "Acme", "account_type": "asset", "ledger_id": "acme" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "quota exceeded");
+
+    // Other ledgers stay unlimited
+    create_account(&app, "1200", "Cash", "asset").await;
+
+    let (_, usage) = get_json(&app, "/api/v1/ledgers/acme/quota").await;
+    assert_eq!(usage["data"]["account_count"], 2);
+    assert_eq!(usage["data"]["max_accounts"], 2);
+}
+
+#[actix_rt::test]
+async fn test_transaction_quota_blocks_posting() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    put_json(
+        &app,
+        "/api/v1/ledgers/default/quota",
+        json!({ "max_transactions": 1 }),
+    )
+    .await;
+
+    post_transfer(&app, "SALE-1", &cash, &revenue, "10.00").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("SALE-2", &cash, &revenue, "10.00"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "quota exceeded");
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+use serde_json::{json, Value};
+
+async fn post_dated<S, B>(
+    app: &S,
+    reference: &str,
+    debit: &str,
+    credit: &str,
+    amount: &str,
+    date: &str,
+) where
+    S: actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse<B>,
+        Error = actix_web::Error,
+    >,
+    B: actix_web::body::MessageBody,
+{
+    let mut body = transfer_body(reference, debit, credit, amount);
+    body["transaction_date"] = json!(date);
+    let (status, _) = post_json(app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+fn account_row<'a>(snapshot: &'a Value, account_id: &str) -> &'a Value {
+    snapshot["data"]["balances"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["account_id"] == account_id)
+        .unwrap()
+}
+
+#[actix_rt::test]
+async fn test_snapshot_freezes_period_end_balances() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_dated(&app, "JAN-1", &cash, &revenue, "100.00", "2024-01-15").await;
+    post_dated(&app, "FEB-1", &cash, &revenue, "40.00", "2024-02-10").await;
+
+    let (_, period) = post_json(
+        &app,
+        "/api/v1/periods",
+        json!({ "name": "2024-01", "start_date": "2024-01-01", "end_date": "2024-01-31" }),
+    )
+    .await;
+    let snapshot_uri = format!(
+        "/api/v1/periods/{}/snapshot",
+        period["data"]["id"].as_str().unwrap()
+    );
+
+    let (status, snapshot) = post_json(&app, &snapshot_uri, json!({})).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(snapshot["data"]["as_of"], "2024-01-31");
+    assert_eq!(snapshot["data"]["has_drift"], false);
+    assert_eq!(account_row(&snapshot, &cash)["balance"], "100.00");
+    assert_eq!(account_row(&snapshot, &revenue)["balance"], "100.00");
+
+    let (status, _) = post_json(&app, &snapshot_uri, json!({})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Activity after the period end leaves the snapshot matching
+    post_dated(&app, "FEB-2", &cash, &revenue, "5.00", "2024-02-11").await;
+    let (status, snapshot) = get_json(&app, &snapshot_uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(snapshot["data"]["has_drift"], false);
+}
+
+#[actix_rt::test]
+async fn test_back_dated_change_shows_drift() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_dated(&app, "JAN-1", &cash, &revenue, "100.00", "2024-01-15").await;
+
+    let (_, period) = post_json(
+        &app,
+        "/api/v1/periods",
+        json!({ "name": "2024-01", "start_date": "2024-01-01", "end_date": "2024-01-31" }),
+    )
+    .await;
+    let snapshot_uri = format!(
+        "/api/v1/periods/{}/snapshot",
+        period["data"]["id"].as_str().unwrap()
+    );
+
+    let (status, _) = get_json(&app, &snapshot_uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = post_json(&app, &snapshot_uri, json!({})).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    post_dated(&app, "JAN-LATE", &cash, &revenue, "25.00", "2024-01-20").await;
+
+    let (_, snapshot) = get_json(&app, &snapshot_uri).await;
+    assert_eq!(snapshot["data"]["has_drift"], true);
+    let cash_row = account_row(&snapshot, &cash);
+    assert_eq!(cash_row["balance"], "100.00");
+    assert_eq!(cash_row["live_balance"], "125.00");
+    assert_eq!(cash_row["drift"], "25.00");
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+use diesel_migrations::MigrationHarness;
+use double_rust_ledger::database;
+use serde_json::Value;
+
+fn check<'a>(body: &'a Value, name: &str) -> &'a Value {
+    body["data"]["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == name)
+        .unwrap()
+}
+
+#[actix_rt::test]
+async fn test_ready_when_every_check_passes() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let (status, body) = get_json(&app, "/ready").await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["ready"], true);
+    for name in ["pool", "database", "migrations"] {
+        assert_eq!(check(&body, name)["ok"], true, "{}", name);
+    }
+}
+
+#[actix_rt::test]
+async fn test_not_ready_with_pending_migrations() {
+    let pool = test_pool();
+    let reverted = pool
+        .get()
+        .unwrap()
+        .revert_last_migration(database::MIGRATIONS)
+        .unwrap()
+        .to_string();
+    let app = init_app!(pool);
+
+    let (status, body) = get_json(&app, "/ready").await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{}", body);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["data"]["ready"], false);
+    let migrations = check(&body, "migrations");
+    assert_eq!(migrations["ok"], false);
+    assert!(
+        migrations["detail"].as_str().unwrap().contains(&reverted),
+        "{}",
+        migrations
+    );
+    assert_eq!(check(&body, "database")["ok"], true);
+    assert_eq!(check(&body, "pool")["ok"], true);
+}
@@ -0,0 +1,470 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+
+#[actix_rt::test]
+async fn test_trial_balance_totals_each_currency_separately() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let euro_bank = create_account_with(
+        &app,
+        serde_json::json!({ "code": "1100", "name": "Euro Bank", "account_type": "asset", "currency": "EUR" }),
+    )
+    .await;
+    let euro_revenue = create_account_with(
+        &app,
+        serde_json::json!({ "code": "4100", "name": "Euro Sales", "account_type": "revenue", "currency": "EUR" }),
+    )
+    .await;
+
+    post_transfer(&app, "SALE-USD", &cash, &revenue, "100.00").await;
+    post_transfer(&app, "SALE-EUR", &euro_bank, &euro_revenue, "40.00").await;
+
+    let (status, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let report = &body["data"];
+    assert_eq!(report["is_balanced"], true);
+    assert_eq!(
+        report["totals"],
+        serde_json::json!([
+            { "currency": "EUR", "total_debits": "40.00", "total_credits": "40.00", "is_balanced": true },
+            { "currency": "USD", "total_debits": "100.00", "total_credits": "100.00", "is_balanced": true }
+        ])
+    );
+    // Dollars and euros are never added together
+    assert!(report["total_debits"].is_null());
+    assert!(report["total_credits"].is_null());
+
+    let euro_row = report["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["account_id"] == euro_bank.as_str())
+        .unwrap();
+    assert_eq!(euro_row["currency"], "EUR");
+}
+
+#[actix_rt::test]
+async fn test_summed_reports_reject_mixed_currencies() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_transfer(&app, "SALE-USD", &cash, &revenue, "100.00").await;
+
+    let (_, period) = post_json(
+        &app,
+        "/api/v1/periods",
+        serde_json::json!({ "name": "FY", "start_date": "2024-01-01", "end_date": "2099-12-31" }),
+    )
+    .await;
+    let period_id = period["data"]["id"].as_str().unwrap().to_string();
+
+    let reports = [
+        "/api/v1/reports/balance-by-date?account_type=asset&from=2024-01-01&to=2024-03-31"
+            .to_string(),
+        "/api/v1/reports/account-type-summary".to_string(),
+        format!(
+            "/api/v1/reports/balance-sheet/compare?period_a={}&period_b={}",
+            period_id, period_id
+        ),
+    ];
+    for uri in &reports {
+        let (status, body) = get_json(&app, uri).await;
+        assert_eq!(status, StatusCode::OK, "{}: {}", uri, body);
+    }
+
+    let euro_bank = create_account_with(
+        &app,
+        serde_json::json!({ "code": "1100", "name": "Euro Bank", "account_type": "asset", "currency": "EUR" }),
+    )
+    .await;
+    let euro_revenue = create_account_with(
+        &app,
+        serde_json::json!({ "code": "4100", "name": "Euro Sales", "account_type": "revenue", "currency": "EUR" }),
+    )
+    .await;
+    post_transfer(&app, "SALE-EUR", &euro_bank, &euro_revenue, "40.00").await;
+
+    for uri in &reports {
+        let (status, body) = get_json(&app, uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", uri, body);
+        assert_eq!(body["message"], "mixed currencies; specify base");
+    }
+}
+
+#[actix_rt::test]
+async fn test_trial_balance_excludes_memo_accounts() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let headcount = create_account_with(
+        &app,
+        serde_json::json!({ "code": "9000", "name": "Headcount", "account_type": "asset", "is_memo": true }),
+    )
+    .await;
+    let headcount_control = create_account_with(
+        &app,
+        serde_json::json!({ "code": "9001", "name": "Headcount Control", "account_type": "equity", "is_memo": true }),
+    )
+    .await;
+
+    post_transfer(&app, "SALE-1", &cash, &revenue, "100.00").await;
+    post_transfer(&app, "STAT-1", &headcount, &headcount_control, "5").await;
+
+    let (status, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let report = &body["data"];
+    assert_eq!(report["is_balanced"], true);
+    assert_eq!(report["total_debits"], "100.00");
+    assert_eq!(report["total_credits"], "100.00");
+
+    // The memo account is still reported with its own balance
+    let memo = report["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["account_id"] == headcount.as_str())
+        .unwrap();
+    assert_eq!(memo["is_memo"], true);
+    assert_eq!(memo["balance"], "5.00");
+}
+
+#[actix_rt::test]
+async fn test_general_ledger_orders_accounts_and_ends_at_balance() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let expense = create_account(&app, "5000", "Rent", "expense").await;
+
+    post_transfer(&app, "SALE-1", &cash, &revenue, "250.00").await;
+    post_transfer(&app, "SALE-2", &cash, &revenue, "100.00").await;
+    post_transfer(&app, "RENT-1", &expense, &cash, "80.00").await;
+
+    let (status, body) = get_json(&app, "/api/v1/reports/general-ledger").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["success"], true);
+
+    let sections = body["data"].as_array().unwrap();
+    let codes: Vec<&str> = sections
+        .iter()
+        .map(|s| s["account_code"].as_str().unwrap())
+        .collect();
+    assert_eq!(codes, vec!["1000", "4000", "5000"]);
+
+    for section in sections {
+        let account_id = section["account_id"].as_str().unwrap();
+        let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", account_id)).await;
+        let last = section["entries"].as_array().unwrap().last().unwrap();
+        assert_eq!(last["running_balance"], balance["data"]["balance"]);
+    }
+
+    let cash_section = &sections[0];
+    assert_eq!(cash_section["entries"].as_array().unwrap().len(), 3);
+    assert_eq!(cash_section["entries"][2]["running_balance"], "270.00");
+}
+
+#[actix_rt::test]
+async fn test_general_ledger_summarizes_the_window() {
+    use rust_decimal::Decimal;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account_with(
+        &app,
+        serde_json::json!({
+            "code": "1000",
+            "name": "Cash",
+            "account_type": "asset",
+            "opening_balance": "500.00",
+            "opening_date": "2024-01-31"
+        }),
+    )
+    .await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for (reference, date, debit, credit, amount) in [
+        ("SALE-1", "2024-02-05", &cash, &revenue, "120.00"),
+        ("SALE-2", "2024-03-10", &cash, &revenue, "80.00"),
+        ("REFUND-1", "2024-03-20", &revenue, &cash, "30.00"),
+    ] {
+        let mut body = transfer_body(reference, debit, credit, amount);
+        body["transaction_date"] = serde_json::json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, body) = get_json(&app, "/api/v1/reports/general-ledger?from=2024-03-01").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let cash_section = &body["data"][0];
+    assert_eq!(cash_section["account_code"], "1000");
+    assert_eq!(cash_section["opening_balance"], "620.00");
+    assert_eq!(cash_section["net_change"], "50.00");
+    assert_eq!(cash_section["closing_balance"], "670.00");
+
+    let amount = |value: &serde_json::Value| value.as_str().unwrap().parse::<Decimal>().unwrap();
+    let entries = cash_section["entries"].as_array().unwrap();
+    let summed: Decimal = entries
+        .iter()
+        .map(|e| amount(&e["debit_amount"]) - amount(&e["credit_amount"]))
+        .sum();
+    let opening = amount(&cash_section["opening_balance"]);
+    let closing = amount(&cash_section["closing_balance"]);
+    assert_eq!(closing - opening, amount(&cash_section["net_change"]));
+    assert_eq!(summed, amount(&cash_section["net_change"]));
+    assert_eq!(entries.last().unwrap()["running_balance"], "670.00");
+}
+
+#[actix_rt::test]
+async fn test_trial_balance_cache_is_invalidated_by_new_transaction() {
+    use diesel::prelude::*;
+    use double_rust_ledger::models::{NewEntry, NewTransaction};
+    use double_rust_ledger::schema::{entries, transactions};
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "100.00").await;
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(body["data"]["total_debits"], "100.00");
+
+    // Write behind the API's back: a cached report must not notice
+    let mut conn = pool.get().unwrap();
+    let now = chrono::Utc::now().to_rfc3339();
+    diesel::insert_into(transactions::table)
+        .values(&NewTransaction {
+            id: "direct-txn".to_string(),
+            reference: "DIRECT-1".to_string(),
+            description: "Direct insert".to_string(),
+            transaction_date: now.clone(),
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            ledger_id: "default".to_string(),
+            reverses_transaction_id: None,
+            status: "posted".to_string(),
+            period_id: None,
+            posting_date: now.clone(),
+            metadata: None,
+        })
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(entries::table)
+        .values(&NewEntry {
+            id: "direct-entry".to_string(),
+            transaction_id: "direct-txn".to_string(),
+            account_id: cash.clone(),
+            debit_amount: 70_000,
+            credit_amount: 0,
+            description: None,
+            created_at: now,
+            currency: "USD".to_string(),
+        })
+        .execute(&mut conn)
+        .unwrap();
+
+    let (_, cached) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(cached["data"]["total_debits"], "100.00");
+
+    post_transfer(&app, "SALE-2", &cash, &revenue, "50.00").await;
+
+    let (_, refreshed) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(refreshed["data"]["total_debits"], "157.00");
+}
+
+#[actix_rt::test]
+async fn test_balance_by_date_is_cumulative_per_month() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let equity = create_account(&app, "3000", "Capital", "equity").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for (reference, date, debit, credit, amount) in [
+        ("CAP-0", "2023-12-15", &cash, &equity, "100.00"),
+        ("CAP-1", "2024-01-10", &cash, &equity, "1000.00"),
+        ("SALE-1", "2024-02-05", &cash, &revenue, "200.00"),
+        ("MOVE-1", "2024-02-20", &bank, &cash, "500.00"),
+        ("SALE-2", "2024-03-31", &bank, &revenue, "300.00"),
+        ("SALE-3", "2024-04-02", &bank, &revenue, "50.00"),
+    ] {
+        let mut body = transfer_body(reference, debit, credit, amount);
+        body["transaction_date"] = serde_json::json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, body) = get_json(
+        &app,
+        "/api/v1/reports/balance-by-date?account_type=asset&from=2024-01-01&to=2024-03-31&interval=month",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let points: Vec<(&str, &str)> = body["data"]["points"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| (p["date"].as_str().unwrap(), p["balance"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        points,
+        vec![
+            ("2024-01-31", "1100.00"),
+            ("2024-02-29", "1300.00"),
+            ("2024-03-31", "1600.00"),
+        ]
+    );
+
+    let (status, _) = get_json(
+        &app,
+        "/api/v1/reports/balance-by-date?account_type=asset&from=2000-01-01&to=2024-12-31&interval=day",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_account_type_summary_ranks_top_accounts() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let petty = create_account(&app, "1200", "Petty Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    post_transfer(&app, "SALE-1", &cash, &revenue, "500.00").await;
+    post_transfer(&app, "SALE-2", &bank, &revenue, "1200.00").await;
+    post_transfer(&app, "SALE-3", &petty, &revenue, "50.00").await;
+
+    let (status, body) = get_json(&app, "/api/v1/reports/account-type-summary?top=2").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let summaries = body["data"].as_array().unwrap();
+    assert_eq!(summaries.len(), 5);
+
+    let assets = &summaries[0];
+    assert_eq!(assets["account_type"], "asset");
+    assert_eq!(assets["total_balance"], "1750.00");
+    assert_eq!(assets["account_count"], 3);
+    assert_eq!(assets["link"], "/api/v1/balance?account_type=asset");
+
+    let top: Vec<&str> = assets["top_accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["account_code"].as_str().unwrap())
+        .collect();
+    assert_eq!(top, vec!["1100", "1000"]);
+    assert_eq!(
+        assets["top_accounts"][0]["link"],
+        format!("/api/v1/balance/{}", bank)
+    );
+
+    let revenue_summary = summaries
+        .iter()
+        .find(|s| s["account_type"] == "revenue")
+        .unwrap();
+    assert_eq!(revenue_summary["total_balance"], "1750.00");
+
+    let (status, _) = get_json(&app, "/api/v1/reports/account-type-summary?top=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_balance_sheet_compare_reports_period_deltas() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let loan = create_account(&app, "2500", "Loan", "liability").await;
+    let equity = create_account(&app, "3000", "Capital", "equity").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for (reference, date, debit, credit, amount) in [
+        ("CAP-1", "2024-01-10", &cash, &equity, "1000.00"),
+        ("LOAN-1", "2024-02-03", &cash, &loan, "500.00"),
+        ("SALE-1", "2024-02-20", &cash, &revenue, "80.00"),
+    ] {
+        let mut body = transfer_body(reference, debit, credit, amount);
+        body["transaction_date"] = serde_json::json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let mut period_ids = Vec::new();
+    for (name, start, end) in [
+        ("2024-01", "2024-01-01", "2024-01-31"),
+        ("2024-02", "2024-02-01", "2024-02-29"),
+    ] {
+        let (_, period) = post_json(
+            &app,
+            "/api/v1/periods",
+            serde_json::json!({ "name": name, "start_date": start, "end_date": end }),
+        )
+        .await;
+        period_ids.push(period["data"]["id"].as_str().unwrap().to_string());
+    }
+
+    let (status, body) = get_json(
+        &app,
+        &format!(
+            "/api/v1/reports/balance-sheet/compare?period_a={}&period_b={}",
+            period_ids[0], period_ids[1]
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["period_a"]["name"], "2024-01");
+
+    let sections = body["data"]["sections"].as_array().unwrap();
+    let section_types: Vec<&str> = sections
+        .iter()
+        .map(|s| s["account_type"].as_str().unwrap())
+        .collect();
+    assert_eq!(section_types, ["asset", "liability", "equity"]);
+
+    let row = |section: usize| {
+        let account = &sections[section]["accounts"][0];
+        (
+            account["balance_a"].as_str().unwrap(),
+            account["balance_b"].as_str().unwrap(),
+            account["delta"].as_str().unwrap(),
+        )
+    };
+    assert_eq!(sections[0]["accounts"][0]["account_id"], cash);
+    assert_eq!(row(0), ("1000.00", "1580.00", "580.00"));
+    assert_eq!(sections[1]["accounts"][0]["account_id"], loan);
+    assert_eq!(row(1), ("0.00", "500.00", "500.00"));
+    assert_eq!(row(2), ("1000.00", "1000.00", "0.00"));
+    assert_eq!(sections[0]["delta"], "580.00");
+    assert_eq!(sections[1]["delta"], "500.00");
+
+    let (status, _) = get_json(
+        &app,
+        &format!(
+            "/api/v1/reports/balance-sheet/compare?period_a={}&period_b=missing",
+            period_ids[0]
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+use double_rust_ledger::middleware::RequestTimeout;
+
+async fn long_running() -> HttpResponse {
+    actix_rt::time::sleep(Duration::from_millis(1200)).await;
+    HttpResponse::Ok().finish()
+}
+
+#[actix_rt::test]
+async fn test_exempt_routes_outlive_the_timeout() {
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestTimeout::new(1).exempt(["/api/v1/reports"]))
+            .route(
+                "/api/v1/reports/general-ledger",
+                web::get().to(long_running),
+            )
+            .route("/api/v1/reportsheet", web::get().to(long_running))
+            .route("/api/v1/transactions", web::get().to(long_running)),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/v1/reports/general-ledger")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Only whole path segments are exempt
+    for uri in ["/api/v1/transactions", "/api/v1/reportsheet"] {
+        let err = test::try_call_service(&app, test::TestRequest::get().uri(uri).to_request())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::REQUEST_TIMEOUT,
+            "{}",
+            uri
+        );
+    }
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use common::test_pool;
+use diesel::migration::MigrationSource;
+use diesel::prelude::*;
+use diesel::sqlite::Sqlite;
+use diesel_migrations::MigrationHarness;
+use double_rust_ledger::database;
+use double_rust_ledger::schema::{accounts, entries, unreadable_amounts};
+use double_rust_ledger::seed;
+
+#[test]
+fn test_startup_refuses_database_ahead_of_binary() {
+    let pool = test_pool();
+
+    // Already migrated databases start again without complaint
+    database::run_migrations(&pool).unwrap();
+
+    // A newer build applied a migration this binary does not embed
+    let mut conn = pool.get().unwrap();
+    diesel::sql_query("INSERT INTO __diesel_schema_migrations (version) VALUES ('20990101000001')")
+        .execute(&mut conn)
+        .unwrap();
+    drop(conn);
+
+    let message = database::run_migrations(&pool).unwrap_err().to_string();
+    assert!(
+        message.contains("Database schema is ahead of this build"),
+        "{}",
+        message
+    );
+    assert!(message.contains("20990101000001"), "{}", message);
+}
+
+#[test]
+fn test_concurrent_startups_migrate_once() {
+    let path = std::env::temp_dir().join(format!("ledger-test-{}.db", uuid::Uuid::new_v4()));
+    let barrier = Arc::new(Barrier::new(2));
+
+    let starts: Vec<_> = (0..2)
+        .map(|_| {
+            let path = path.clone();
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                let pool = database::create_pool(path.to_str().unwrap(), 2).unwrap();
+                barrier.wait();
+                database::run_migrations(&pool)
+            })
+        })
+        .collect();
+    for start in starts {
+        start.join().unwrap().unwrap();
+    }
+
+    let pool = database::create_pool(path.to_str().unwrap(), 2).unwrap();
+    let mut conn = pool.get().unwrap();
+    let applied = conn.applied_migrations().unwrap();
+    let embedded = MigrationSource::<Sqlite>::migrations(&database::MIGRATIONS).unwrap();
+    assert_eq!(applied.len(), embedded.len());
+
+    // The last migration's column is there, so no step was skipped or repeated
+    diesel::sql_query("SELECT posting_date FROM transactions")
+        .execute(&mut conn)
+        .unwrap();
+}
+
+/// Migrates a fresh database up to, but not including, the migration named `name`.
+fn migrate_until(path: &std::path::Path, name: &str) -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(path.to_str().unwrap()).unwrap();
+    while !conn
+        .pending_migrations(database::MIGRATIONS)
+        .unwrap()
+        .first()
+        .is_some_and(|m| m.name().to_string().ends_with(name))
+    {
+        conn.run_next_migration(database::MIGRATIONS).unwrap();
+    }
+    conn
+}
+
+#[test]
+fn test_text_amounts_migrate_to_storage_units() {
+    let path = std::env::temp_dir().join(format!("ledger-test-{}.db", uuid::Uuid::new_v4()));
+    let mut conn = migrate_until(&path, "store_entry_amounts_as_integers");
+
+    diesel::sql_query(
+        "INSERT INTO entries (id, transaction_id, account_id, debit_amount, credit_amount, created_at)
+         VALUES ('e1', 't1', 'a1', '100.00', '0', '2024-01-01'),
+                ('e2', 't1', 'a2', '0', '0.1', '2024-01-01'),
+                ('e3', 't1', 'a3', '0.0001', '12345678.5', '2024-01-01')",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    conn.run_pending_migrations(database::MIGRATIONS).unwrap();
+
+    let amounts: Vec<(String, i64, i64)> = entries::table
+        .select((entries::id, entries::debit_amount, entries::credit_amount))
+        .order(entries::id.asc())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(
+        amounts,
+        vec![
+            ("e1".to_string(), 1_000_000, 0),
+            ("e2".to_string(), 0, 1000),
+            ("e3".to_string(), 1, 123_456_785_000),
+        ]
+    );
+}
+
+#[test]
+fn test_unreadable_text_amounts_are_zeroed_and_flagged() {
+    let path = std::env::temp_dir().join(format!("ledger-test-{}.db", uuid::Uuid::new_v4()));
+    let mut conn = migrate_until(&path, "store_entry_amounts_as_integers");
+
+    // e2 was flagged, raw value kept, by the old repair endpoint's flag mode
+    diesel::sql_query(
+        "INSERT INTO entries (id, transaction_id, account_id, debit_amount, credit_amount, created_at, amount_flagged)
+         VALUES ('e1', 't1', 'a1', '1O0.00', '5.00', '2024-01-01', 0),
+                ('e2', 't1', 'a2', '0', 'n/a', '2024-01-01', 1),
+                ('e3', 't1', 'a3', '0.00001', '99999999999999999999', '2024-01-01', 0)",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    conn.run_pending_migrations(database::MIGRATIONS).unwrap();
+
+    let amounts: Vec<(String, i64, i64, bool)> = entries::table
+        .select((
+            entries::id,
+            entries::debit_amount,
+            entries::credit_amount,
+            entries::amount_flagged,
+        ))
+        .order(entries::id.asc())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(
+        amounts,
+        vec![
+            ("e1".to_string(), 0, 50_000, true),
+            ("e2".to_string(), 0, 0, true),
+            ("e3".to_string(), 0, 0, true),
+        ]
+    );
+
+    let raw: Vec<(String, String, String)> = unreadable_amounts::table
+        .select((
+            unreadable_amounts::entry_id,
+            unreadable_amounts::field,
+            unreadable_amounts::raw_value,
+        ))
+        .order((
+            unreadable_amounts::entry_id.asc(),
+            unreadable_amounts::field.asc(),
+        ))
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(
+        raw,
+        vec![
+            ("e1".into(), "debit_amount".into(), "1O0.00".into()),
+            ("e2".into(), "credit_amount".into(), "n/a".into()),
+            (
+                "e3".into(),
+                "credit_amount".into(),
+                "99999999999999999999".into()
+            ),
+            ("e3".into(), "debit_amount".into(), "0.00001".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_rows_from_before_currencies_get_the_ledger_currency() {
+    let path = std::env::temp_dir().join(format!("ledger-test-{}.db", uuid::Uuid::new_v4()));
+    let mut conn = migrate_until(&path, "add_currency");
+
+    diesel::sql_query(
+        "INSERT INTO accounts (id, code, name, account_type, created_at, updated_at)
+         VALUES ('a1', '1000', 'Cash', 'asset', '2024-01-01', '2024-01-01')",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    diesel::sql_query(
+        "INSERT INTO entries (id, transaction_id, account_id, debit_amount, credit_amount, created_at)
+         VALUES ('e1', 't1', 'a1', '10.00', '0', '2024-01-01')",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    conn.run_pending_migrations(database::MIGRATIONS).unwrap();
+
+    assert_eq!(seed::backfill_currency(&mut conn, "EUR").unwrap(), 2);
+    assert_eq!(seed::backfill_currency(&mut conn, "EUR").unwrap(), 0);
+
+    let account_currency: String = accounts::table
+        .find("a1")
+        .select(accounts::currency)
+        .first(&mut conn)
+        .unwrap();
+    let entry_currency: String = entries::table
+        .find("e1")
+        .select(entries::currency)
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(account_currency, "EUR");
+    assert_eq!(entry_currency, "EUR");
+}
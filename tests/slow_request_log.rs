@@ -0,0 +1,73 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use actix_web::{test, web, App, HttpResponse};
+use diesel::prelude::*;
+use double_rust_ledger::database::{self, DbPool};
+use double_rust_ledger::middleware::SlowRequestLog;
+use log::{Level, Log, Metadata, Record};
+use uuid::Uuid;
+
+/// Keeps every warning so the test can see what the middleware logged.
+struct CapturingLogger {
+    warnings: Mutex<Vec<String>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.warnings
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    warnings: Mutex::new(Vec::new()),
+};
+
+async fn slow(pool: web::Data<DbPool>) -> HttpResponse {
+    let mut conn = pool.get().unwrap();
+    diesel::sql_query("SELECT 1").execute(&mut conn).unwrap();
+    actix_rt::time::sleep(Duration::from_millis(60)).await;
+    HttpResponse::Ok().finish()
+}
+
+async fn fast() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+#[actix_rt::test]
+async fn test_only_requests_over_threshold_are_logged() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let path = std::env::temp_dir().join(format!("ledger-test-{}.db", Uuid::new_v4()));
+    let pool = database::create_pool(path.to_str().unwrap(), 2).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool))
+            .wrap(SlowRequestLog::new(30))
+            .route("/slow", web::get().to(slow))
+            .route("/fast", web::get().to(fast)),
+    )
+    .await;
+
+    test::call_service(&app, test::TestRequest::get().uri("/fast").to_request()).await;
+    test::call_service(&app, test::TestRequest::get().uri("/slow").to_request()).await;
+
+    let warnings = LOGGER.warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].starts_with("Slow request: GET /slow took "));
+    assert!(warnings[0].contains("(db "));
+    assert!(warnings[0].ends_with("threshold 30 ms)"));
+}
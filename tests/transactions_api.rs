@@ -0,0 +1,1958 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::*;
+use serde_json::{json, Value};
+
+#[actix_rt::test]
+async fn test_transaction_entries_are_returned_in_stable_order() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "SPLIT-1",
+            "description": "Split sale",
+            "entries": [
+                { "account_id": cash, "debit_amount": "40.00" },
+                { "account_id": bank, "debit_amount": "60.00" },
+                { "account_id": revenue, "credit_amount": "100.00" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let transaction_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let uri = format!("/api/v1/transactions/{}", transaction_id);
+    let (_, first) = get_json(&app, &uri).await;
+    let entry_keys = |body: &serde_json::Value| -> Vec<(String, String)> {
+        body["data"]["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                (
+                    e["created_at"].as_str().unwrap().to_string(),
+                    e["id"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+
+    let first_keys = entry_keys(&first);
+    assert_eq!(first_keys.len(), 3);
+    let mut sorted = first_keys.clone();
+    sorted.sort();
+    assert_eq!(first_keys, sorted);
+
+    for _ in 0..5 {
+        let (_, again) = get_json(&app, &uri).await;
+        assert_eq!(entry_keys(&again), first_keys);
+    }
+}
+
+#[actix_rt::test]
+async fn test_entry_account_outside_ledger_is_rejected() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let foreign_revenue = create_account_with(
+        &app,
+        json!({ "code": "4000", "name": "Acme Sales", "account_type": "revenue", "ledger_id": "acme" }),
+    )
+    .await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("XLEDGER-1", &cash, &foreign_revenue, "10.00"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "account not in ledger");
+
+    // Posting inside the account's own ledger is allowed
+    let acme_cash = create_account_with(
+        &app,
+        json!({ "code": "1000-A", "name": "Acme Cash", "account_type": "asset", "ledger_id": "acme" }),
+    )
+    .await;
+    let mut body = transfer_body("ACME-1", &acme_cash, &foreign_revenue, "10.00");
+    body["ledger_id"] = json!("acme");
+    let (status, created) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["data"]["ledger_id"], "acme");
+}
+
+#[actix_rt::test]
+async fn test_batch_reports_errors_per_item() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut empty_reference = transfer_body("", &cash, &revenue, "10.00");
+    empty_reference["reference"] = json!("");
+    let mut unbalanced = transfer_body("BATCH-3", &cash, &revenue, "10.00");
+    unbalanced["entries"][1]["credit_amount"] = json!("9.00");
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions/batch",
+        json!({
+            "transactions": [
+                transfer_body("BATCH-0", &cash, &revenue, "10.00"),
+                empty_reference,
+                transfer_body("BATCH-2", &cash, &revenue, "20.00"),
+                unbalanced
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let results = body["data"].as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0]["status"], "created");
+    assert_eq!(results[2]["status"], "created");
+
+    assert_eq!(results[1]["index"], 1);
+    assert_eq!(results[1]["status"], "failed");
+    assert!(results[1]["errors"][0]
+        .as_str()
+        .unwrap()
+        .starts_with("reference"));
+
+    assert_eq!(results[3]["index"], 3);
+    assert_eq!(results[3]["status"], "failed");
+    assert_eq!(
+        results[3]["errors"][0],
+        "Total debits must equal total credits"
+    );
+
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(balance["data"]["balance"], "30.00");
+}
+
+#[actix_rt::test]
+async fn test_entry_errors_name_the_indexed_field() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "BAD-1",
+            "description": "Malformed entries",
+            "entries": [
+                { "account_id": cash, "debit_amount": "10.00" },
+                { "account_id": revenue, "credit_amount": "10.00", "description": "x".repeat(256) },
+                { "account_id": cash, "debit_amount": "-5.00" },
+                { "account_id": "", "debit_amount": "5.00" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Validation failed");
+    let errors: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e.as_str().unwrap())
+        .collect();
+    assert!(
+        errors.contains(&"entries[1].description: failed length validation"),
+        "{:?}",
+        errors
+    );
+    assert!(
+        errors.contains(&"entries[2].debit_amount: must not be negative"),
+        "{:?}",
+        errors
+    );
+    assert!(
+        errors.contains(&"entries[3].account_id: must not be empty"),
+        "{:?}",
+        errors
+    );
+
+    let (_, body) = post_json(
+        &app,
+        "/api/v1/transactions/batch",
+        json!({
+            "transactions": [{
+                "reference": "BAD-2",
+                "description": "Negative credit",
+                "entries": [
+                    { "account_id": cash, "debit_amount": "-1.00" },
+                    { "account_id": revenue, "credit_amount": "-1.00" }
+                ]
+            }]
+        }),
+    )
+    .await;
+    assert_eq!(
+        body["data"][0]["errors"],
+        json!([
+            "entries[0].debit_amount: must not be negative",
+            "entries[1].credit_amount: must not be negative"
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn test_atomic_batch_writes_nothing_on_failure() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut unbalanced = transfer_body("ATOMIC-1", &cash, &revenue, "10.00");
+    unbalanced["entries"][1]["credit_amount"] = json!("1.00");
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions/batch",
+        json!({
+            "atomic": true,
+            "transactions": [transfer_body("ATOMIC-0", &cash, &revenue, "10.00"), unbalanced]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["data"][0]["status"], "skipped");
+    assert_eq!(body["data"][1]["status"], "failed");
+
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(balance["data"]["balance"], "0.00");
+}
+
+#[actix_rt::test]
+async fn test_verify_balanced_transaction() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let transaction = post_transfer(&app, "SALE-1", &cash, &revenue, "75.00").await;
+
+    let (status, body) = get_json(
+        &app,
+        &format!(
+            "/api/v1/transactions/{}/verify",
+            transaction["id"].as_str().unwrap()
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["is_balanced"], true);
+    assert_eq!(body["data"]["debit_total"], "75.00");
+    assert_eq!(body["data"]["credit_total"], "75.00");
+    assert_eq!(body["data"]["accounts_valid"], true);
+    assert_eq!(body["data"]["accounts"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn test_verify_transaction_with_deleted_account() {
+    use diesel::prelude::*;
+    use double_rust_ledger::schema::accounts;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let transaction = post_transfer(&app, "SALE-1", &cash, &revenue, "75.00").await;
+
+    let mut conn = pool.get().unwrap();
+    diesel::delete(accounts::table.find(&revenue))
+        .execute(&mut conn)
+        .unwrap();
+
+    let (_, body) = get_json(
+        &app,
+        &format!(
+            "/api/v1/transactions/{}/verify",
+            transaction["id"].as_str().unwrap()
+        ),
+    )
+    .await;
+    assert_eq!(body["data"]["is_balanced"], true);
+    assert_eq!(body["data"]["accounts_valid"], false);
+
+    let missing = body["data"]["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["account_id"] == revenue.as_str())
+        .unwrap();
+    assert_eq!(missing["exists"], false);
+}
+
+#[actix_rt::test]
+async fn test_audit_trail_links_original_and_reversal() {
+    use diesel::prelude::*;
+    use double_rust_ledger::schema::transactions;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let original = post_transfer(&app, "SALE-1", &cash, &revenue, "40.00").await;
+    let reversal = post_transfer(&app, "REV-SALE-1", &revenue, &cash, "40.00").await;
+
+    let original_id = original["id"].as_str().unwrap();
+    let reversal_id = reversal["id"].as_str().unwrap();
+
+    let mut conn = pool.get().unwrap();
+    diesel::update(transactions::table.find(reversal_id))
+        .set(transactions::reverses_transaction_id.eq(original_id))
+        .execute(&mut conn)
+        .unwrap();
+
+    let (status, trail) = get_json(
+        &app,
+        &format!("/api/v1/transactions/{}/audit-trail", original_id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(trail["data"]["reverses"].is_null());
+    assert_eq!(trail["data"]["reversed_by"][0]["id"], reversal_id);
+    assert_eq!(trail["data"]["audit_entries"][0]["action"], "created");
+
+    let (_, trail) = get_json(
+        &app,
+        &format!("/api/v1/transactions/{}/audit-trail", reversal_id),
+    )
+    .await;
+    assert_eq!(trail["data"]["reverses"]["id"], original_id);
+    assert_eq!(trail["data"]["reversed_by"].as_array().unwrap().len(), 0);
+}
+
+#[actix_rt::test]
+async fn test_posting_to_parent_rejected_in_leaf_only_mode() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        post_leaf_only: true,
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let assets = create_account(&app, "1000", "Current Assets", "asset").await;
+    let cash = create_account_with(
+        &app,
+        json!({ "code": "1010", "name": "Cash", "account_type": "asset", "parent_id": assets }),
+    )
+    .await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("PARENT-1", &assets, &revenue, "10.00"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains(&assets));
+
+    post_transfer(&app, "LEAF-1", &cash, &revenue, "10.00").await;
+}
+
+#[actix_rt::test]
+async fn test_posting_to_parent_allowed_by_default() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let assets = create_account(&app, "1000", "Current Assets", "asset").await;
+    create_account_with(
+        &app,
+        json!({ "code": "1010", "name": "Cash", "account_type": "asset", "parent_id": assets }),
+    )
+    .await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    post_transfer(&app, "PARENT-1", &assets, &revenue, "10.00").await;
+}
+
+#[actix_rt::test]
+async fn test_concurrent_transactions_with_serialized_writes() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        serialize_writes: true,
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let requests = (0..50).map(|i| {
+        post_json(
+            &app,
+            "/api/v1/transactions",
+            transfer_body(&format!("STRESS-{}", i), &cash, &revenue, "1.25"),
+        )
+    });
+    let results = futures_util::future::join_all(requests).await;
+
+    for (status, body) in &results {
+        assert_eq!(*status, StatusCode::CREATED, "{}", body);
+    }
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["balance"], "62.50");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(body["data"]["total_debits"], "62.50");
+    assert_eq!(body["data"]["is_balanced"], true);
+}
+
+#[actix_rt::test]
+async fn test_voided_transaction_drops_out_of_balances() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "100.00").await;
+    let mistake = post_transfer(&app, "SALE-2", &cash, &revenue, "999.00").await;
+    let void_uri = format!(
+        "/api/v1/transactions/{}/void",
+        mistake["id"].as_str().unwrap()
+    );
+
+    let (status, body) = post_json(&app, &void_uri, json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["voided"], true);
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["balance"], "100.00");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(body["data"]["total_credits"], "100.00");
+
+    // Still on record, unlike a delete
+    let (status, _) = get_json(
+        &app,
+        &format!("/api/v1/transactions/{}", mistake["id"].as_str().unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = post_json(&app, &void_uri, json!({})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[actix_rt::test]
+async fn test_void_refused_in_closed_period() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut body = transfer_body("JAN-1", &cash, &revenue, "10.00");
+    body["transaction_date"] = json!("2024-01-31T18:00:00Z");
+    let (_, created) = post_json(&app, "/api/v1/transactions", body).await;
+    let void_uri = format!(
+        "/api/v1/transactions/{}/void",
+        created["data"]["id"].as_str().unwrap()
+    );
+
+    let (status, period) = post_json(
+        &app,
+        "/api/v1/periods",
+        json!({ "name": "2024-01", "start_date": "2024-01-01", "end_date": "2024-01-31" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let close_uri = format!(
+        "/api/v1/periods/{}/close",
+        period["data"]["id"].as_str().unwrap()
+    );
+    let (status, _) = post_json(&app, &close_uri, json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = post_json(&app, &void_uri, json!({})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "Period 2024-01 is closed");
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["balance"], "10.00");
+}
+
+#[actix_rt::test]
+async fn test_closed_period_refuses_every_write() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut body = transfer_body("JAN-1", &cash, &revenue, "10.00");
+    body["transaction_date"] = json!("2024-01-15");
+    let (_, posted) = post_json(&app, "/api/v1/transactions", body).await;
+    let posted_id = posted["data"]["id"].as_str().unwrap().to_string();
+
+    let mut body = transfer_body("JAN-DRAFT", &cash, &revenue, "5.00");
+    body["transaction_date"] = json!("2024-01-20");
+    body["status"] = json!("draft");
+    let (_, draft) = post_json(&app, "/api/v1/transactions", body).await;
+    let draft_id = draft["data"]["id"].as_str().unwrap().to_string();
+
+    let (_, period) = post_json(
+        &app,
+        "/api/v1/periods",
+        json!({ "name": "2024-01", "start_date": "2024-01-01", "end_date": "2024-01-31" }),
+    )
+    .await;
+    let close_uri = format!(
+        "/api/v1/periods/{}/close",
+        period["data"]["id"].as_str().unwrap()
+    );
+    let (status, _) = post_json(&app, &close_uri, json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut body = transfer_body("JAN-2", &cash, &revenue, "1.00");
+    body["transaction_date"] = json!("2024-01-31");
+    let (status, body) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "Period 2024-01 is closed");
+
+    let post_uri = format!("/api/v1/transactions/{}/post", draft_id);
+    let (status, body) = post_json(&app, &post_uri, json!({})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "Period 2024-01 is closed");
+
+    let entries_uri = format!("/api/v1/transactions/{}/entries", draft_id);
+    let replacement = transfer_body("JAN-DRAFT", &cash, &revenue, "6.00");
+    let (status, _) = put_json(
+        &app,
+        &entries_uri,
+        json!({ "entries": replacement["entries"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = delete(&app, &format!("/api/v1/transactions/{}", posted_id)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "Period 2024-01 is closed");
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(body["data"]["balance"], "10.00");
+
+    // February is still open
+    let mut body = transfer_body("FEB-1", &cash, &revenue, "1.00");
+    body["transaction_date"] = json!("2024-02-01");
+    let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[actix_rt::test]
+async fn test_malformed_dates_are_rejected() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for field in ["transaction_date", "posting_date"] {
+        let mut body = transfer_body("BAD-DATE", &cash, &revenue, "10.00");
+        body[field] = json!("15/10/2026");
+        let (status, body) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["errors"],
+            json!([format!(
+                "{}: must be an RFC 3339 timestamp or a YYYY-MM-DD date",
+                field
+            )])
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_posting_date_drives_period_lock_and_reports() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    // Invoiced in January but posted in February, and the other way round
+    let mut body = transfer_body("INV-JAN", &cash, &revenue, "10.00");
+    body["transaction_date"] = json!("2024-01-31");
+    body["posting_date"] = json!("2024-02-01");
+    let (status, posted_late) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(posted_late["data"]["transaction_date"], "2024-01-31");
+    assert_eq!(posted_late["data"]["posting_date"], "2024-02-01");
+
+    let mut body = transfer_body("ACCRUAL-JAN", &cash, &revenue, "3.00");
+    body["transaction_date"] = json!("2024-02-02");
+    body["posting_date"] = json!("2024-01-31");
+    let (_, posted_early) = post_json(&app, "/api/v1/transactions", body).await;
+
+    let mut body = transfer_body("FEB-1", &cash, &revenue, "1.00");
+    body["transaction_date"] = json!("2024-02-03");
+    let (_, defaulted) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(defaulted["data"]["posting_date"], "2024-02-03");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance?to=2024-01-31").await;
+    assert_eq!(body["data"]["total_debits"], "3.00");
+
+    let (_, period) = post_json(
+        &app,
+        "/api/v1/periods",
+        json!({ "name": "2024-01", "start_date": "2024-01-01", "end_date": "2024-01-31" }),
+    )
+    .await;
+    let close_uri = format!(
+        "/api/v1/periods/{}/close",
+        period["data"]["id"].as_str().unwrap()
+    );
+    let (status, _) = post_json(&app, &close_uri, json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let void_uri = |created: &Value| {
+        format!(
+            "/api/v1/transactions/{}/void",
+            created["data"]["id"].as_str().unwrap()
+        )
+    };
+    let (status, _) = post_json(&app, &void_uri(&posted_late), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = post_json(&app, &void_uri(&posted_early), json!({})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "Period 2024-01 is closed");
+}
+
+#[actix_rt::test]
+async fn test_locked_account_rejects_posting_but_still_reports() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    post_transfer(&app, "SALE-1", &cash, &revenue, "40.00").await;
+
+    let account_uri = format!("/api/v1/accounts/{}", revenue);
+    let (status, body) = put_json(&app, &account_uri, json!({ "postable": false })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["postable"], false);
+    assert_eq!(body["data"]["is_active"], true);
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("SALE-2", &cash, &revenue, "5.00"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "account is locked for posting");
+
+    let (status, body) = get_json(&app, &format!("/api/v1/balance/{}", revenue)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["balance"], "40.00");
+
+    put_json(&app, &account_uri, json!({ "postable": true })).await;
+    post_transfer(&app, "SALE-2", &cash, &revenue, "5.00").await;
+}
+
+#[actix_rt::test]
+async fn test_transaction_list_truncated_at_cap() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        max_list_results: 3,
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    for i in 0..3 {
+        post_transfer(&app, &format!("SALE-{}", i), &cash, &revenue, "10.00").await;
+    }
+
+    let (_, body) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 3);
+    assert!(body.get("meta").is_none());
+
+    for i in 3..5 {
+        post_transfer(&app, &format!("SALE-{}", i), &cash, &revenue, "10.00").await;
+    }
+
+    let (status, body) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"].as_array().unwrap().len(), 3);
+    assert_eq!(body["meta"]["truncated"], true);
+    assert_eq!(body["meta"]["total"], 5);
+    assert_eq!(body["meta"]["limit"], 3);
+}
+
+#[actix_rt::test]
+async fn test_entries_without_description_inherit_the_transaction_description() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut body = transfer_body("SALE-1", &cash, &revenue, "10.00");
+    body["entries"][0]["description"] = json!("Till 3");
+    let (status, created) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let find = |entries: &serde_json::Value, account_id: &str| {
+        entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["account_id"] == account_id)
+            .unwrap()
+            .clone()
+    };
+
+    let id = created["data"]["id"].as_str().unwrap();
+    let (_, fetched) = get_json(&app, &format!("/api/v1/transactions/{}", id)).await;
+    for transaction in [&created, &fetched] {
+        let explicit = find(&transaction["data"]["entries"], &cash);
+        assert_eq!(explicit["description"], "Till 3");
+        assert_eq!(explicit["description_inherited"], false);
+
+        let inherited = find(&transaction["data"]["entries"], &revenue);
+        assert_eq!(inherited["description"], "Transfer SALE-1");
+        assert_eq!(inherited["description_inherited"], true);
+    }
+
+    let (_, listed) = get_json(&app, "/api/v1/entries").await;
+    assert_eq!(find(&listed["data"], &cash)["description"], "Till 3");
+    let inherited = find(&listed["data"], &revenue);
+    assert_eq!(inherited["description"], "Transfer SALE-1");
+    assert_eq!(inherited["description_inherited"], true);
+}
+
+#[actix_rt::test]
+async fn test_references_are_unique_globally_by_default() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut first = transfer_body("INV-001", &cash, &revenue, "10.00");
+    first["transaction_date"] = json!("2023-03-01");
+    let (status, _) = post_json(&app, "/api/v1/transactions", first).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let mut next_year = transfer_body("INV-001", &cash, &revenue, "10.00");
+    next_year["transaction_date"] = json!("2024-03-01");
+    let (status, body) = post_json(&app, "/api/v1/transactions", next_year).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "Reference INV-001 already exists");
+}
+
+#[actix_rt::test]
+async fn test_references_recur_across_periods_when_scoped_by_period() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        reference_scope: "period".to_string(),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    for (name, start, end) in [
+        ("FY2023", "2023-01-01", "2023-12-31"),
+        ("FY2024", "2024-01-01", "2024-12-31"),
+    ] {
+        let (status, _) = post_json(
+            &app,
+            "/api/v1/periods",
+            json!({ "name": name, "start_date": start, "end_date": end }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let dated = |date: &str| {
+        let mut body = transfer_body("INV-001", &cash, &revenue, "10.00");
+        body["transaction_date"] = json!(date);
+        body
+    };
+
+    let (status, _) = post_json(&app, "/api/v1/transactions", dated("2023-03-01")).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, body) = post_json(&app, "/api/v1/transactions", dated("2024-03-01")).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    let (status, body) = post_json(&app, "/api/v1/transactions", dated("2024-06-01")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["message"],
+        "Reference INV-001 already exists in period FY2024"
+    );
+
+    let (status, _) = post_json(&app, "/api/v1/transactions", dated("2025-01-15")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_transaction_list_includes_entry_aggregates() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let (status, _) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "SPLIT-1",
+            "description": "Split sale",
+            "entries": [
+                { "account_id": cash, "debit_amount": "60.00", "credit_amount": null },
+                { "account_id": bank, "debit_amount": "40.00", "credit_amount": null },
+                { "account_id": revenue, "debit_amount": null, "credit_amount": "100.00" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    post_transfer(&app, "SALE-1", &cash, &revenue, "12.50").await;
+
+    let (status, body) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(status, StatusCode::OK);
+    let listed = body["data"].as_array().unwrap();
+    let split = listed.iter().find(|t| t["reference"] == "SPLIT-1").unwrap();
+    assert_eq!(split["entry_count"], 3);
+    assert_eq!(split["total_amount"], "100.00");
+    assert_eq!(split["description"], "Split sale");
+
+    let sale = listed.iter().find(|t| t["reference"] == "SALE-1").unwrap();
+    assert_eq!(sale["entry_count"], 2);
+    assert_eq!(sale["total_amount"], "12.50");
+}
+
+#[actix_rt::test]
+async fn test_replacing_entries_of_a_draft() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut draft = transfer_body("DRAFT-1", &cash, &revenue, "10.00");
+    draft["status"] = json!("draft");
+    let (_, created) = post_json(&app, "/api/v1/transactions", draft).await;
+    let id = created["data"]["id"].as_str().unwrap();
+    let uri = format!("/api/v1/transactions/{}/entries", id);
+
+    let (status, _) = put_json(
+        &app,
+        &uri,
+        json!({ "entries": [
+            { "account_id": bank, "debit_amount": "25.00", "credit_amount": null },
+            { "account_id": revenue, "debit_amount": null, "credit_amount": "20.00" }
+        ] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = put_json(
+        &app,
+        &uri,
+        json!({ "entries": [
+            { "account_id": bank, "debit_amount": "25.00", "credit_amount": null },
+            { "account_id": revenue, "debit_amount": null, "credit_amount": "25.00" }
+        ] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let entries = body["data"]["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e["account_id"] != cash.as_str()));
+    assert!(entries
+        .iter()
+        .any(|e| e["account_id"] == bank.as_str() && e["debit_amount"] == "25.00"));
+
+    let (_, listed) = get_json(&app, &format!("/api/v1/entries?account_id={}", cash)).await;
+    assert_eq!(listed["data"], json!([]));
+}
+
+#[actix_rt::test]
+async fn test_replacing_entries_of_a_posted_transaction_rejected() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let posted = post_transfer(&app, "SALE-1", &cash, &revenue, "10.00").await;
+
+    let (status, body) = put_json(
+        &app,
+        &format!(
+            "/api/v1/transactions/{}/entries",
+            posted["id"].as_str().unwrap()
+        ),
+        json!({ "entries": [
+            { "account_id": cash, "debit_amount": "99.00", "credit_amount": null },
+            { "account_id": revenue, "debit_amount": null, "credit_amount": "99.00" }
+        ] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["message"],
+        "Only draft transactions can have their entries replaced"
+    );
+
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(balance["data"]["balance"], "10.00");
+}
+
+#[actix_rt::test]
+async fn test_fixed_clock_makes_timestamps_deterministic() {
+    use chrono::{Duration, TimeZone, Utc};
+    use double_rust_ledger::clock::FixedClock;
+    use std::sync::Arc;
+
+    let pool = test_pool();
+    let clock = Arc::new(FixedClock::new(
+        Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap(),
+    ));
+    let app = init_app!(pool, Default::default(), clock.clone());
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let (_, account) = get_json(&app, &format!("/api/v1/accounts/{}", cash)).await;
+    assert_eq!(account["data"]["created_at"], "2024-01-31T12:00:00+00:00");
+
+    clock.advance(Duration::hours(1));
+    let transaction = post_transfer(&app, "SALE-1", &cash, &revenue, "10.00").await;
+    assert_eq!(transaction["created_at"], "2024-01-31T13:00:00+00:00");
+    assert_eq!(transaction["transaction_date"], "2024-01-31T13:00:00+00:00");
+    assert!(transaction["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|e| e["created_at"] == "2024-01-31T13:00:00+00:00"));
+
+    let (_, audit) = get_json(&app, "/api/v1/admin/audit?entity_type=transaction").await;
+    assert_eq!(
+        audit["data"]["items"][0]["created_at"],
+        "2024-01-31T13:00:00+00:00"
+    );
+}
+
+#[actix_rt::test]
+async fn test_transactions_sort_by_each_key() {
+    use chrono::{Duration, TimeZone, Utc};
+    use double_rust_ledger::clock::FixedClock;
+    use std::sync::Arc;
+
+    let pool = test_pool();
+    let clock = Arc::new(FixedClock::new(
+        Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap(),
+    ));
+    let app = init_app!(pool, Default::default(), clock.clone());
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut ids = Vec::new();
+    for (reference, date) in [
+        ("C-REF", "2024-01-10"),
+        ("A-REF", "2024-01-30"),
+        ("B-REF", "2024-01-20"),
+    ] {
+        let mut body = transfer_body(reference, &cash, &revenue, "10.00");
+        body["transaction_date"] = json!(date);
+        let (status, created) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(created["data"]["id"].as_str().unwrap().to_string());
+        clock.advance(Duration::minutes(1));
+    }
+    let (t1, t2, t3) = (&ids[0], &ids[1], &ids[2]);
+
+    for (sort_by, expected) in [
+        ("reference", [t2, t3, t1]),
+        ("transaction_date", [t1, t3, t2]),
+        ("created_at", [t1, t2, t3]),
+        ("updated_at", [t1, t2, t3]),
+    ] {
+        let (status, body) =
+            get_json(&app, &format!("/api/v1/transactions?sort_by={}", sort_by)).await;
+        assert_eq!(status, StatusCode::OK);
+        let listed: Vec<&str> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|transaction| transaction["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(listed, expected, "sort_by={}", sort_by);
+
+        let (_, body) = get_json(
+            &app,
+            &format!("/api/v1/transactions?sort_by={}&order=desc", sort_by),
+        )
+        .await;
+        assert_eq!(body["data"][0]["id"], expected[2].as_str());
+    }
+
+    let (status, _) = get_json(&app, "/api/v1/transactions?sort_by=amount").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_entries_share_the_transaction_timestamp() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut entries: Vec<serde_json::Value> = (0..12)
+        .map(|_| json!({ "account_id": cash, "debit_amount": "1.00" }))
+        .collect();
+    entries.push(json!({ "account_id": revenue, "credit_amount": "12.00" }));
+    let many_entries =
+        json!({ "reference": "SPLIT-1", "description": "Split", "entries": entries });
+
+    let (status, created) = post_json(&app, "/api/v1/transactions", many_entries).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, batch) = post_json(
+        &app,
+        "/api/v1/transactions/batch",
+        json!({ "transactions": [transfer_body("BATCH-1", &cash, &revenue, "5.00")] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let batch_id = batch["data"][0]["transaction_id"].as_str().unwrap();
+    let (_, batched) = get_json(&app, &format!("/api/v1/transactions/{}", batch_id)).await;
+
+    for transaction in [&created["data"], &batched["data"]] {
+        let entries = transaction["entries"].as_array().unwrap();
+        assert!(!entries.is_empty());
+        assert!(
+            entries
+                .iter()
+                .all(|e| e["created_at"] == transaction["created_at"]),
+            "{}",
+            transaction
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_transactions_by_accounts_are_distinct() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let expense = create_account(&app, "5000", "Rent", "expense").await;
+
+    let both = post_transfer(&app, "MOVE-1", &bank, &cash, "50.00").await;
+    let sale = post_transfer(&app, "SALE-1", &cash, &revenue, "20.00").await;
+    post_transfer(&app, "OTHER-1", &expense, &revenue, "5.00").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions/by-accounts",
+        json!({ "account_ids": [cash, bank] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let ids: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids.len(), 2, "{}", body);
+    assert!(ids.contains(&both["id"].as_str().unwrap()));
+    assert!(ids.contains(&sale["id"].as_str().unwrap()));
+    assert_eq!(body["meta"]["total"], 2);
+
+    let (status, _) = post_json(
+        &app,
+        "/api/v1/transactions/by-accounts",
+        json!({ "account_ids": [] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_transactions_by_accounts_date_range() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    for (reference, date) in [
+        ("JAN", "2024-01-15T10:00:00Z"),
+        ("FEB", "2024-02-29T18:00:00Z"),
+        ("MAR", "2024-03-01T09:00:00Z"),
+    ] {
+        let mut body = transfer_body(reference, &cash, &revenue, "10.00");
+        body["transaction_date"] = json!(date);
+        let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    // A bare `to` date covers that whole day
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions/by-accounts",
+        json!({ "account_ids": [cash], "from": "2024-02-01", "to": "2024-02-29" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["meta"]["total"], 1);
+    assert_eq!(body["data"][0]["reference"], "FEB");
+
+    for range in [
+        json!({ "from": "yesterday" }),
+        json!({ "to": "2024-13-01" }),
+        json!({ "from": "2024-03-01", "to": "2024-02-01" }),
+    ] {
+        let mut body = range.clone();
+        body["account_ids"] = json!([cash]);
+        let (status, _) = post_json(&app, "/api/v1/transactions/by-accounts", body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", range);
+    }
+}
+
+#[actix_rt::test]
+async fn test_ids_are_uuids_by_default() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let sale = post_transfer(&app, "SALE-1", &cash, &revenue, "20.00").await;
+
+    for id in [
+        cash.as_str(),
+        revenue.as_str(),
+        sale["id"].as_str().unwrap(),
+    ] {
+        assert!(uuid::Uuid::parse_str(id).is_ok(), "{} is not a UUID", id);
+    }
+}
+
+#[actix_rt::test]
+async fn test_sequential_ids_are_prefixed_and_counted_per_kind() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        id_format: "sequential".to_string(),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let first = post_transfer(&app, "SALE-1", &cash, &revenue, "20.00").await;
+    let second = post_transfer(&app, "SALE-2", &cash, &revenue, "5.00").await;
+
+    assert_eq!(cash, "acc_000001");
+    assert_eq!(revenue, "acc_000002");
+    assert_eq!(first["id"], "txn_000001");
+    assert_eq!(second["id"], "txn_000002");
+
+    let (status, body) = get_json(&app, "/api/v1/transactions/txn_000002").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["reference"], "SALE-2");
+}
+
+#[actix_rt::test]
+async fn test_database_rejects_over_length_values() {
+    use diesel::prelude::*;
+    use double_rust_ledger::errors::AppError;
+    use double_rust_ledger::schema::{accounts, transactions};
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let sale = post_transfer(&app, "SALE-1", &cash, &revenue, "20.00").await;
+
+    // Writes that bypass the request validators, as a bulk loader would
+    let mut conn = pool.get().unwrap();
+    let errors = [
+        diesel::update(transactions::table.find(sale["id"].as_str().unwrap()))
+            .set(transactions::reference.eq("R".repeat(51)))
+            .execute(&mut conn)
+            .unwrap_err(),
+        diesel::update(transactions::table.find(sale["id"].as_str().unwrap()))
+            .set(transactions::description.eq("D".repeat(501)))
+            .execute(&mut conn)
+            .unwrap_err(),
+        diesel::update(accounts::table.find(&cash))
+            .set(accounts::code.eq("1".repeat(21)))
+            .execute(&mut conn)
+            .unwrap_err(),
+    ];
+
+    let messages: Vec<String> = errors
+        .into_iter()
+        .map(|error| match AppError::from(error) {
+            AppError::ValidationError(message) => message,
+            other => panic!("expected a validation error, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "reference: exceeds the maximum length",
+            "description: exceeds the maximum length",
+            "code: exceeds the maximum length",
+        ]
+    );
+
+    // Values at the limit are still accepted
+    diesel::update(accounts::table.find(&cash))
+        .set(accounts::code.eq("1".repeat(20)))
+        .execute(&mut conn)
+        .unwrap();
+}
+
+#[actix_rt::test]
+async fn test_transactions_over_the_cap_need_approval() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        max_transaction_amount: Some(rust_decimal::Decimal::new(1000, 0)),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    post_transfer(&app, "SALE-1", &cash, &revenue, "1000.00").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("SALE-2", &cash, &revenue, "1000.01"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["message"],
+        "transaction exceeds limit; approval required"
+    );
+
+    let req = actix_web::test::TestRequest::post()
+        .uri("/api/v1/transactions")
+        .insert_header(("X-Ledger-Approval", "true"))
+        .set_json(transfer_body("SALE-2", &cash, &revenue, "1000.01"))
+        .to_request();
+    let (status, body) = send(&app, req).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+}
+
+#[actix_rt::test]
+async fn test_oversized_amounts_rejected_without_totalling() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    // Two of the largest decimals would overflow the debit total
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "HUGE-1",
+            "description": "Oversized",
+            "entries": [
+                { "account_id": cash, "debit_amount": "79228162514264337593543950335" },
+                { "account_id": cash, "debit_amount": "79228162514264337593543950335" },
+                { "account_id": revenue, "credit_amount": "1.00" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let errors: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e.as_str().unwrap())
+        .collect();
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert!(errors.iter().all(|e| e.contains("debit_amount")), "{:?}", errors);
+}
+
+#[actix_rt::test]
+async fn test_skip_existing_returns_matching_transaction() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut body = transfer_body("IMP-1", &cash, &revenue, "25.00");
+    body["transaction_date"] = json!("2024-05-01");
+
+    let (status, created) = post_json(
+        &app,
+        "/api/v1/transactions?skip_existing=true",
+        body.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, existing) = post_json(
+        &app,
+        "/api/v1/transactions?skip_existing=true",
+        body.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(existing["data"]["id"], created["data"]["id"]);
+    assert_eq!(existing["data"]["entries"].as_array().unwrap().len(), 2);
+
+    // Without the flag the duplicate reference is still refused
+    let (status, _) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (_, list) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(list["data"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn test_metadata_is_persisted_and_filterable() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut ids = Vec::new();
+    for (reference, metadata) in [
+        (
+            "META-1",
+            json!({ "source": "stripe", "external_id": "ch_1" }),
+        ),
+        ("META-2", json!({ "source": "shopify", "external_id": 42 })),
+    ] {
+        let mut body = transfer_body(reference, &cash, &revenue, "10.00");
+        body["metadata"] = metadata;
+        let (status, body) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+        ids.push(body["data"]["id"].as_str().unwrap().to_string());
+    }
+    post_transfer(&app, "PLAIN-1", &cash, &revenue, "5.00").await;
+
+    let (_, body) = get_json(&app, &format!("/api/v1/transactions/{}", ids[0])).await;
+    assert_eq!(
+        body["data"]["metadata"],
+        json!({ "source": "stripe", "external_id": "ch_1" })
+    );
+
+    let (status, body) = get_json(&app, "/api/v1/transactions?metadata.source=stripe").await;
+    assert_eq!(status, StatusCode::OK);
+    let listed = body["data"].as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["reference"], "META-1");
+    assert_eq!(listed[0]["metadata"]["source"], "stripe");
+
+    // Numbers match their text, and every filter must hold
+    let (_, body) = get_json(
+        &app,
+        "/api/v1/transactions?metadata.source=shopify&metadata.external_id=42",
+    )
+    .await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    let (_, body) = get_json(
+        &app,
+        "/api/v1/transactions?metadata.source=stripe&metadata.external_id=42",
+    )
+    .await;
+    assert!(body["data"].as_array().unwrap().is_empty());
+
+    let (_, body) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 3);
+
+    let mut body = transfer_body("META-3", &cash, &revenue, "10.00");
+    body["metadata"] = json!(["not", "an", "object"]);
+    let (status, body) = post_json(&app, "/api/v1/transactions", body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["errors"], json!(["metadata: must be a JSON object"]));
+}
+
+#[actix_rt::test]
+async fn test_create_returns_its_own_transaction_when_references_repeat() {
+    let pool = test_pool();
+    let config = double_rust_ledger::config::AppConfig {
+        reference_scope: "period".to_string(),
+        ..Default::default()
+    };
+    let app = init_app!(pool, config);
+
+    for (name, start, end) in [
+        ("FY2023", "2023-01-01", "2023-12-31"),
+        ("FY2024", "2024-01-01", "2024-12-31"),
+    ] {
+        let (status, _) = post_json(
+            &app,
+            "/api/v1/periods",
+            json!({ "name": name, "start_date": start, "end_date": end }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let mut ids = Vec::new();
+    for (date, amount) in [("2023-03-01", "10.00"), ("2024-03-01", "25.00")] {
+        let mut body = transfer_body("INV-001", &cash, &revenue, amount);
+        body["transaction_date"] = json!(date);
+        let (status, body) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+        let created = &body["data"];
+        assert_eq!(created["transaction_date"], date);
+        let entries = created["entries"].as_array().unwrap();
+        assert!(entries
+            .iter()
+            .all(|entry| entry["transaction_id"] == created["id"]));
+        let debit = entries
+            .iter()
+            .find(|entry| entry["account_id"] == cash.as_str())
+            .unwrap();
+        assert_eq!(debit["debit_amount"], amount);
+        ids.push(created["id"].as_str().unwrap().to_string());
+    }
+
+    assert_ne!(ids[0], ids[1]);
+}
+
+#[actix_rt::test]
+async fn test_transaction_list_pages() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    for n in 0..12 {
+        post_transfer(&app, &format!("TXN-{:02}", n), &cash, &revenue, "1.00").await;
+    }
+
+    let (status, body) = get_json(
+        &app,
+        "/api/v1/transactions?sort_by=reference&order=asc&limit=5&offset=5",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let references: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|transaction| transaction["reference"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        references,
+        ["TXN-05", "TXN-06", "TXN-07", "TXN-08", "TXN-09"]
+    );
+    assert_eq!(
+        body["meta"],
+        json!({ "total": 12, "limit": 5, "offset": 5 })
+    );
+    assert_eq!(body["data"][0]["entry_count"], 2);
+
+    let (status, _) = get_json(&app, "/api/v1/transactions?offset=-1").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_reversal_nets_balances_to_zero() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "SALE-1",
+            "description": "Split sale",
+            "entries": [
+                { "account_id": cash, "debit_amount": "40.00" },
+                { "account_id": bank, "debit_amount": "60.00" },
+                { "account_id": revenue, "credit_amount": "100.00" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let original_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let uri = format!("/api/v1/transactions/{}/reverse", original_id);
+    let (status, body) = post_json(&app, &uri, json!({})).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    let reversal = &body["data"];
+    assert_eq!(reversal["reference"], "REV-SALE-1");
+    assert_eq!(reversal["description"], "Reversal of SALE-1: Split sale");
+    assert_eq!(reversal["reverses_transaction_id"], original_id.as_str());
+
+    // Same accounts, each amount on the other side
+    let entries = reversal["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    for (account, debit, credit) in [
+        (&cash, "0.00", "40.00"),
+        (&bank, "0.00", "60.00"),
+        (&revenue, "100.00", "0.00"),
+    ] {
+        let entry = entries
+            .iter()
+            .find(|entry| entry["account_id"] == account.as_str())
+            .unwrap();
+        assert_eq!(entry["debit_amount"], debit, "{}", entry);
+        assert_eq!(entry["credit_amount"], credit, "{}", entry);
+    }
+
+    for account in [&cash, &bank, &revenue] {
+        let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", account)).await;
+        assert_eq!(body["data"]["balance"], "0.00", "{}", account);
+    }
+
+    let (status, body) = post_json(&app, &uri, json!({})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["message"],
+        format!(
+            "Transaction is already reversed by {}",
+            reversal["id"].as_str().unwrap()
+        )
+    );
+
+    let (status, _) = post_json(&app, "/api/v1/transactions/missing/reverse", json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn test_entries_must_post_to_active_accounts() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("SALE-1", &cash, "no-such-account", "10.00"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Unknown or inactive account: no-such-account"
+    );
+
+    let (status, _) = put_json(
+        &app,
+        &format!("/api/v1/accounts/{}", revenue),
+        json!({ "is_active": false }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("SALE-2", &cash, &revenue, "10.00"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        format!("Unknown or inactive account: {}", revenue)
+    );
+
+    // Nothing was written for either attempt
+    let (_, body) = get_json(&app, "/api/v1/transactions").await;
+    assert!(body["data"].as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_multi_currency_transaction_balances_per_currency() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    let euro_bank = create_account_with(
+        &app,
+        json!({ "code": "1100", "name": "Euro Bank", "account_type": "asset", "currency": "eur" }),
+    )
+    .await;
+    let euro_revenue = create_account_with(
+        &app,
+        json!({ "code": "4100", "name": "Euro Sales", "account_type": "revenue", "currency": "EUR" }),
+    )
+    .await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "FX-1",
+            "description": "Sales in two currencies",
+            "entries": [
+                { "account_id": cash, "debit_amount": "100.00" },
+                { "account_id": revenue, "credit_amount": "100.00" },
+                { "account_id": euro_bank, "debit_amount": "50.00", "currency": "EUR" },
+                { "account_id": euro_revenue, "credit_amount": "50.00" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let entries = body["data"]["entries"].as_array().unwrap();
+    let currency_of = |account_id: &str| {
+        entries
+            .iter()
+            .find(|entry| entry["account_id"] == account_id)
+            .unwrap()["currency"]
+            .clone()
+    };
+    assert_eq!(currency_of(&cash), "USD");
+    assert_eq!(currency_of(&revenue), "USD");
+    assert_eq!(currency_of(&euro_bank), "EUR");
+    assert_eq!(currency_of(&euro_revenue), "EUR");
+
+    // An entry may not restate its account in another currency
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "FX-2",
+            "description": "Wrong currency",
+            "entries": [
+                { "account_id": cash, "debit_amount": "10.00", "currency": "EUR" },
+                { "account_id": revenue, "credit_amount": "10.00" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Entry currency EUR does not match account 1000 currency USD"
+    );
+}
+
+#[actix_rt::test]
+async fn test_amounts_render_at_their_own_currency_scale() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let dinar_cash = create_account_with(
+        &app,
+        json!({ "code": "1200", "name": "Dinar Cash", "account_type": "asset", "currency": "KWD" }),
+    )
+    .await;
+    let dinar_sales = create_account_with(
+        &app,
+        json!({ "code": "4200", "name": "Dinar Sales", "account_type": "revenue", "currency": "KWD" }),
+    )
+    .await;
+
+    let transaction = post_transfer(&app, "KWD-1", &dinar_cash, &dinar_sales, "1.234").await;
+    let debit = transaction["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["account_id"] == dinar_cash.as_str())
+        .unwrap();
+    assert_eq!(debit["debit_amount"], "1.234");
+    assert_eq!(debit["credit_amount"], "0.000");
+
+    let (_, body) = get_json(&app, &format!("/api/v1/balance/{}", dinar_cash)).await;
+    assert_eq!(body["data"]["balance"], "1.234");
+
+    let (_, body) = get_json(&app, "/api/v1/reports/trial-balance").await;
+    assert_eq!(body["data"]["total_debits"], "1.234");
+    assert_eq!(body["data"]["totals"][0]["total_credits"], "1.234");
+}
+
+#[actix_rt::test]
+async fn test_amount_finer_than_currency_rejected() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("FINE-1", &cash, &revenue, "10.005"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "entries[0].debit_amount: 10.005 has more decimal places than USD allows (2)"
+    );
+
+    // Trailing zeros past the minor units are not extra precision
+    let (status, _) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("FINE-2", &cash, &revenue, "10.0000"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[actix_rt::test]
+async fn test_transaction_unbalanced_in_one_currency_rejected() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let euro_revenue = create_account_with(
+        &app,
+        json!({ "code": "4100", "name": "Euro Sales", "account_type": "revenue", "currency": "EUR" }),
+    )
+    .await;
+
+    // Debits equal credits overall, but neither currency balances on its own
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("FX-1", &cash, &euro_revenue, "100.00"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Total debits must equal total credits in each currency; unbalanced: EUR, USD"
+    );
+
+    let (_, body) = get_json(&app, "/api/v1/transactions").await;
+    assert!(body["data"].as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_idempotency_key_returns_the_original_transaction() {
+    use chrono::{Duration, TimeZone, Utc};
+    use double_rust_ledger::clock::FixedClock;
+    use double_rust_ledger::config::AppConfig;
+    use std::sync::Arc;
+
+    let pool = test_pool();
+    let clock = Arc::new(FixedClock::new(
+        Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap(),
+    ));
+    let config = AppConfig {
+        idempotency_key_ttl_secs: 3600,
+        ..Default::default()
+    };
+    let app = init_app!(pool, config, clock.clone());
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    let post_with_key = |key: &str, body: Value| {
+        actix_web::test::TestRequest::post()
+            .uri("/api/v1/transactions")
+            .insert_header(("Idempotency-Key", key))
+            .set_json(body)
+            .to_request()
+    };
+
+    let body = transfer_body("SALE-1", &cash, &revenue, "10.00");
+    let (status, first) = send(&app, post_with_key("retry-1", body.clone())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, second) = send(&app, post_with_key("retry-1", body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second["data"]["id"], first["data"]["id"]);
+
+    let (_, list) = get_json(&app, "/api/v1/transactions").await;
+    assert_eq!(list["data"].as_array().unwrap().len(), 1);
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", cash)).await;
+    assert_eq!(balance["data"]["balance"], "10.00");
+
+    // Once the key expires it creates a new transaction
+    clock.advance(Duration::hours(2));
+    let (status, third) = send(
+        &app,
+        post_with_key("retry-1", transfer_body("SALE-2", &cash, &revenue, "5.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_ne!(third["data"]["id"], first["data"]["id"]);
+
+    let (status, _) = send(
+        &app,
+        post_with_key("", transfer_body("SALE-3", &cash, &revenue, "5.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_amounts_are_stored_without_precision_loss() {
+    use diesel::prelude::*;
+    use double_rust_ledger::schema::entries;
+
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let bank = create_account(&app, "1100", "Bank", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+
+    // 0.1 + 0.2 must balance 0.3 exactly
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        json!({
+            "reference": "SPLIT-1",
+            "description": "Split sale",
+            "entries": [
+                { "account_id": cash, "debit_amount": "0.1" },
+                { "account_id": bank, "debit_amount": "0.2" },
+                { "account_id": revenue, "credit_amount": "0.3" }
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    let transaction_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let (_, balance) = get_json(&app, &format!("/api/v1/balance/{}", revenue)).await;
+    assert_eq!(balance["data"]["balance"], "0.30");
+    let (_, verification) = get_json(
+        &app,
+        &format!("/api/v1/transactions/{}/verify", transaction_id),
+    )
+    .await;
+    assert_eq!(verification["data"]["is_balanced"], true);
+
+    // Stored as integers at four decimal places
+    let mut conn = pool.get().unwrap();
+    let mut stored: Vec<(i64, i64)> = entries::table
+        .filter(entries::transaction_id.eq(&transaction_id))
+        .select((entries::debit_amount, entries::credit_amount))
+        .load(&mut conn)
+        .unwrap();
+    stored.sort();
+    assert_eq!(stored, vec![(0, 3000), (1000, 0), (2000, 0)]);
+
+    // A fifth decimal place cannot be stored, so it is refused rather than rounded
+    let (status, body) = post_json(
+        &app,
+        "/api/v1/transactions",
+        transfer_body("TINY-1", &cash, &revenue, "0.00001"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["errors"][0],
+        "entries[0].debit_amount: 0.00001 has more than 4 decimal places"
+    );
+}
+
+#[actix_rt::test]
+async fn test_search_matches_reference_or_description() {
+    let pool = test_pool();
+    let app = init_app!(pool);
+
+    let cash = create_account(&app, "1000", "Cash", "asset").await;
+    let revenue = create_account(&app, "4000", "Sales", "revenue").await;
+    for (reference, description, date) in [
+        ("INV-100", "Consulting for ACME Corp", "2024-01-10"),
+        ("INV-101", "Widgets", "2024-02-15"),
+        ("ACME-REFUND", "Refund", "2024-03-01"),
+        ("INV-102", "Rent", "2024-03-05"),
+        ("INV_103", "100% discount", "2024-03-06"),
+    ] {
+        let mut body = transfer_body(reference, &cash, &revenue, "10.00");
+        body["description"] = json!(description);
+        body["transaction_date"] = json!(date);
+        let (status, body) = post_json(&app, "/api/v1/transactions", body).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+    }
+
+    let search = |query: &'static str| {
+        let app = &app;
+        async move {
+            let (status, body) =
+                get_json(app, &format!("/api/v1/transactions/search?{}", query)).await;
+            assert_eq!(status, StatusCode::OK, "{}", body);
+            let mut references: Vec<String> = body["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|transaction| transaction["reference"].as_str().unwrap().to_string())
+                .collect();
+            references.sort();
+            (references, body["meta"]["total"].clone())
+        }
+    };
+
+    let (references, total) = search("q=acme").await;
+    assert_eq!(references, ["ACME-REFUND", "INV-100"]);
+    assert_eq!(total, 2);
+
+    let (references, _) = search("q=acme&from=2024-02-01").await;
+    assert_eq!(references, ["ACME-REFUND"]);
+
+    let (references, _) = search("q=inv&from=2024-02-01&to=2024-03-05").await;
+    assert_eq!(references, ["INV-101", "INV-102"]);
+
+    // Wildcard characters in the query match literally
+    let (references, _) = search("q=%25").await;
+    assert_eq!(references, ["INV_103"]);
+    let (references, _) = search("q=V_1").await;
+    assert_eq!(references, ["INV_103"]);
+
+    let (references, total) = search("q=inv&limit=2&offset=2").await;
+    assert_eq!(references.len(), 2);
+    assert_eq!(total, 4);
+
+    let (status, _) = get_json(&app, "/api/v1/transactions/search?q=%20").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}